@@ -0,0 +1,149 @@
+-- Схема PostgreSQL; соответствует схеме SQLite после миграции 015.
+-- Время хранится в TIMESTAMPTZ, флаги - в BOOLEAN
+
+CREATE TABLE users (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    email TEXT NOT NULL CONSTRAINT users_email_key UNIQUE,
+    role TEXT NOT NULL,
+    time_zone TEXT NOT NULL DEFAULT 'Europe/Moscow',
+    version BIGINT NOT NULL DEFAULT 1,
+    deleted_at TIMESTAMPTZ,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    deactivated_at TIMESTAMPTZ
+);
+
+-- Ключ имени тега (name_key) вычисляется в приложении: без учета регистра,
+-- пробелов и ё/е
+CREATE TABLE tags (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL CONSTRAINT tags_name_key UNIQUE,
+    name_key TEXT NOT NULL CONSTRAINT tags_name_key_key UNIQUE,
+    color TEXT,
+    description TEXT
+);
+
+CREATE TABLE projects (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL,
+    owner_id TEXT NOT NULL REFERENCES users (id),
+    customer_id TEXT REFERENCES users (id),
+    status TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    version BIGINT NOT NULL DEFAULT 1
+);
+
+CREATE TABLE tasks (
+    id TEXT PRIMARY KEY,
+    title TEXT NOT NULL,
+    description TEXT NOT NULL,
+    status TEXT NOT NULL,
+    priority TEXT NOT NULL,
+    customer_id TEXT NOT NULL REFERENCES users (id),
+    executor_id TEXT NOT NULL REFERENCES users (id),
+    created_at TIMESTAMPTZ NOT NULL,
+    due_date TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    version BIGINT NOT NULL DEFAULT 1,
+    deleted_at TIMESTAMPTZ,
+    unarchived_at TIMESTAMPTZ,
+    project_id TEXT REFERENCES projects (id),
+    estimate_minutes BIGINT,
+    rank BIGINT NOT NULL DEFAULT 0
+);
+
+CREATE TABLE task_tags (
+    task_id TEXT NOT NULL REFERENCES tasks (id),
+    tag_id TEXT NOT NULL REFERENCES tags (id),
+    PRIMARY KEY (task_id, tag_id)
+);
+
+-- Соисполнители и наблюдатели; seq задает порядок добавления.
+-- Ссылка на задачу не ограничена внешним ключом: участники сохраняются
+-- и при переносе задачи в архив
+CREATE TABLE task_participants (
+    task_id TEXT NOT NULL,
+    user_id TEXT NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    role TEXT NOT NULL,
+    seq BIGSERIAL NOT NULL,
+    PRIMARY KEY (task_id, user_id)
+);
+
+-- Цепочка передач задачи между исполнителями
+CREATE TABLE task_delegations (
+    id TEXT PRIMARY KEY,
+    task_id TEXT NOT NULL,
+    from_user_id TEXT NOT NULL REFERENCES users (id),
+    to_user_id TEXT NOT NULL REFERENCES users (id),
+    by_user_id TEXT NOT NULL REFERENCES users (id),
+    reason TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    seq BIGSERIAL NOT NULL
+);
+
+CREATE INDEX idx_task_delegations_task ON task_delegations (task_id);
+
+-- Ограничения числа задач в колонках доски (WIP-лимиты)
+CREATE TABLE wip_limits (
+    status TEXT PRIMARY KEY,
+    wip_limit BIGINT NOT NULL
+);
+
+-- Сохраненные виды списка задач; условия фильтрации и колонки хранятся в JSON
+CREATE TABLE saved_views (
+    id TEXT PRIMARY KEY,
+    owner_id TEXT NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    filters TEXT NOT NULL,
+    sort TEXT,
+    columns TEXT NOT NULL,
+    shared BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE default_views (
+    user_id TEXT PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE,
+    view_id TEXT NOT NULL REFERENCES saved_views (id) ON DELETE CASCADE
+);
+
+-- Учет времени по задачам. Запись без ended_at - запущенный таймер;
+-- записи остаются у архивных задач
+CREATE TABLE time_entries (
+    id TEXT PRIMARY KEY,
+    task_id TEXT NOT NULL,
+    user_id TEXT NOT NULL REFERENCES users (id),
+    started_at TIMESTAMPTZ NOT NULL,
+    ended_at TIMESTAMPTZ,
+    minutes BIGINT NOT NULL DEFAULT 0,
+    note TEXT NOT NULL DEFAULT ''
+);
+
+CREATE INDEX idx_time_entries_task ON time_entries (task_id);
+CREATE INDEX idx_time_entries_user ON time_entries (user_id, started_at);
+
+-- Архив завершенных задач: та же структура, что у tasks и task_tags
+CREATE TABLE archived_tasks (
+    id TEXT PRIMARY KEY,
+    title TEXT NOT NULL,
+    description TEXT NOT NULL,
+    status TEXT NOT NULL,
+    priority TEXT NOT NULL,
+    customer_id TEXT NOT NULL,
+    executor_id TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    due_date TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    version BIGINT NOT NULL DEFAULT 1,
+    deleted_at TIMESTAMPTZ,
+    project_id TEXT,
+    estimate_minutes BIGINT,
+    rank BIGINT NOT NULL DEFAULT 0,
+    archived_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE archived_task_tags (
+    task_id TEXT NOT NULL REFERENCES archived_tasks (id),
+    tag_id TEXT NOT NULL REFERENCES tags (id),
+    PRIMARY KEY (task_id, tag_id)
+);
@@ -0,0 +1,2 @@
+-- Язык пользователя (ru, en); NULL - язык выбирается по запросу
+ALTER TABLE users ADD COLUMN locale TEXT;
@@ -0,0 +1,5 @@
+-- Время отмены задачи: от него отсчитывается срок архивации отмененных задач.
+-- Для ранее отмененных задач оно неизвестно и берется равным времени миграции
+ALTER TABLE tasks ADD COLUMN cancelled_at TIMESTAMPTZ;
+ALTER TABLE archived_tasks ADD COLUMN cancelled_at TIMESTAMPTZ;
+UPDATE tasks SET cancelled_at = now() WHERE status = 'cancelled';
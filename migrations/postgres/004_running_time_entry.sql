@@ -0,0 +1,4 @@
+-- У пользователя не больше одного запущенного таймера (записи без ended_at),
+-- даже при одновременных запросах на запуск. Имя по шаблону <таблица>_<колонка>_key,
+-- чтобы нарушение описывалось как "UNIQUE constraint failed: time_entries.user_id"
+CREATE UNIQUE INDEX time_entries_user_id_key ON time_entries (user_id) WHERE ended_at IS NULL;
@@ -0,0 +1,191 @@
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{web, HttpResponse, Responder, get, post};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::calendar::timezone::format_local;
+use crate::calendar::BusinessCalendar;
+use crate::clock::Clock;
+use crate::db::{ArchiveFilter, Repository};
+use crate::i18n::Locale;
+use crate::models::{Task, User};
+use super::tasks::{executor_tz, load_executor_tz, TaskResponse};
+use super::{etag, ApiError, ErrorResponse};
+
+// Параметры поиска по архиву; пустые значения игнорируются
+#[derive(Deserialize, IntoParams, ToSchema)]
+#[into_params(parameter_in = Query)]
+pub struct ArchiveQuery {
+    pub search: Option<String>, // Подстрока названия или описания
+    pub status: Option<String>, // completed или cancelled
+    pub customer_id: Option<String>,
+    pub executor_id: Option<String>,
+    pub tag: Option<String>,
+    pub from: Option<NaiveDate>, // Дата завершения (UTC), включительно
+    pub to: Option<NaiveDate>,   // Дата завершения (UTC), включительно
+}
+
+impl ArchiveQuery {
+    fn filter(&self) -> ArchiveFilter {
+        let non_empty = |value: &Option<String>| {
+            value.as_ref().map(|v| v.trim()).filter(|v| !v.is_empty()).map(str::to_string)
+        };
+        let start_of_day = |date: NaiveDate| date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+
+        ArchiveFilter {
+            search: non_empty(&self.search),
+            status: non_empty(&self.status),
+            customer_id: non_empty(&self.customer_id),
+            executor_id: non_empty(&self.executor_id),
+            tag: non_empty(&self.tag),
+            finished_from: self.from.map(start_of_day),
+            finished_to: self.to.and_then(|d| d.succ_opt()).map(start_of_day),
+        }
+    }
+}
+
+#[utoipa::path(
+    tag = "archive",
+    params(ArchiveQuery),
+    responses((status = 200, description = "Архивные задачи", body = [TaskResponse]))
+)]
+#[get("/archive")]
+pub async fn get_archive(
+    repo: web::Data<Arc<dyn Repository>>,
+    calendar: web::Data<BusinessCalendar>,
+    clock: web::Data<Arc<dyn Clock>>,
+    query: web::Query<ArchiveQuery>,
+) -> Result<impl Responder, ApiError> {
+    let now = clock.now();
+    let zones = all_users(repo.get_ref().as_ref())?
+        .iter()
+        .map(|u| (u.id.clone(), u.tz()))
+        .collect();
+    let tasks: Vec<TaskResponse> = repo.get_archived_tasks(&query.filter())?
+        .into_iter()
+        .map(|task| {
+            let tz = executor_tz(&zones, &task);
+            TaskResponse::new(task, &calendar, tz, now)
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(tasks))
+}
+
+// Выгрузка архива в CSV с подписями на языке запроса
+#[utoipa::path(
+    tag = "archive",
+    params(ArchiveQuery),
+    responses((status = 200, description = "Архивные задачи в формате CSV", content_type = "text/csv", body = String))
+)]
+#[get("/archive/export")]
+pub async fn export_archive(
+    repo: web::Data<Arc<dyn Repository>>,
+    query: web::Query<ArchiveQuery>,
+    locale: Locale,
+) -> Result<impl Responder, ApiError> {
+    let tasks = repo.get_archived_tasks(&query.filter())?;
+
+    let users = all_users(repo.get_ref().as_ref())?;
+    let names: HashMap<String, String> = users.iter().map(|u| (u.id.clone(), u.name.clone())).collect();
+    let zones = users.iter().map(|u| (u.id.clone(), u.tz())).collect();
+
+    let csv = archive_csv(&tasks, &names, &zones, locale)
+        .map_err(|e| ApiError::Internal(format!("CSV export error: {}", e)))?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename("archive.csv".to_string())],
+        })
+        .body(csv))
+}
+
+#[utoipa::path(
+    tag = "archive",
+    params(("id" = String, Path, description = "Идентификатор задачи")),
+    responses(
+        (status = 200, description = "Задача возвращена из архива", body = TaskResponse),
+        (status = 404, description = "Задача не найдена в архиве", body = ErrorResponse)
+    )
+)]
+#[post("/archive/tasks/{id}/restore")]
+pub async fn unarchive_task(
+    repo: web::Data<Arc<dyn Repository>>,
+    calendar: web::Data<BusinessCalendar>,
+    clock: web::Data<Arc<dyn Clock>>,
+    path: web::Path<String>,
+) -> Result<impl Responder, ApiError> {
+    let now = clock.now();
+    let id = path.into_inner();
+    repo.unarchive_task(&id, now)?;
+
+    let task = repo.get_task_by_id(&id)?;
+    let tz = load_executor_tz(repo.get_ref().as_ref(), &task);
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(task.version))
+        .json(TaskResponse::new(task, &calendar, tz, now)))
+}
+
+// Архивные задачи могут ссылаться на удаленных пользователей
+pub fn all_users(repo: &dyn Repository) -> Result<Vec<User>, ApiError> {
+    let mut users = repo.get_users()?;
+    users.extend(repo.get_deleted_users()?);
+    Ok(users)
+}
+
+fn archive_csv(
+    tasks: &[Task],
+    names: &HashMap<String, String>,
+    zones: &HashMap<String, chrono_tz::Tz>,
+    locale: Locale,
+) -> Result<String, csv::Error> {
+    // BOM нужен, чтобы Excel распознал UTF-8
+    let mut writer = csv::Writer::from_writer("\u{feff}".as_bytes().to_vec());
+
+    writer.write_record([
+        locale.t("tasks.title"),
+        locale.t("tasks.description"),
+        locale.t("tasks.status"),
+        locale.t("tasks.priority"),
+        locale.t("tasks.customer"),
+        locale.t("tasks.executor"),
+        locale.t("archive.created_at"),
+        locale.t("tasks.due_date"),
+        locale.t("archive.completed_at"),
+        locale.t("archive.archived_at"),
+        locale.t("common.tags"),
+    ])?;
+
+    for task in tasks {
+        let tz = executor_tz(zones, task);
+        let local = |dt: Option<DateTime<Utc>>| dt.map(|d| format_local(d, tz)).unwrap_or_default();
+        let name = |id: &String| names.get(id).cloned().unwrap_or_else(|| id.clone());
+
+        writer.write_record([
+            task.title.clone(),
+            task.description.clone(),
+            task.status.label(locale),
+            task.priority.label(locale),
+            name(&task.customer_id),
+            name(&task.executor_id),
+            local(Some(task.created_at)),
+            local(task.due_date),
+            local(task.completed_at),
+            local(task.archived_at),
+            task.tags.iter().map(|t| t.name.as_str()).collect::<Vec<_>>().join(", "),
+        ])?;
+    }
+
+    let bytes = writer.into_inner().map_err(|e| e.into_error())?;
+    Ok(String::from_utf8(bytes).expect("CSV is built from UTF-8 strings"))
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_archive)
+       .service(export_archive)
+       .service(unarchive_task);
+}
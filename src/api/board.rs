@@ -0,0 +1,186 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder, get, post, put};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use std::sync::Arc;
+
+use crate::calendar::BusinessCalendar;
+use crate::clock::Clock;
+use crate::db::Repository;
+use crate::models::TaskStatus;
+use super::tasks::{apply_status, executor_tz, load_executor_tz, user_time_zones, TaskResponse};
+use super::{check_if_match, etag, ApiError, ErrorResponse};
+
+// Фильтры доски; пустые значения игнорируются
+#[derive(Deserialize, IntoParams, ToSchema)]
+#[into_params(parameter_in = Query)]
+pub struct BoardQuery {
+    pub project_id: Option<String>,
+    pub executor_id: Option<String>,
+}
+
+// Колонка доски: задачи одного статуса в порядке rank
+#[derive(Serialize, ToSchema)]
+pub struct BoardColumn {
+    pub status: String,
+    pub wip_limit: Option<u32>,
+    pub count: usize, // Число задач в колонке без учета фильтров доски
+    pub tasks: Vec<TaskResponse>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BoardResponse {
+    pub columns: Vec<BoardColumn>,
+}
+
+// Перенос карточки: новый статус и карточка, перед которой она встает;
+// без before_id карточка встает в конец колонки
+#[derive(Deserialize, ToSchema)]
+pub struct MoveTaskRequest {
+    pub status: String,
+    pub before_id: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct WipLimitRequest {
+    pub wip_limit: Option<u32>, // null снимает ограничение
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct WipLimitResponse {
+    pub status: String,
+    pub wip_limit: Option<u32>,
+}
+
+#[utoipa::path(
+    tag = "board",
+    params(BoardQuery),
+    responses((status = 200, description = "Доска: по колонке на каждый статус", body = BoardResponse))
+)]
+#[get("/board")]
+pub async fn get_board(
+    repo: web::Data<Arc<dyn Repository>>,
+    calendar: web::Data<BusinessCalendar>,
+    clock: web::Data<Arc<dyn Clock>>,
+    query: web::Query<BoardQuery>,
+) -> Result<impl Responder, ApiError> {
+    let now = clock.now();
+    let non_empty = |value: &Option<String>| value.clone().filter(|v| !v.trim().is_empty());
+    let project_id = non_empty(&query.project_id);
+    let executor_id = non_empty(&query.executor_id);
+
+    let zones = user_time_zones(repo.get_ref().as_ref())?;
+    let limits = repo.get_wip_limits()?;
+    let mut tasks = repo.get_tasks()?;
+    tasks.sort_by(|a, b| a.rank.cmp(&b.rank).then(a.created_at.cmp(&b.created_at)));
+
+    let columns = TaskStatus::ALL.iter()
+        .map(|status| {
+            let column: Vec<_> = tasks.iter().filter(|t| t.status == *status).collect();
+            BoardColumn {
+                status: status.code().to_string(),
+                wip_limit: limits.get(status.code()).copied(),
+                count: column.len(),
+                tasks: column.into_iter()
+                    .filter(|t| project_id.is_none() || t.project_id == project_id)
+                    .filter(|t| executor_id.as_ref().is_none_or(|id| t.executor_id == *id))
+                    .map(|t| TaskResponse::new(t.clone(), &calendar, executor_tz(&zones, t), now))
+                    .collect(),
+            }
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(BoardResponse { columns }))
+}
+
+#[utoipa::path(
+    tag = "board",
+    params(
+        ("id" = String, Path, description = "Идентификатор задачи"),
+        ("If-Match" = String, Header, description = "Версия задачи из заголовка ETag")
+    ),
+    request_body = MoveTaskRequest,
+    responses(
+        (status = 200, description = "Карточка перенесена", body = TaskResponse),
+        (status = 400, description = "Некорректный статус", body = ErrorResponse),
+        (status = 404, description = "Задача не найдена", body = ErrorResponse),
+        (status = 409, description = "Колонка заполнена до WIP-лимита", body = ErrorResponse),
+        (status = 412, description = "Задача изменена другим пользователем", body = ErrorResponse),
+        (status = 428, description = "Не передан заголовок If-Match", body = ErrorResponse)
+    )
+)]
+#[post("/tasks/{id}/move")]
+pub async fn move_task(
+    repo: web::Data<Arc<dyn Repository>>,
+    calendar: web::Data<BusinessCalendar>,
+    clock: web::Data<Arc<dyn Clock>>,
+    path: web::Path<String>,
+    http_req: HttpRequest,
+    req: web::Json<MoveTaskRequest>,
+) -> Result<impl Responder, ApiError> {
+    let now = clock.now();
+    let id = path.into_inner();
+
+    let mut task = repo.get_task_by_id(&id)?;
+    check_if_match(&http_req, task.version)?;
+
+    // Смена статуса проверяется так же, как при обновлении задачи
+    apply_status(repo.get_ref().as_ref(), &mut task, &req.status, now)?;
+
+    task.rank = repo.move_task(&task, req.before_id.as_deref())?;
+    task.version += 1;
+
+    let tz = load_executor_tz(repo.get_ref().as_ref(), &task);
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(task.version))
+        .json(TaskResponse::new(task, &calendar, tz, now)))
+}
+
+#[utoipa::path(
+    tag = "board",
+    responses((status = 200, description = "WIP-лимиты колонок", body = [WipLimitResponse]))
+)]
+#[get("/board/limits")]
+pub async fn get_wip_limits(repo: web::Data<Arc<dyn Repository>>) -> Result<impl Responder, ApiError> {
+    let limits = repo.get_wip_limits()?;
+    let response: Vec<WipLimitResponse> = TaskStatus::ALL.iter()
+        .map(|status| WipLimitResponse {
+            status: status.code().to_string(),
+            wip_limit: limits.get(status.code()).copied(),
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[utoipa::path(
+    tag = "board",
+    params(("status" = String, Path, description = "Код статуса колонки")),
+    request_body = WipLimitRequest,
+    responses(
+        (status = 200, description = "Лимит изменен", body = WipLimitResponse),
+        (status = 400, description = "Неизвестный статус или нулевой лимит", body = ErrorResponse)
+    )
+)]
+#[put("/board/limits/{status}")]
+pub async fn set_wip_limit(
+    repo: web::Data<Arc<dyn Repository>>,
+    path: web::Path<String>,
+    req: web::Json<WipLimitRequest>,
+) -> Result<impl Responder, ApiError> {
+    let status = path.into_inner();
+    if !TaskStatus::ALL.iter().any(|s| s.code() == status) {
+        return Err(ApiError::BadRequest("errors.invalid_status".to_string()));
+    }
+    if req.wip_limit == Some(0) {
+        return Err(ApiError::BadRequest("errors.invalid_wip_limit".to_string()));
+    }
+
+    repo.set_wip_limit(&status, req.wip_limit)?;
+    Ok(HttpResponse::Ok().json(WipLimitResponse { status, wip_limit: req.wip_limit }))
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_board)
+       .service(move_task)
+       .service(get_wip_limits)
+       .service(set_wip_limit);
+}
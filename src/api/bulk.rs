@@ -0,0 +1,293 @@
+use actix_web::{web, HttpResponse, Responder, post};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use chrono_tz::Tz;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::calendar::timezone::DueDateInput;
+use crate::clock::Clock;
+use crate::db::{Repository, TaskChange};
+use crate::i18n::Locale;
+use crate::models::tag::normalize_tag_name;
+use crate::models::{Task, ViewFilters};
+use crate::validation::{self, Validator};
+use super::tasks::{
+    executor_tz, parse_priority, parse_status, prepare_reassign, resolve_tags, set_status,
+    user_time_zones, ReassignRequest,
+};
+use super::users::check_assignable;
+use super::{ApiError, ErrorResponse};
+
+// Наибольшее число задач в одном групповом изменении
+const MAX_BULK_TASKS: usize = 500;
+
+// Групповое изменение: задачи задаются списком идентификаторов или условиями
+// фильтра (как у сохраненного вида) - одним из двух способов
+#[derive(Deserialize, ToSchema)]
+pub struct BulkRequest {
+    pub ids: Option<Vec<String>>,
+    pub filter: Option<ViewFilters>,
+    pub operation: BulkOperation,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BulkOperation {
+    SetStatus { status: String },
+    SetPriority { priority: String },
+    Reassign { to_user_id: String, by_user_id: String, reason: String },
+    AddTags { tags: Vec<String> },
+    RemoveTags { tags: Vec<String> },
+    SetDueDate { due_date: Option<DueDateInput> }, // null снимает срок
+    Delete,
+}
+
+// Результат по одной задаче; ошибка указывает, почему задачу нельзя изменить
+#[derive(Serialize, ToSchema)]
+pub struct BulkItemResult {
+    pub id: String,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<i64>, // Новая версия задачи; у удаленной задачи отсутствует
+}
+
+// applied = false: хотя бы одну задачу изменить нельзя, и не изменена ни одна
+#[derive(Serialize, ToSchema)]
+pub struct BulkResponse {
+    pub applied: bool,
+    pub results: Vec<BulkItemResult>,
+}
+
+#[utoipa::path(
+    tag = "tasks",
+    request_body = BulkRequest,
+    responses(
+        (status = 200, description = "Изменение применено ко всем задачам", body = BulkResponse),
+        (status = 400, description = "Некорректная операция или выборка задач", body = ErrorResponse),
+        (status = 404, description = "Пользователь из операции не найден", body = ErrorResponse),
+        (status = 409, description = "Часть задач изменить нельзя; не изменена ни одна", body = BulkResponse),
+        (status = 412, description = "Задача изменена другим пользователем во время применения", body = ErrorResponse),
+        (status = 422, description = "Имена тегов не прошли проверку", body = ErrorResponse)
+    )
+)]
+#[post("/tasks/bulk")]
+pub async fn bulk_update_tasks(
+    repo: web::Data<Arc<dyn Repository>>,
+    clock: web::Data<Arc<dyn Clock>>,
+    locale: Locale,
+    req: web::Json<BulkRequest>,
+) -> Result<impl Responder, ApiError> {
+    let req = req.into_inner();
+    let repo = repo.get_ref().as_ref();
+    check_operation(repo, &req.operation)?;
+
+    // Задачи выборки; отсутствующие задачи попадают в результаты с ошибкой
+    let selected: Vec<(String, Result<Task, ApiError>)> = match (req.ids, req.filter) {
+        (Some(ids), None) => {
+            let mut seen = HashSet::new();
+            ids.into_iter()
+                .filter(|id| seen.insert(id.clone()))
+                .map(|id| {
+                    let task = repo.get_task_by_id(&id).map_err(ApiError::from);
+                    (id, task)
+                })
+                .collect()
+        }
+        (None, Some(filter)) => repo.get_tasks()?
+            .into_iter()
+            .filter(|task| filter.matches(task))
+            .map(|task| (task.id.clone(), Ok(task)))
+            .collect(),
+        _ => return Err(ApiError::BadRequest("errors.bulk_selection".to_string())),
+    };
+    if selected.is_empty() {
+        return Err(ApiError::BadRequest("errors.bulk_empty".to_string()));
+    }
+    if selected.len() > MAX_BULK_TASKS {
+        return Err(ApiError::BadRequest("errors.bulk_too_many".to_string()));
+    }
+
+    let now = clock.now();
+    let mut planner = Planner::new(repo, &req.operation, &selected, now)?;
+    let planned: Vec<(String, Result<TaskChange, ApiError>)> = selected.into_iter()
+        .map(|(id, task)| {
+            let change = task.and_then(|task| planner.plan(task));
+            (id, change)
+        })
+        .collect();
+
+    if planned.iter().any(|(_, change)| change.is_err()) {
+        let results = planned.into_iter()
+            .map(|(id, change)| match change {
+                Ok(_) => BulkItemResult { id, ok: true, error: None, version: None },
+                Err(err) => BulkItemResult { id, ok: false, error: Some(err.localized_message(locale)), version: None },
+            })
+            .collect();
+        return Ok(HttpResponse::Conflict().json(BulkResponse { applied: false, results }));
+    }
+
+    let mut changes: Vec<TaskChange> = planned.into_iter().filter_map(|(_, change)| change.ok()).collect();
+    // Теги создаются только после того, как все задачи прошли проверку
+    if let BulkOperation::AddTags { tags } = &req.operation {
+        let names: Vec<String> = tags.iter().filter(|tag| !tag.trim().is_empty()).cloned().collect();
+        let added = resolve_tags(repo, &names)?;
+        for change in &mut changes {
+            if let TaskChange::Update(task) = change {
+                for tag in &added {
+                    if !task.tags.iter().any(|t| t.id == tag.id) {
+                        task.tags.push(tag.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    repo.apply_task_changes(&changes, now)?;
+
+    let results = changes.iter()
+        .map(|change| match change {
+            TaskChange::Update(task) | TaskChange::Reassign(task, _) => BulkItemResult {
+                id: task.id.clone(),
+                ok: true,
+                error: None,
+                version: Some(task.version + 1),
+            },
+            TaskChange::Delete(task) => BulkItemResult { id: task.id.clone(), ok: true, error: None, version: None },
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(BulkResponse { applied: true, results }))
+}
+
+// Проверки операции, не зависящие от задач
+fn check_operation(repo: &dyn Repository, operation: &BulkOperation) -> Result<(), ApiError> {
+    match operation {
+        BulkOperation::SetStatus { status } => {
+            parse_status(status)?;
+        }
+        BulkOperation::SetPriority { priority } => {
+            parse_priority(priority)?;
+        }
+        BulkOperation::Reassign { to_user_id, by_user_id, reason } => {
+            if reason.trim().is_empty() {
+                return Err(ApiError::BadRequest("errors.reassign_reason_required".to_string()));
+            }
+            check_assignable(&repo.get_user_by_id(to_user_id)?)?;
+            repo.get_user_by_id(by_user_id)?;
+        }
+        BulkOperation::AddTags { tags } | BulkOperation::RemoveTags { tags } => {
+            if tags.iter().all(|tag| tag.trim().is_empty()) {
+                return Err(ApiError::BadRequest("errors.bulk_tags_required".to_string()));
+            }
+            let mut v = Validator::new();
+            validation::task_tags(&mut v, tags);
+            v.finish().map_err(ApiError::Validation)?;
+        }
+        BulkOperation::SetDueDate { .. } | BulkOperation::Delete => {}
+    }
+    Ok(())
+}
+
+// Готовит изменение каждой задачи с теми же проверками, что и одиночные запросы
+struct Planner<'a> {
+    operation: &'a BulkOperation,
+    zones: HashMap<String, Tz>,
+    // Свободные места в колонке нового статуса, если у нее есть WIP-лимит
+    wip_free: Option<usize>,
+    // Один момент времени для всех задач выборки
+    now: DateTime<Utc>,
+}
+
+impl<'a> Planner<'a> {
+    fn new(
+        repo: &dyn Repository,
+        operation: &'a BulkOperation,
+        selected: &[(String, Result<Task, ApiError>)],
+        now: DateTime<Utc>,
+    ) -> Result<Self, ApiError> {
+        let zones = match operation {
+            BulkOperation::SetDueDate { .. } => user_time_zones(repo)?,
+            _ => Default::default(),
+        };
+
+        let mut wip_free = None;
+        if let BulkOperation::SetStatus { status } = operation {
+            if let Some(&limit) = repo.get_wip_limits()?.get(status.as_str()) {
+                // Задачи выборки освобождают места в колонке, из которой уходят
+                let status = parse_status(status)?;
+                let ids: HashSet<&str> = selected.iter().map(|(id, _)| id.as_str()).collect();
+                let staying = repo.get_tasks()?
+                    .iter()
+                    .filter(|t| t.status == status && !ids.contains(t.id.as_str()))
+                    .count();
+                let moving_within = selected.iter()
+                    .filter(|(_, task)| task.as_ref().is_ok_and(|t| t.status == status))
+                    .count();
+                wip_free = Some((limit as usize).saturating_sub(staying + moving_within));
+            }
+        }
+
+        Ok(Self { operation, zones, wip_free, now })
+    }
+
+    fn plan(&mut self, mut task: Task) -> Result<TaskChange, ApiError> {
+        match self.operation {
+            BulkOperation::SetStatus { status } => {
+                let status = parse_status(status)?;
+                if status != task.status {
+                    if let Some(free) = self.wip_free.as_mut() {
+                        if *free == 0 {
+                            return Err(ApiError::Conflict("errors.wip_limit_reached".to_string()));
+                        }
+                        *free -= 1;
+                    }
+                }
+                set_status(&mut task, status, self.now);
+            }
+            BulkOperation::SetPriority { priority } => task.priority = parse_priority(priority)?,
+            BulkOperation::Reassign { to_user_id, by_user_id, reason } => {
+                let req = ReassignRequest {
+                    to_user_id: to_user_id.clone(),
+                    by_user_id: by_user_id.clone(),
+                    reason: reason.clone(),
+                };
+                let delegation = prepare_reassign(&task, &req, self.now)?;
+                return Ok(TaskChange::Reassign(task, delegation));
+            }
+            // Теги добавляются после проверки всей выборки; здесь проверяется,
+            // что вместе с имеющимися их не станет больше допустимого
+            BulkOperation::AddTags { tags } => {
+                let keys: HashSet<String> = task.tags.iter()
+                    .map(|tag| normalize_tag_name(&tag.name))
+                    .chain(tags.iter().filter(|tag| !tag.trim().is_empty()).map(|tag| normalize_tag_name(tag)))
+                    .collect();
+                let mut v = Validator::new();
+                v.max_items("tags", keys.len(), validation::TAGS_MAX);
+                v.finish().map_err(ApiError::Validation)?;
+            }
+            BulkOperation::RemoveTags { tags } => {
+                let keys: Vec<String> = tags.iter().map(|tag| normalize_tag_name(tag)).collect();
+                task.tags.retain(|tag| !keys.contains(&normalize_tag_name(&tag.name)));
+            }
+            BulkOperation::SetDueDate { due_date } => {
+                // Конец дня зависит от пояса исполнителя, поэтому срок
+                // проверяется для каждой задачи отдельно
+                let tz = executor_tz(&self.zones, &task);
+                let due_date = due_date.as_ref().map(|due| due.resolve(tz));
+                let mut v = Validator::new();
+                v.not_in_past("due_date", due_date, self.now);
+                v.finish().map_err(ApiError::Validation)?;
+                task.due_date = due_date;
+            }
+            BulkOperation::Delete => return Ok(TaskChange::Delete(task)),
+        }
+        Ok(TaskChange::Update(task))
+    }
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(bulk_update_tasks);
+}
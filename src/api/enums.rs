@@ -0,0 +1,51 @@
+use actix_web::{web, HttpResponse, Responder, get};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::i18n::Locale;
+use crate::models::{ProjectStatus, TaskPriority, TaskStatus, UserRole};
+
+#[derive(Serialize, ToSchema)]
+pub struct EnumOption {
+    pub value: &'static str,
+    pub label: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct EnumsResponse {
+    pub locale: Locale,
+    pub status: Vec<EnumOption>,
+    pub priority: Vec<EnumOption>,
+    pub role: Vec<EnumOption>,
+    pub project_status: Vec<EnumOption>,
+}
+
+// Значения перечислений с подписями на языке запроса
+#[utoipa::path(
+    tag = "enums",
+    responses((status = 200, description = "Значения перечислений с подписями", body = EnumsResponse))
+)]
+#[get("/enums")]
+pub async fn get_enums(locale: Locale) -> impl Responder {
+    let response = EnumsResponse {
+        locale,
+        status: TaskStatus::ALL.iter()
+            .map(|s| EnumOption { value: s.code(), label: s.label(locale) })
+            .collect(),
+        priority: TaskPriority::ALL.iter()
+            .map(|p| EnumOption { value: p.code(), label: p.label(locale) })
+            .collect(),
+        role: UserRole::ALL.iter()
+            .map(|r| EnumOption { value: r.code(), label: r.label(locale) })
+            .collect(),
+        project_status: ProjectStatus::ALL.iter()
+            .map(|s| EnumOption { value: s.code(), label: s.label(locale) })
+            .collect(),
+    };
+
+    HttpResponse::Ok().json(response)
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_enums);
+}
@@ -1,53 +1,347 @@
+pub mod archive;
+pub mod board;
+pub mod bulk;
+pub mod enums;
+pub mod openapi;
+pub mod projects;
+pub mod reports;
+pub mod tags;
 pub mod tasks;
+pub mod time_entries;
+pub mod trash;
 pub mod users;
+pub mod views;
 
-use actix_web::{HttpResponse, ResponseError};
-use serde::Serialize;
+use actix_web::dev::ServiceResponse;
+use actix_web::error::{JsonPayloadError, PathError, QueryPayloadError};
+use actix_web::http::header::{EntityTag, Header, HeaderName, HeaderValue, IfMatch};
+use actix_web::{web, HttpRequest, HttpResponse, ResponseError};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::fmt;
+use utoipa::ToSchema;
+use uuid::Uuid;
 
 use crate::db::RepositoryError;
+use crate::i18n::Locale;
+use crate::validation::FieldError;
 
-#[derive(Serialize)]
-struct ErrorResponse {
-    error: String,
+// Заголовок с идентификатором запроса; переданный клиентом идентификатор сохраняется
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+#[derive(Serialize, ToSchema)]
+pub struct ErrorResponse {
+    pub error: String,
+    // Стабильный код ошибки: ключ каталога без "errors." ("invalid_priority")
+    // или вид ошибки ("not_found"), если сообщение не из каталога
+    pub code: String,
+    // Подробности ошибки разбора запроса (некорректный JSON, параметр пути)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    // Идентификатор запроса из заголовка X-Request-Id
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    // Текущая версия записи при конфликте изменений (412)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_version: Option<i64>,
+    // Ошибки отдельных полей при проверке входных данных (422)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
 }
 
 #[derive(Debug)]
 pub enum ApiError {
     NotFound(String),
+    // Запись не найдена: в сообщении каталога {id} заменяется идентификатором записи
+    MissingRecord { message: String, id: String },
     BadRequest(String),
+    // Запрос не удалось разобрать: тело, путь или параметры не соответствуют ожидаемым
+    Malformed { message: String, detail: String },
+    Conflict(String),
+    PreconditionRequired(String),
+    PreconditionFailed { message: String, current_version: i64 },
+    Validation(Vec<FieldError>),
     Internal(String),
 }
 
+// Сообщения об ошибках задаются ключами каталога ("errors.invalid_priority")
+// или готовым текстом; перевод выполняется при формировании ответа
 impl fmt::Display for ApiError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ApiError::NotFound(msg) => write!(f, "Not found: {}", msg),
-            ApiError::BadRequest(msg) => write!(f, "Bad request: {}", msg),
-            ApiError::Internal(msg) => write!(f, "Internal error: {}", msg),
+            ApiError::NotFound(msg) => write!(f, "Not found: {}", Locale::En.translate(msg)),
+            ApiError::MissingRecord { message, id } => {
+                write!(f, "Not found: {}", Locale::En.translate_with(message, &[("id", id)]))
+            }
+            ApiError::BadRequest(msg) => write!(f, "Bad request: {}", Locale::En.translate(msg)),
+            ApiError::Malformed { message, detail } => {
+                write!(f, "Bad request: {} ({})", Locale::En.translate(message), detail)
+            }
+            ApiError::Conflict(msg) => write!(f, "Conflict: {}", Locale::En.translate(msg)),
+            ApiError::PreconditionRequired(msg) => {
+                write!(f, "Precondition required: {}", Locale::En.translate(msg))
+            }
+            ApiError::PreconditionFailed { message, current_version } => write!(
+                f,
+                "Precondition failed: {} (current version {})",
+                Locale::En.translate(message),
+                current_version
+            ),
+            ApiError::Validation(errors) => {
+                let fields: Vec<String> = errors.iter().map(|e| format!("{} ({})", e.field, e.code)).collect();
+                write!(f, "Validation failed: {}", fields.join(", "))
+            }
+            ApiError::Internal(msg) => write!(f, "Internal error: {}", Locale::En.translate(msg)),
         }
     }
 }
 
-impl ResponseError for ApiError {
-    fn error_response(&self) -> HttpResponse {
-        let error = self.to_string();
-        let response = ErrorResponse { error };
-        
+impl ApiError {
+    // Код ошибки для клиентов: не зависит от языка и текста сообщения
+    pub fn code(&self) -> String {
+        let (kind, msg) = match self {
+            ApiError::NotFound(msg) => ("not_found", Some(msg)),
+            ApiError::MissingRecord { .. } => ("not_found", None),
+            ApiError::BadRequest(msg) => ("bad_request", Some(msg)),
+            ApiError::Malformed { message, .. } => ("bad_request", Some(message)),
+            ApiError::Conflict(msg) => ("conflict", Some(msg)),
+            ApiError::PreconditionRequired(msg) => ("precondition_required", Some(msg)),
+            ApiError::PreconditionFailed { message, .. } => ("precondition_failed", Some(message)),
+            ApiError::Validation(_) => ("validation", None),
+            ApiError::Internal(_) => ("internal", None),
+        };
+        msg.and_then(|msg| msg.strip_prefix("errors."))
+            .unwrap_or(kind)
+            .to_string()
+    }
+
+    pub fn localized_message(&self, locale: Locale) -> String {
+        let (prefix, msg) = match self {
+            ApiError::NotFound(msg) => ("errors.not_found", msg),
+            ApiError::MissingRecord { message, id } => {
+                return format!("{}: {}", locale.t("errors.not_found"), locale.translate_with(message, &[("id", id)]));
+            }
+            ApiError::BadRequest(msg) => ("errors.bad_request", msg),
+            ApiError::Malformed { message, .. } => ("errors.bad_request", message),
+            ApiError::Conflict(msg) => ("errors.conflict", msg),
+            ApiError::PreconditionRequired(msg) => ("errors.precondition_required", msg),
+            ApiError::PreconditionFailed { message, .. } => ("errors.precondition_failed", message),
+            ApiError::Validation(errors) => {
+                let messages: Vec<String> = errors.iter().map(|e| e.localized(locale).message).collect();
+                return format!("{}: {}", locale.t("errors.validation"), messages.join("; "));
+            }
+            ApiError::Internal(msg) => ("errors.internal", msg),
+        };
+        format!("{}: {}", locale.t(prefix), locale.translate(msg))
+    }
+
+    pub fn localized_response(&self, locale: Locale, request_id: Option<&str>) -> HttpResponse {
+        let current_version = match self {
+            ApiError::PreconditionFailed { current_version, .. } => Some(*current_version),
+            _ => None,
+        };
+        let detail = match self {
+            ApiError::Malformed { detail, .. } => Some(detail.clone()),
+            _ => None,
+        };
+        let errors = match self {
+            ApiError::Validation(errors) => errors.iter().map(|e| e.localized(locale)).collect(),
+            _ => Vec::new(),
+        };
+        let response = ErrorResponse {
+            error: self.localized_message(locale),
+            code: self.code(),
+            detail,
+            request_id: request_id.map(str::to_string),
+            current_version,
+            errors,
+        };
+
         match self {
-            ApiError::NotFound(_) => HttpResponse::NotFound().json(response),
-            ApiError::BadRequest(_) => HttpResponse::BadRequest().json(response),
+            ApiError::NotFound(_) | ApiError::MissingRecord { .. } => HttpResponse::NotFound().json(response),
+            ApiError::BadRequest(_) | ApiError::Malformed { .. } => HttpResponse::BadRequest().json(response),
+            ApiError::Conflict(_) => HttpResponse::Conflict().json(response),
+            ApiError::PreconditionRequired(_) => HttpResponse::PreconditionRequired().json(response),
+            ApiError::PreconditionFailed { current_version, .. } => HttpResponse::PreconditionFailed()
+                .insert_header(etag(*current_version))
+                .json(response),
+            ApiError::Validation(_) => HttpResponse::UnprocessableEntity().json(response),
             ApiError::Internal(_) => HttpResponse::InternalServerError().json(response),
         }
     }
 }
 
+impl ResponseError for ApiError {
+    fn error_response(&self) -> HttpResponse {
+        self.localized_response(Locale::default(), None)
+    }
+}
+
+// Идентификатор запроса: из заголовка X-Request-Id, если он разумной длины
+// и состоит из допустимых символов, иначе новый
+pub fn request_id(req: &HttpRequest) -> String {
+    req.headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| {
+            (1..=64).contains(&id.len())
+                && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        })
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string())
+}
+
+// Приводит ответ API к единому виду: ошибка ApiError переводится на язык запроса,
+// прочие ошибки (не перехваченные обработчиками) оформляются так же JSON-ответом.
+// Каждый ответ получает заголовок X-Request-Id, ответ с ошибкой - и поле request_id.
+// Подключается в build_app через wrap_fn для области /api
+pub fn localize_error(res: ServiceResponse, locale: Locale, request_id: &str) -> ServiceResponse {
+    let localized = res.response().error().map(|err| match err.as_error::<ApiError>() {
+        Some(err) => err.localized_response(locale, Some(request_id)),
+        None => {
+            let status = res.status();
+            let err = if status.is_server_error() {
+                ApiError::Internal(err.to_string())
+            } else if status == actix_web::http::StatusCode::NOT_FOUND {
+                ApiError::NotFound(err.to_string())
+            } else {
+                ApiError::BadRequest(err.to_string())
+            };
+            err.localized_response(locale, Some(request_id))
+        }
+    });
+
+    let mut res = match localized {
+        Some(response) => {
+            // Внутренние ошибки записываются в журнал, чтобы найти их по идентификатору из ответа
+            if let Some(err) = res.response().error().filter(|_| response.status().is_server_error()) {
+                eprintln!("Ошибка запроса {}: {}", request_id, err);
+            }
+            res.into_response(response)
+        }
+        None => res,
+    };
+    if let Ok(value) = HeaderValue::from_str(request_id) {
+        res.headers_mut().insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+    }
+    res
+}
+
+// Обработчики ошибок извлечения данных запроса: вместо текстовых ответов actix
+// возвращается ErrorResponse с кодом invalid_json, invalid_path или invalid_query
+pub fn json_config() -> web::JsonConfig {
+    web::JsonConfig::default().error_handler(|err: JsonPayloadError, _| {
+        let message = match err {
+            JsonPayloadError::ContentType => "errors.invalid_content_type",
+            JsonPayloadError::Overflow { .. } | JsonPayloadError::OverflowKnownLength { .. } => "errors.payload_too_large",
+            _ => "errors.invalid_json",
+        };
+        ApiError::Malformed { message: message.to_string(), detail: err.to_string() }.into()
+    })
+}
+
+pub fn path_config() -> web::PathConfig {
+    web::PathConfig::default().error_handler(|err: PathError, _| {
+        ApiError::Malformed { message: "errors.invalid_path".to_string(), detail: err.to_string() }.into()
+    })
+}
+
+pub fn query_config() -> web::QueryConfig {
+    web::QueryConfig::default().error_handler(|err: QueryPayloadError, _| {
+        ApiError::Malformed { message: "errors.invalid_query".to_string(), detail: err.to_string() }.into()
+    })
+}
+
+// Ответ на запрос к несуществующему адресу API
+pub async fn route_not_found() -> Result<HttpResponse, ApiError> {
+    Err(ApiError::NotFound("errors.route_not_found".to_string()))
+}
+
 impl From<RepositoryError> for ApiError {
     fn from(err: RepositoryError) -> Self {
         match err {
-            RepositoryError::NotFound(msg) => ApiError::NotFound(msg),
+            RepositoryError::NotFound { entity, id } => ApiError::MissingRecord {
+                message: format!("errors.missing.{}", entity.code()),
+                id,
+            },
+            RepositoryError::VersionConflict { current, .. } => ApiError::PreconditionFailed {
+                message: "errors.version_conflict".to_string(),
+                current_version: current,
+            },
+            RepositoryError::Constraint(msg) => ApiError::Conflict(constraint_message(&msg).to_string()),
             RepositoryError::Database(err) => ApiError::Internal(format!("Database error: {}", err)),
+            #[cfg(feature = "postgres")]
+            RepositoryError::Postgres(err) => ApiError::Internal(format!("Database error: {}", err)),
             RepositoryError::Internal(msg) => ApiError::Internal(msg),
         }
     }
 }
+
+// Ключ сообщения по тексту нарушенного ограничения SQLite
+fn constraint_message(constraint: &str) -> &'static str {
+    if constraint.contains("users.email") {
+        "errors.email_taken"
+    } else if constraint.contains("tags.name") {
+        "errors.tag_exists"
+    } else if constraint.contains("time_entries.user_id") {
+        "errors.timer_already_running"
+    } else if constraint.starts_with("UNIQUE") {
+        "errors.duplicate"
+    } else {
+        "errors.constraint_violation"
+    }
+}
+
+// Заголовок ETag для версии записи
+pub fn etag(version: i64) -> actix_web::http::header::ETag {
+    actix_web::http::header::ETag(EntityTag::new_strong(version.to_string()))
+}
+
+// Проверяет заголовок If-Match изменяющего запроса. Заголовок обязателен;
+// "*" допускает любую версию
+pub fn check_if_match(req: &HttpRequest, current_version: i64) -> Result<(), ApiError> {
+    if !req.headers().contains_key(IfMatch::name()) {
+        return Err(ApiError::PreconditionRequired("errors.if_match_required".to_string()));
+    }
+
+    let matches = match IfMatch::parse(req) {
+        Ok(IfMatch::Any) => true,
+        Ok(IfMatch::Items(tags)) => {
+            let current = EntityTag::new_strong(current_version.to_string());
+            tags.iter().any(|tag| tag.strong_eq(&current))
+        }
+        Err(_) => return Err(ApiError::BadRequest("errors.invalid_if_match".to_string())),
+    };
+
+    if !matches {
+        return Err(ApiError::PreconditionFailed {
+            message: "errors.version_conflict".to_string(),
+            current_version,
+        });
+    }
+
+    Ok(())
+}
+
+// Десериализация полей частичного обновления (JSON Merge Patch, RFC 7396).
+// Поле с #[serde(default, deserialize_with = "nullable")]: отсутствует - None,
+// null - Some(None) (значение очищается), иначе Some(Some(значение))
+pub fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+// Поле с #[serde(default, deserialize_with = "non_null")]: отсутствует - None,
+// null недопустим, так как поле обязательно
+pub fn non_null<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    match Option::<T>::deserialize(deserializer)? {
+        Some(value) => Ok(Some(value)),
+        None => Err(serde::de::Error::custom("field cannot be null")),
+    }
+}
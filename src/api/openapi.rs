@@ -0,0 +1,138 @@
+use actix_web::{web, HttpResponse, Responder, get};
+use utoipa::OpenApi;
+
+use super::{archive, board, bulk, enums, projects, reports, tags, tasks, time_entries, trash, users, views};
+
+// Описание API в формате OpenAPI 3, собираемое из аннотаций обработчиков
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Business Tasks API",
+        description = "API системы управления бизнес-поручениями"
+    ),
+    paths(
+        tasks::get_tasks,
+        tasks::get_task,
+        tasks::create_task,
+        tasks::update_task,
+        tasks::patch_task,
+        tasks::delete_task,
+        tasks::add_co_executor,
+        tasks::remove_co_executor,
+        tasks::add_watcher,
+        tasks::remove_watcher,
+        tasks::reassign_task,
+        tasks::get_task_delegations,
+        bulk::bulk_update_tasks,
+        board::get_board,
+        board::move_task,
+        board::get_wip_limits,
+        board::set_wip_limit,
+        time_entries::get_task_time_entries,
+        time_entries::create_time_entry,
+        time_entries::delete_time_entry,
+        time_entries::start_timer,
+        time_entries::stop_timer,
+        tags::get_tags,
+        tags::create_tag,
+        tags::update_tag,
+        tags::delete_tag,
+        tags::merge_tag,
+        users::get_users,
+        users::get_user,
+        users::create_user,
+        users::update_user,
+        users::patch_user,
+        users::delete_user,
+        users::deactivate_user,
+        users::reactivate_user,
+        users::get_user_tasks,
+        views::get_views,
+        views::get_view,
+        views::create_view,
+        views::update_view,
+        views::delete_view,
+        views::get_default_view,
+        views::set_default_view,
+        projects::get_projects,
+        projects::get_project,
+        projects::create_project,
+        projects::update_project,
+        projects::delete_project,
+        projects::get_project_tasks,
+        projects::get_project_stats,
+        trash::get_trash,
+        trash::restore_task,
+        trash::restore_user,
+        archive::get_archive,
+        archive::export_archive,
+        archive::unarchive_task,
+        reports::overdue_report,
+        reports::stats_report,
+        reports::delegation_report,
+        time_entries::timesheet_report,
+        time_entries::export_timesheet,
+        enums::get_enums,
+    ),
+    tags(
+        (name = "tasks", description = "Поручения"),
+        (name = "tags", description = "Теги"),
+        (name = "users", description = "Пользователи"),
+        (name = "board", description = "Доска задач"),
+        (name = "views", description = "Сохраненные виды списка поручений"),
+        (name = "projects", description = "Проекты"),
+        (name = "time", description = "Учет времени"),
+        (name = "trash", description = "Корзина"),
+        (name = "archive", description = "Архив"),
+        (name = "reports", description = "Отчеты"),
+        (name = "enums", description = "Справочники")
+    )
+)]
+struct ApiRoutes;
+
+// Полная спецификация с путями относительно корня сервера
+pub fn spec() -> utoipa::openapi::OpenApi {
+    let routes = ApiRoutes::openapi();
+    let mut doc = utoipa::openapi::OpenApi::new(routes.info.clone(), utoipa::openapi::Paths::new());
+    doc.tags = routes.tags.clone();
+    doc.nest("/api", routes)
+}
+
+#[get("/openapi.json")]
+pub async fn openapi_json() -> impl Responder {
+    HttpResponse::Ok().json(spec())
+}
+
+// Документация по /api/openapi.json; страница и ее скрипт отдаются приложением
+#[get("/docs")]
+pub async fn api_docs(tmpl: web::Data<tera::Tera>) -> impl Responder {
+    match tmpl.render("api_docs.html", &tera::Context::new()) {
+        Ok(rendered) => HttpResponse::Ok().content_type("text/html").body(rendered),
+        Err(e) => {
+            eprintln!("Ошибка рендеринга шаблона: {}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(openapi_json)
+       .service(api_docs);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spec_references_only_known_schemas() {
+        let doc = serde_json::to_string(&spec()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&doc).unwrap();
+        let schemas = value["components"]["schemas"].as_object().unwrap();
+
+        for reference in doc.split("\"$ref\":\"#/components/schemas/").skip(1) {
+            let name = reference.split('"').next().unwrap();
+            assert!(schemas.contains_key(name), "schema {} is not registered", name);
+        }
+    }
+}
@@ -0,0 +1,288 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder, get, post, put, delete};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use std::sync::Arc;
+
+use crate::calendar::BusinessCalendar;
+use crate::clock::Clock;
+use crate::db::Repository;
+use crate::models::{Project, ProjectStatus, Task, TaskStatus};
+use super::reports::{task_stats, StatsResponse};
+use super::tasks::{executor_tz, user_time_zones, TaskResponse};
+use super::{check_if_match, etag, ApiError, ErrorResponse};
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateProjectRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub owner_id: String,
+    pub customer_id: Option<String>,
+    pub status: Option<String>, // По умолчанию active
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct UpdateProjectRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub owner_id: String,
+    pub customer_id: Option<String>, // Отсутствие или null - проект без заказчика
+    pub status: String,
+}
+
+// Проект с ходом выполнения его задач
+#[derive(Serialize, ToSchema)]
+pub struct ProjectResponse {
+    #[serde(flatten)]
+    pub project: Project,
+    pub task_count: usize,
+    pub completed: usize,
+    pub overdue: usize,
+    pub progress: u8, // Доля завершенных задач без учета отмененных, в процентах
+}
+
+impl ProjectResponse {
+    pub fn new(project: Project, tasks: &[&Task], now: DateTime<Utc>) -> Self {
+        let completed = tasks.iter().filter(|t| t.status == TaskStatus::Completed).count();
+        let cancelled = tasks.iter().filter(|t| t.status == TaskStatus::Cancelled).count();
+        let overdue = tasks.iter().filter(|t| t.is_overdue(now)).count();
+
+        let planned = tasks.len() - cancelled;
+        let progress = (completed * 100).checked_div(planned).unwrap_or(0) as u8;
+
+        Self { project, task_count: tasks.len(), completed, overdue, progress }
+    }
+}
+
+#[utoipa::path(
+    tag = "projects",
+    responses((status = 200, description = "Список проектов", body = [ProjectResponse]))
+)]
+#[get("/projects")]
+pub async fn get_projects(
+    repo: web::Data<Arc<dyn Repository>>,
+    clock: web::Data<Arc<dyn Clock>>,
+) -> Result<impl Responder, ApiError> {
+    let now = clock.now();
+    let tasks = repo.get_tasks()?;
+    let projects: Vec<ProjectResponse> = repo.get_projects()?
+        .into_iter()
+        .map(|project| {
+            let own: Vec<&Task> = tasks.iter()
+                .filter(|t| t.project_id.as_deref() == Some(project.id.as_str()))
+                .collect();
+            ProjectResponse::new(project, &own, now)
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(projects))
+}
+
+#[utoipa::path(
+    tag = "projects",
+    params(("id" = String, Path, description = "Идентификатор проекта")),
+    responses(
+        (status = 200, description = "Проект; заголовок ETag содержит его версию", body = ProjectResponse),
+        (status = 404, description = "Проект не найден", body = ErrorResponse)
+    )
+)]
+#[get("/projects/{id}")]
+pub async fn get_project(
+    repo: web::Data<Arc<dyn Repository>>,
+    clock: web::Data<Arc<dyn Clock>>,
+    path: web::Path<String>,
+) -> Result<impl Responder, ApiError> {
+    let id = path.into_inner();
+    let project = repo.get_project_by_id(&id)?;
+    let tasks = repo.get_tasks_by_project(&id)?;
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(project.version))
+        .json(ProjectResponse::new(project, &tasks.iter().collect::<Vec<_>>(), clock.now())))
+}
+
+#[utoipa::path(
+    tag = "projects",
+    request_body = CreateProjectRequest,
+    responses(
+        (status = 201, description = "Проект создан", body = Project),
+        (status = 400, description = "Некорректный запрос", body = ErrorResponse),
+        (status = 404, description = "Руководитель или заказчик не найден", body = ErrorResponse)
+    )
+)]
+#[post("/projects")]
+pub async fn create_project(
+    repo: web::Data<Arc<dyn Repository>>,
+    clock: web::Data<Arc<dyn Clock>>,
+    req: web::Json<CreateProjectRequest>,
+) -> Result<impl Responder, ApiError> {
+    let req = req.into_inner();
+    check_users(repo.get_ref().as_ref(), &req.owner_id, req.customer_id.as_deref())?;
+
+    let mut project = Project::new(
+        validate_name(&req.name)?,
+        req.description.unwrap_or_default(),
+        req.owner_id,
+        clock.now(),
+    );
+    project.customer_id = req.customer_id;
+    if let Some(status) = req.status {
+        project.status = parse_status(&status)?;
+    }
+
+    repo.create_project(&project)?;
+    Ok(HttpResponse::Created().insert_header(etag(project.version)).json(project))
+}
+
+#[utoipa::path(
+    tag = "projects",
+    params(
+        ("id" = String, Path, description = "Идентификатор проекта"),
+        ("If-Match" = String, Header, description = "Версия проекта из заголовка ETag")
+    ),
+    request_body = UpdateProjectRequest,
+    responses(
+        (status = 200, description = "Проект обновлен", body = Project),
+        (status = 400, description = "Некорректный запрос", body = ErrorResponse),
+        (status = 404, description = "Проект или пользователь не найден", body = ErrorResponse),
+        (status = 412, description = "Проект изменен другим пользователем", body = ErrorResponse),
+        (status = 428, description = "Не передан заголовок If-Match", body = ErrorResponse)
+    )
+)]
+#[put("/projects/{id}")]
+pub async fn update_project(
+    repo: web::Data<Arc<dyn Repository>>,
+    path: web::Path<String>,
+    http_req: HttpRequest,
+    req: web::Json<UpdateProjectRequest>,
+) -> Result<impl Responder, ApiError> {
+    let id = path.into_inner();
+    let req = req.into_inner();
+
+    let mut project = repo.get_project_by_id(&id)?;
+    check_if_match(&http_req, project.version)?;
+    check_users(repo.get_ref().as_ref(), &req.owner_id, req.customer_id.as_deref())?;
+
+    project.name = validate_name(&req.name)?;
+    project.description = req.description.unwrap_or_default();
+    project.owner_id = req.owner_id;
+    project.customer_id = req.customer_id;
+    project.status = parse_status(&req.status)?;
+
+    repo.update_project(&project)?;
+    project.version += 1;
+
+    Ok(HttpResponse::Ok().insert_header(etag(project.version)).json(project))
+}
+
+#[utoipa::path(
+    tag = "projects",
+    params(
+        ("id" = String, Path, description = "Идентификатор проекта"),
+        ("If-Match" = String, Header, description = "Версия проекта из заголовка ETag")
+    ),
+    responses(
+        (status = 204, description = "Проект удален; его задачи остались без проекта"),
+        (status = 404, description = "Проект не найден", body = ErrorResponse),
+        (status = 412, description = "Проект изменен другим пользователем", body = ErrorResponse),
+        (status = 428, description = "Не передан заголовок If-Match", body = ErrorResponse)
+    )
+)]
+#[delete("/projects/{id}")]
+pub async fn delete_project(
+    repo: web::Data<Arc<dyn Repository>>,
+    path: web::Path<String>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, ApiError> {
+    let id = path.into_inner();
+    let project = repo.get_project_by_id(&id)?;
+    check_if_match(&http_req, project.version)?;
+    repo.delete_project(&id)?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    tag = "projects",
+    params(("id" = String, Path, description = "Идентификатор проекта")),
+    responses(
+        (status = 200, description = "Задачи проекта", body = [TaskResponse]),
+        (status = 404, description = "Проект не найден", body = ErrorResponse)
+    )
+)]
+#[get("/projects/{id}/tasks")]
+pub async fn get_project_tasks(
+    repo: web::Data<Arc<dyn Repository>>,
+    calendar: web::Data<BusinessCalendar>,
+    clock: web::Data<Arc<dyn Clock>>,
+    path: web::Path<String>,
+) -> Result<impl Responder, ApiError> {
+    let now = clock.now();
+    let id = path.into_inner();
+    repo.get_project_by_id(&id)?;
+
+    let zones = user_time_zones(repo.get_ref().as_ref())?;
+    let tasks: Vec<TaskResponse> = repo.get_tasks_by_project(&id)?
+        .into_iter()
+        .map(|task| {
+            let tz = executor_tz(&zones, &task);
+            TaskResponse::new(task, &calendar, tz, now)
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(tasks))
+}
+
+#[utoipa::path(
+    tag = "projects",
+    params(("id" = String, Path, description = "Идентификатор проекта")),
+    responses(
+        (status = 200, description = "Статистика по задачам проекта", body = StatsResponse),
+        (status = 404, description = "Проект не найден", body = ErrorResponse)
+    )
+)]
+#[get("/projects/{id}/stats")]
+pub async fn get_project_stats(
+    repo: web::Data<Arc<dyn Repository>>,
+    calendar: web::Data<BusinessCalendar>,
+    clock: web::Data<Arc<dyn Clock>>,
+    path: web::Path<String>,
+) -> Result<impl Responder, ApiError> {
+    let now = clock.now();
+    let id = path.into_inner();
+    repo.get_project_by_id(&id)?;
+
+    let zones = user_time_zones(repo.get_ref().as_ref())?;
+    let tasks = repo.get_tasks_by_project(&id)?;
+    Ok(HttpResponse::Ok().json(task_stats(&tasks, &calendar, &zones, now)))
+}
+
+// Руководитель и заказчик проекта должны существовать
+fn check_users(repo: &dyn Repository, owner_id: &str, customer_id: Option<&str>) -> Result<(), ApiError> {
+    repo.get_user_by_id(owner_id)?;
+    if let Some(customer_id) = customer_id {
+        repo.get_user_by_id(customer_id)?;
+    }
+    Ok(())
+}
+
+fn validate_name(name: &str) -> Result<String, ApiError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ApiError::BadRequest("errors.project_name_required".to_string()));
+    }
+    Ok(name.to_string())
+}
+
+fn parse_status(code: &str) -> Result<ProjectStatus, ApiError> {
+    ProjectStatus::ALL.iter()
+        .find(|s| s.code() == code)
+        .cloned()
+        .ok_or_else(|| ApiError::BadRequest("errors.invalid_project_status".to_string()))
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_projects)
+       .service(get_project)
+       .service(create_project)
+       .service(update_project)
+       .service(delete_project)
+       .service(get_project_tasks)
+       .service(get_project_stats);
+}
@@ -0,0 +1,176 @@
+use actix_web::{web, HttpResponse, Responder, get};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use crate::calendar::BusinessCalendar;
+use crate::clock::Clock;
+use crate::db::Repository;
+use crate::models::Task;
+use super::tasks::{executor_tz, user_time_zones, TaskResponse};
+use super::ApiError;
+
+// Момент, на который считается просрочка; по умолчанию текущий. Будущий
+// момент показывает, что окажется просрочено к этому времени
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReportQuery {
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct StatsResponse {
+    pub total: usize,
+    pub by_status: BTreeMap<String, usize>,
+    pub by_priority: BTreeMap<String, usize>,
+    pub overdue: usize,
+    pub overdue_days_total: i64,
+    pub overdue_working_days_total: i64,
+}
+
+// Просроченные задачи, начиная с наиболее просроченных по рабочим дням
+#[utoipa::path(
+    tag = "reports",
+    params(ReportQuery),
+    responses((status = 200, description = "Просроченные задачи", body = [TaskResponse]))
+)]
+#[get("/reports/overdue")]
+pub async fn overdue_report(
+    repo: web::Data<Arc<dyn Repository>>,
+    calendar: web::Data<BusinessCalendar>,
+    clock: web::Data<Arc<dyn Clock>>,
+    query: web::Query<ReportQuery>,
+) -> Result<impl Responder, ApiError> {
+    let now = query.as_of.unwrap_or_else(|| clock.now());
+    let zones = user_time_zones(repo.get_ref().as_ref())?;
+    let mut tasks: Vec<TaskResponse> = repo.get_tasks()?
+        .into_iter()
+        .filter(|task| task.is_overdue(now))
+        .map(|task| {
+            let tz = executor_tz(&zones, &task);
+            TaskResponse::new(task, &calendar, tz, now)
+        })
+        .collect();
+
+    tasks.sort_by(|a, b| {
+        b.overdue_working_days.cmp(&a.overdue_working_days)
+            .then(b.overdue_days.cmp(&a.overdue_days))
+    });
+
+    Ok(HttpResponse::Ok().json(tasks))
+}
+
+#[utoipa::path(
+    tag = "reports",
+    params(ReportQuery),
+    responses((status = 200, description = "Статистика по задачам", body = StatsResponse))
+)]
+#[get("/reports/stats")]
+pub async fn stats_report(
+    repo: web::Data<Arc<dyn Repository>>,
+    calendar: web::Data<BusinessCalendar>,
+    clock: web::Data<Arc<dyn Clock>>,
+    query: web::Query<ReportQuery>,
+) -> Result<impl Responder, ApiError> {
+    let now = query.as_of.unwrap_or_else(|| clock.now());
+    let zones = user_time_zones(repo.get_ref().as_ref())?;
+    let tasks = repo.get_tasks()?;
+    Ok(HttpResponse::Ok().json(task_stats(&tasks, &calendar, &zones, now)))
+}
+
+// Сколько раз задачи переходили от одного исполнителя к другому
+#[derive(Serialize, ToSchema)]
+pub struct DelegationFlow {
+    pub from_user_id: String,
+    pub to_user_id: String,
+    pub count: usize,
+}
+
+// Итоги передач по пользователю: отдано, получено и передано им как инициатором
+#[derive(Serialize, ToSchema)]
+pub struct DelegationTotals {
+    pub user_id: String,
+    pub delegated: usize,
+    pub received: usize,
+    pub initiated: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DelegationReport {
+    pub total: usize,
+    pub flows: Vec<DelegationFlow>,
+    pub by_user: Vec<DelegationTotals>,
+}
+
+// Кто, сколько и кому передавал задач; направления по убыванию числа передач
+#[utoipa::path(
+    tag = "reports",
+    responses((status = 200, description = "Отчет по передачам задач", body = DelegationReport))
+)]
+#[get("/reports/delegations")]
+pub async fn delegation_report(repo: web::Data<Arc<dyn Repository>>) -> Result<impl Responder, ApiError> {
+    let delegations = repo.get_delegations()?;
+
+    let mut flows: BTreeMap<(String, String), usize> = BTreeMap::new();
+    let mut totals: BTreeMap<String, DelegationTotals> = BTreeMap::new();
+    for delegation in &delegations {
+        *flows.entry((delegation.from_user_id.clone(), delegation.to_user_id.clone())).or_insert(0) += 1;
+        totals_for(&mut totals, &delegation.from_user_id).delegated += 1;
+        totals_for(&mut totals, &delegation.to_user_id).received += 1;
+        totals_for(&mut totals, &delegation.by_user_id).initiated += 1;
+    }
+
+    let mut flows: Vec<DelegationFlow> = flows.into_iter()
+        .map(|((from_user_id, to_user_id), count)| DelegationFlow { from_user_id, to_user_id, count })
+        .collect();
+    flows.sort_by_key(|flow| std::cmp::Reverse(flow.count));
+
+    Ok(HttpResponse::Ok().json(DelegationReport {
+        total: delegations.len(),
+        flows,
+        by_user: totals.into_values().collect(),
+    }))
+}
+
+fn totals_for<'a>(totals: &'a mut BTreeMap<String, DelegationTotals>, user_id: &str) -> &'a mut DelegationTotals {
+    totals.entry(user_id.to_string()).or_insert_with(|| DelegationTotals {
+        user_id: user_id.to_string(),
+        delegated: 0,
+        received: 0,
+        initiated: 0,
+    })
+}
+
+// Сводка по набору задач: используется для общего отчета и для проектов
+pub fn task_stats(tasks: &[Task], calendar: &BusinessCalendar, zones: &HashMap<String, Tz>, now: DateTime<Utc>) -> StatsResponse {
+    let mut stats = StatsResponse {
+        total: tasks.len(),
+        by_status: BTreeMap::new(),
+        by_priority: BTreeMap::new(),
+        overdue: 0,
+        overdue_days_total: 0,
+        overdue_working_days_total: 0,
+    };
+
+    for task in tasks {
+        *stats.by_status.entry(format!("{:?}", task.status)).or_insert(0) += 1;
+        *stats.by_priority.entry(format!("{:?}", task.priority)).or_insert(0) += 1;
+
+        if task.is_overdue(now) {
+            stats.overdue += 1;
+            stats.overdue_days_total += task.overdue_days(now).unwrap_or(0);
+            stats.overdue_working_days_total += task.overdue_working_days(calendar, executor_tz(zones, task), now).unwrap_or(0);
+        }
+    }
+
+    stats
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(overdue_report)
+       .service(stats_report)
+       .service(delegation_report);
+}
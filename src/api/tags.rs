@@ -0,0 +1,202 @@
+use actix_web::{web, HttpResponse, Responder, get, post, put, delete};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use std::sync::Arc;
+
+use crate::db::Repository;
+use crate::models::tag::{clean_tag_name, normalize_tag_name};
+use crate::models::Tag;
+use crate::validation::{self, Validator};
+use super::{ApiError, ErrorResponse};
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateTagRequest {
+    pub name: String,
+    pub color: Option<String>, // #RRGGBB
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct UpdateTagRequest {
+    pub name: String,
+    pub color: Option<String>, // #RRGGBB
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct MergeTagRequest {
+    pub target_id: String, // Тег, который остается после объединения
+}
+
+// Тег с числом задач, в которых он используется
+#[derive(Serialize, ToSchema)]
+pub struct TagResponse {
+    #[serde(flatten)]
+    pub tag: Tag,
+    pub usage_count: usize,
+}
+
+#[utoipa::path(
+    tag = "tags",
+    responses((status = 200, description = "Список тегов", body = [TagResponse]))
+)]
+#[get("/tags")]
+pub async fn get_tags(repo: web::Data<Arc<dyn Repository>>) -> Result<impl Responder, ApiError> {
+    let counts = repo.get_tag_usage_counts()?;
+    let tags: Vec<TagResponse> = repo.get_tags()?
+        .into_iter()
+        .map(|tag| {
+            let usage_count = counts.get(&tag.id).copied().unwrap_or(0);
+            TagResponse { tag, usage_count }
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(tags))
+}
+
+#[utoipa::path(
+    tag = "tags",
+    request_body = CreateTagRequest,
+    responses(
+        (status = 201, description = "Тег создан", body = Tag),
+        (status = 400, description = "Некорректный запрос", body = ErrorResponse),
+        (status = 409, description = "Тег с таким именем уже существует", body = ErrorResponse),
+        (status = 422, description = "Поля не прошли проверку", body = ErrorResponse)
+    )
+)]
+#[post("/tags")]
+pub async fn create_tag(
+    repo: web::Data<Arc<dyn Repository>>,
+    req: web::Json<CreateTagRequest>,
+) -> Result<impl Responder, ApiError> {
+    let mut tag = Tag::new(validate_name(repo.get_ref().as_ref(), &req.name, None)?);
+    tag.color = validate_color(req.color.as_deref())?;
+    tag.description = non_empty(req.description.as_deref());
+
+    repo.create_tag(&tag)?;
+    Ok(HttpResponse::Created().json(tag))
+}
+
+#[utoipa::path(
+    tag = "tags",
+    params(("id" = String, Path, description = "Идентификатор тега")),
+    request_body = UpdateTagRequest,
+    responses(
+        (status = 200, description = "Тег обновлен", body = Tag),
+        (status = 400, description = "Некорректный запрос", body = ErrorResponse),
+        (status = 404, description = "Тег не найден", body = ErrorResponse),
+        (status = 409, description = "Тег с таким именем уже существует", body = ErrorResponse),
+        (status = 422, description = "Поля не прошли проверку", body = ErrorResponse)
+    )
+)]
+#[put("/tags/{id}")]
+pub async fn update_tag(
+    repo: web::Data<Arc<dyn Repository>>,
+    path: web::Path<String>,
+    req: web::Json<UpdateTagRequest>,
+) -> Result<impl Responder, ApiError> {
+    let id = path.into_inner();
+
+    let mut tag = repo.get_tag_by_id(&id)?;
+    tag.name = validate_name(repo.get_ref().as_ref(), &req.name, Some(&id))?;
+    tag.color = validate_color(req.color.as_deref())?;
+    tag.description = non_empty(req.description.as_deref());
+
+    repo.update_tag(&tag)?;
+    Ok(HttpResponse::Ok().json(tag))
+}
+
+#[utoipa::path(
+    tag = "tags",
+    params(("id" = String, Path, description = "Идентификатор тега")),
+    responses(
+        (status = 204, description = "Тег удален и снят со всех задач"),
+        (status = 404, description = "Тег не найден", body = ErrorResponse)
+    )
+)]
+#[delete("/tags/{id}")]
+pub async fn delete_tag(
+    repo: web::Data<Arc<dyn Repository>>,
+    path: web::Path<String>,
+) -> Result<impl Responder, ApiError> {
+    let id = path.into_inner();
+    repo.delete_tag(&id)?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// Объединение тегов: задачи тега {id} получают целевой тег, а сам тег удаляется
+#[utoipa::path(
+    tag = "tags",
+    params(("id" = String, Path, description = "Идентификатор объединяемого тега")),
+    request_body = MergeTagRequest,
+    responses(
+        (status = 200, description = "Теги объединены", body = TagResponse),
+        (status = 400, description = "Тег нельзя объединить с самим собой", body = ErrorResponse),
+        (status = 404, description = "Тег не найден", body = ErrorResponse)
+    )
+)]
+#[post("/tags/{id}/merge")]
+pub async fn merge_tag(
+    repo: web::Data<Arc<dyn Repository>>,
+    path: web::Path<String>,
+    req: web::Json<MergeTagRequest>,
+) -> Result<impl Responder, ApiError> {
+    let id = path.into_inner();
+    if id == req.target_id {
+        return Err(ApiError::BadRequest("errors.tag_merge_self".to_string()));
+    }
+
+    repo.merge_tags(&id, &req.target_id)?;
+
+    let tag = repo.get_tag_by_id(&req.target_id)?;
+    let usage_count = repo.get_tag_usage_counts()?.get(&tag.id).copied().unwrap_or(0);
+    Ok(HttpResponse::Ok().json(TagResponse { tag, usage_count }))
+}
+
+// Имя тега не может быть пустым, длиннее общего ограничения и должно быть
+// уникальным без учета регистра, лишних пробелов и различия ё/е
+fn validate_name(repo: &dyn Repository, name: &str, own_id: Option<&str>) -> Result<String, ApiError> {
+    let key = normalize_tag_name(name);
+    if key.is_empty() {
+        return Err(ApiError::BadRequest("errors.tag_name_required".to_string()));
+    }
+
+    let mut v = Validator::new();
+    validation::tag_name(&mut v, "name", name);
+    v.finish().map_err(ApiError::Validation)?;
+
+    let taken = repo.get_tags()?
+        .iter()
+        .any(|t| normalize_tag_name(&t.name) == key && Some(t.id.as_str()) != own_id);
+    if taken {
+        return Err(ApiError::Conflict("errors.tag_exists".to_string()));
+    }
+
+    Ok(clean_tag_name(name))
+}
+
+fn validate_color(color: Option<&str>) -> Result<Option<String>, ApiError> {
+    let Some(color) = non_empty(color) else {
+        return Ok(None);
+    };
+
+    let valid = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        return Err(ApiError::BadRequest("errors.invalid_tag_color".to_string()));
+    }
+
+    Ok(Some(color.to_lowercase()))
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value.map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_tags)
+       .service(create_tag)
+       .service(update_tag)
+       .service(delete_tag)
+       .service(merge_tag);
+}
@@ -1,24 +1,35 @@
-use actix_web::{web, HttpResponse, Responder, get, post, put, delete};
+use actix_web::{web, HttpRequest, HttpResponse, Responder, get, post, put, patch, delete};
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use std::collections::HashMap;
 use std::sync::Arc;
 
+use crate::calendar::timezone::{default_time_zone, format_local, DueDateInput};
+use crate::calendar::BusinessCalendar;
+use crate::clock::Clock;
 use crate::db::Repository;
-use crate::models::{Task, TaskPriority, TaskStatus, Tag};
-use super::ApiError;
+use crate::models::{Delegation, ParticipantRole, Task, TaskPriority, TaskStatus, Tag};
+use crate::validation::{self, Validator};
+use super::users::check_assignable;
+use super::{check_if_match, etag, non_null, nullable, ApiError, ErrorResponse};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct CreateTaskRequest {
     pub title: String,
     pub description: String,
     pub priority: String,
     pub customer_id: String,
     pub executor_id: String,
-    pub due_date: Option<DateTime<Utc>>,
+    pub due_date: Option<DueDateInput>, // Момент времени или дата (конец дня в поясе исполнителя)
+    pub sla_days: Option<u32>, // Срок в рабочих днях, если due_date не указан
     pub tags: Vec<String>, // Список имен тегов
+    pub project_id: Option<String>,
+    pub estimate_minutes: Option<i64>, // Оценка трудозатрат
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct UpdateTaskRequest {
     pub title: String,
     pub description: String,
@@ -26,177 +37,766 @@ pub struct UpdateTaskRequest {
     pub priority: String,
     pub customer_id: String,
     pub executor_id: String,
-    pub due_date: Option<DateTime<Utc>>,
+    pub due_date: Option<DueDateInput>, // Момент времени или дата (конец дня в поясе исполнителя)
     pub tags: Vec<String>, // Список имен тегов
+    pub project_id: Option<String>, // Отсутствие или null - задача вне проекта
+    pub estimate_minutes: Option<i64>, // Отсутствие или null - без оценки
 }
 
-#[derive(Deserialize)]
-pub struct CreateTagRequest {
-    pub name: String,
+// Частичное обновление задачи (JSON Merge Patch): отсутствующие поля
+// не меняются, "due_date": null снимает срок, "project_id": null - проект,
+// "estimate_minutes": null - оценку
+#[derive(Deserialize, ToSchema)]
+pub struct PatchTaskRequest {
+    #[serde(default, deserialize_with = "non_null")]
+    pub title: Option<String>,
+    #[serde(default, deserialize_with = "non_null")]
+    pub description: Option<String>,
+    #[serde(default, deserialize_with = "non_null")]
+    pub status: Option<String>,
+    #[serde(default, deserialize_with = "non_null")]
+    pub priority: Option<String>,
+    #[serde(default, deserialize_with = "non_null")]
+    pub customer_id: Option<String>,
+    #[serde(default, deserialize_with = "non_null")]
+    pub executor_id: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<DueDateInput>)]
+    pub due_date: Option<Option<DueDateInput>>,
+    #[serde(default, deserialize_with = "non_null")]
+    pub tags: Option<Vec<String>>, // Список имен тегов; заменяет текущий
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>)]
+    pub project_id: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<i64>)]
+    pub estimate_minutes: Option<Option<i64>>,
 }
 
+// Пользователь, добавляемый в соисполнители или наблюдатели
+#[derive(Deserialize, ToSchema)]
+pub struct ParticipantRequest {
+    pub user_id: String,
+}
+
+// Передача задачи другому исполнителю. Передать задачу может текущий
+// исполнитель или заказчик, причина обязательна
+#[derive(Deserialize, ToSchema)]
+pub struct ReassignRequest {
+    pub to_user_id: String,
+    pub by_user_id: String,
+    pub reason: String,
+}
+
+// Задача вместе с вычисляемыми полями просрочки на момент now и датами
+// в часовом поясе исполнителя
+#[derive(Serialize, ToSchema)]
+pub struct TaskResponse {
+    #[serde(flatten)]
+    pub task: Task,
+    pub overdue_days: Option<i64>,
+    pub overdue_working_days: Option<i64>,
+    pub time_zone: String,
+    pub created_at_local: String,
+    pub due_date_local: Option<String>,
+    pub completed_at_local: Option<String>,
+    pub estimate_variance_minutes: Option<i64>, // Учтенное время минус оценка
+}
+
+impl TaskResponse {
+    pub fn new(task: Task, calendar: &BusinessCalendar, tz: Tz, now: DateTime<Utc>) -> Self {
+        let overdue_days = task.overdue_days(now);
+        let overdue_working_days = task.overdue_working_days(calendar, tz, now);
+        let created_at_local = format_local(task.created_at, tz);
+        let due_date_local = task.due_date.map(|d| format_local(d, tz));
+        let completed_at_local = task.completed_at.map(|d| format_local(d, tz));
+        let estimate_variance_minutes = task.estimate_variance_minutes();
+        Self {
+            task,
+            overdue_days,
+            overdue_working_days,
+            time_zone: tz.name().to_string(),
+            created_at_local,
+            due_date_local,
+            completed_at_local,
+            estimate_variance_minutes,
+        }
+    }
+}
+
+// Часовые пояса всех пользователей для построения списков задач
+pub fn user_time_zones(repo: &dyn Repository) -> Result<HashMap<String, Tz>, ApiError> {
+    Ok(repo.get_users()?
+        .into_iter()
+        .map(|user| (user.id.clone(), user.tz()))
+        .collect())
+}
+
+// Часовой пояс исполнителя задачи
+pub fn executor_tz(zones: &HashMap<String, Tz>, task: &Task) -> Tz {
+    zones.get(&task.executor_id).copied().unwrap_or_else(default_time_zone)
+}
+
+// Часовой пояс исполнителя задачи без предварительной выборки всех пользователей
+pub fn load_executor_tz(repo: &dyn Repository, task: &Task) -> Tz {
+    repo.get_user_by_id(&task.executor_id).map(|u| u.tz()).unwrap_or_else(|_| default_time_zone())
+}
+
+pub fn parse_priority(code: &str) -> Result<TaskPriority, ApiError> {
+    match code {
+        "low" => Ok(TaskPriority::Low),
+        "medium" => Ok(TaskPriority::Medium),
+        "high" => Ok(TaskPriority::High),
+        "critical" => Ok(TaskPriority::Critical),
+        _ => Err(ApiError::BadRequest("errors.invalid_priority".to_string())),
+    }
+}
+
+pub fn parse_status(code: &str) -> Result<TaskStatus, ApiError> {
+    match code {
+        "new" => Ok(TaskStatus::New),
+        "in_progress" => Ok(TaskStatus::InProgress),
+        "completed" => Ok(TaskStatus::Completed),
+        "cancelled" => Ok(TaskStatus::Cancelled),
+        _ => Err(ApiError::BadRequest("errors.invalid_status".to_string())),
+    }
+}
+
+// Смена статуса задачи с проверкой WIP-лимита: заполненная до лимита
+// колонка доски новых задач не принимает
+pub fn apply_status(repo: &dyn Repository, task: &mut Task, code: &str, now: DateTime<Utc>) -> Result<(), ApiError> {
+    let status = parse_status(code)?;
+    if status == task.status {
+        return Ok(());
+    }
+
+    if let Some(&limit) = repo.get_wip_limits()?.get(code) {
+        let in_column = repo.get_tasks()?
+            .iter()
+            .filter(|t| t.status == status && t.id != task.id)
+            .count();
+        if in_column >= limit as usize {
+            return Err(ApiError::Conflict("errors.wip_limit_reached".to_string()));
+        }
+    }
+
+    set_status(task, status, now);
+    Ok(())
+}
+
+// Смена статуса без проверки лимита; при завершении и отмене фиксируется их время.
+// Задача, перешедшая в другую колонку доски, встает в ее начало
+pub fn set_status(task: &mut Task, status: TaskStatus, now: DateTime<Utc>) {
+    if status == task.status {
+        return;
+    }
+    match status {
+        TaskStatus::Completed => task.completed_at = Some(now),
+        TaskStatus::Cancelled => task.cancelled_at = Some(now),
+        _ => {}
+    }
+    task.status = status;
+    task.rank = 0;
+}
+
+// Оценка трудозатрат в минутах должна быть положительной
+fn check_estimate(estimate_minutes: Option<i64>) -> Result<Option<i64>, ApiError> {
+    match estimate_minutes {
+        Some(minutes) if minutes <= 0 => Err(ApiError::BadRequest("errors.invalid_estimate".to_string())),
+        _ => Ok(estimate_minutes),
+    }
+}
+
+// Проект задачи должен существовать
+fn check_project(repo: &dyn Repository, project_id: Option<String>) -> Result<Option<String>, ApiError> {
+    if let Some(id) = &project_id {
+        repo.get_project_by_id(id)?;
+    }
+    Ok(project_id)
+}
+
+// Теги задачи по именам; имена сравниваются без учета регистра, пробелов и ё/е
+pub fn resolve_tags(repo: &dyn Repository, names: &[String]) -> Result<Vec<Tag>, ApiError> {
+    Ok(repo.get_or_create_tags_by_names(names)?)
+}
+
+#[utoipa::path(
+    tag = "tasks",
+    responses((status = 200, description = "Список задач", body = [TaskResponse]))
+)]
 #[get("/tasks")]
-pub async fn get_tasks(repo: web::Data<Arc<dyn Repository>>) -> Result<impl Responder, ApiError> {
-    let tasks = repo.get_tasks()?;
+pub async fn get_tasks(
+    repo: web::Data<Arc<dyn Repository>>,
+    calendar: web::Data<BusinessCalendar>,
+    clock: web::Data<Arc<dyn Clock>>,
+) -> Result<impl Responder, ApiError> {
+    let zones = user_time_zones(repo.get_ref().as_ref())?;
+    let now = clock.now();
+    let tasks: Vec<TaskResponse> = repo.get_tasks()?
+        .into_iter()
+        .map(|task| {
+            let tz = executor_tz(&zones, &task);
+            TaskResponse::new(task, &calendar, tz, now)
+        })
+        .collect();
     Ok(HttpResponse::Ok().json(tasks))
 }
 
+#[utoipa::path(
+    tag = "tasks",
+    params(("id" = String, Path, description = "Идентификатор задачи")),
+    responses(
+        (status = 200, description = "Задача; заголовок ETag содержит ее версию", body = TaskResponse),
+        (status = 404, description = "Задача не найдена", body = ErrorResponse)
+    )
+)]
 #[get("/tasks/{id}")]
 pub async fn get_task(
     repo: web::Data<Arc<dyn Repository>>,
+    calendar: web::Data<BusinessCalendar>,
+    clock: web::Data<Arc<dyn Clock>>,
     path: web::Path<String>,
 ) -> Result<impl Responder, ApiError> {
     let id = path.into_inner();
     let task = repo.get_task_by_id(&id)?;
-    Ok(HttpResponse::Ok().json(task))
+    let tz = load_executor_tz(repo.get_ref().as_ref(), &task);
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(task.version))
+        .json(TaskResponse::new(task, &calendar, tz, clock.now())))
 }
 
+#[utoipa::path(
+    tag = "tasks",
+    request_body = CreateTaskRequest,
+    responses(
+        (status = 201, description = "Задача создана", body = TaskResponse),
+        (status = 400, description = "Некорректный запрос или пользователь деактивирован", body = ErrorResponse),
+        (status = 404, description = "Заказчик, исполнитель или проект не найден", body = ErrorResponse),
+        (status = 422, description = "Поля не прошли проверку", body = ErrorResponse)
+    )
+)]
 #[post("/tasks")]
 pub async fn create_task(
     repo: web::Data<Arc<dyn Repository>>,
+    calendar: web::Data<BusinessCalendar>,
+    clock: web::Data<Arc<dyn Clock>>,
     req: web::Json<CreateTaskRequest>,
 ) -> Result<impl Responder, ApiError> {
-    // Проверяем, существуют ли заказчик и исполнитель
+    // Проверяем, существуют ли заказчик и исполнитель и можно ли их назначить
     let customer = repo.get_user_by_id(&req.customer_id)?;
     let executor = repo.get_user_by_id(&req.executor_id)?;
+    check_assignable(&customer)?;
+    check_assignable(&executor)?;
     
     // Обрабатываем приоритет
-    let priority = match req.priority.as_str() {
-        "low" => TaskPriority::Low,
-        "medium" => TaskPriority::Medium,
-        "high" => TaskPriority::High,
-        "critical" => TaskPriority::Critical,
-        _ => return Err(ApiError::BadRequest("Invalid priority".to_string())),
+    let priority = parse_priority(&req.priority)?;
+    
+    // Проверяем поля; все ошибки возвращаются одним ответом
+    let mut v = Validator::new();
+    validation::task_title(&mut v, &req.title);
+    validation::task_description(&mut v, &req.description);
+    validation::task_tags(&mut v, &req.tags);
+    validation::task_sla_days(&mut v, req.sla_days);
+    
+    // Сроки интерпретируются в часовом поясе исполнителя.
+    // Если срок не указан явно, вычисляем его по SLA в рабочих днях;
+    // слишком большой SLA уже отклонен проверкой
+    let tz = executor.tz();
+    let now = clock.now();
+    let sla_days = req.sla_days.filter(|days| *days <= validation::SLA_DAYS_MAX);
+    let due_date = match (req.due_date, sla_days) {
+        (Some(due), _) => Some(due.resolve(tz)),
+        (None, Some(days)) => Some(calendar.sla_due_date(now, days, tz)),
+        (None, None) => None,
     };
+    v.not_in_past("due_date", due_date, now);
+    v.finish().map_err(ApiError::Validation)?;
     
     // Получаем или создаем теги
-    let mut tags = Vec::new();
-    for tag_name in &req.tags {
-        // Ищем тег по имени среди существующих
-        let existing_tags = repo.get_tags()?;
-        let tag = existing_tags.iter()
-            .find(|t| t.name == *tag_name)
-            .cloned();
-        
-        if let Some(tag) = tag {
-            tags.push(tag);
-        } else {
-            // Создаем новый тег
-            let new_tag = Tag::new(tag_name.clone());
-            repo.create_tag(&new_tag)?;
-            tags.push(new_tag);
-        }
-    }
+    let tags = resolve_tags(repo.get_ref().as_ref(), &req.tags)?;
+    
+    let project_id = check_project(repo.get_ref().as_ref(), req.project_id.clone())?;
+    let estimate_minutes = check_estimate(req.estimate_minutes)?;
     
     // Создаем задачу
-    let task = Task::new(
+    let mut task = Task::new(
         req.title.clone(),
         req.description.clone(),
         req.customer_id.clone(),
         req.executor_id.clone(),
         priority,
-        req.due_date,
+        due_date,
         tags,
+        now,
     );
+    task.project_id = project_id;
+    task.estimate_minutes = estimate_minutes;
     
     repo.create_task(&task)?;
     
-    Ok(HttpResponse::Created().json(task))
+    Ok(HttpResponse::Created()
+        .insert_header(etag(task.version))
+        .json(TaskResponse::new(task, &calendar, tz, now)))
 }
 
+#[utoipa::path(
+    tag = "tasks",
+    params(
+        ("id" = String, Path, description = "Идентификатор задачи"),
+        ("If-Match" = String, Header, description = "Версия задачи из заголовка ETag")
+    ),
+    request_body = UpdateTaskRequest,
+    responses(
+        (status = 200, description = "Задача обновлена", body = TaskResponse),
+        (status = 400, description = "Некорректный запрос или смена исполнителя без передачи", body = ErrorResponse),
+        (status = 404, description = "Задача, пользователь или проект не найдены", body = ErrorResponse),
+        (status = 409, description = "Колонка нового статуса заполнена до WIP-лимита", body = ErrorResponse),
+        (status = 412, description = "Задача изменена другим пользователем", body = ErrorResponse),
+        (status = 422, description = "Поля не прошли проверку", body = ErrorResponse),
+        (status = 428, description = "Не передан заголовок If-Match", body = ErrorResponse)
+    )
+)]
 #[put("/tasks/{id}")]
 pub async fn update_task(
     repo: web::Data<Arc<dyn Repository>>,
+    calendar: web::Data<BusinessCalendar>,
+    clock: web::Data<Arc<dyn Clock>>,
     path: web::Path<String>,
+    http_req: HttpRequest,
     req: web::Json<UpdateTaskRequest>,
 ) -> Result<impl Responder, ApiError> {
     let id = path.into_inner();
     
-    // Получаем существующую задачу
+    // Получаем существующую задачу и проверяем, что клиент изменяет актуальную версию
     let mut task = repo.get_task_by_id(&id)?;
+    check_if_match(&http_req, task.version)?;
     
-    // Проверяем, существуют ли заказчик и исполнитель
+    // Проверяем, существуют ли заказчик и исполнитель. Деактивированный заказчик
+    // остается у задачи, но назначить его заново нельзя
     let customer = repo.get_user_by_id(&req.customer_id)?;
     let executor = repo.get_user_by_id(&req.executor_id)?;
+    if customer.id != task.customer_id {
+        check_assignable(&customer)?;
+    }
+    
+    // Срок в прошлом допустим, только если он не меняется
+    let now = clock.now();
+    let due_date = req.due_date.map(|due| due.resolve(executor.tz()));
+    let mut v = Validator::new();
+    validation::task_title(&mut v, &req.title);
+    validation::task_description(&mut v, &req.description);
+    validation::task_tags(&mut v, &req.tags);
+    if due_date != task.due_date {
+        v.not_in_past("due_date", due_date, now);
+    }
+    v.finish().map_err(ApiError::Validation)?;
     
     // Обновляем поля задачи
     task.title = req.title.clone();
     task.description = req.description.clone();
     
-    task.status = match req.status.as_str() {
-        "new" => TaskStatus::New,
-        "in_progress" => TaskStatus::InProgress,
-        "completed" => {
-            // Если задача завершена, устанавливаем время завершения
-            if task.status != TaskStatus::Completed {
-                task.completed_at = Some(Utc::now());
-            }
-            TaskStatus::Completed
-        },
-        "cancelled" => TaskStatus::Cancelled,
-        _ => return Err(ApiError::BadRequest("Invalid status".to_string())),
-    };
-    
-    task.priority = match req.priority.as_str() {
-        "low" => TaskPriority::Low,
-        "medium" => TaskPriority::Medium,
-        "high" => TaskPriority::High,
-        "critical" => TaskPriority::Critical,
-        _ => return Err(ApiError::BadRequest("Invalid priority".to_string())),
-    };
+    apply_status(repo.get_ref().as_ref(), &mut task, &req.status, now)?;
+    task.priority = parse_priority(&req.priority)?;
     
     task.customer_id = req.customer_id.clone();
-    task.executor_id = req.executor_id.clone();
-    task.due_date = req.due_date;
+    check_executor_unchanged(&task, &req.executor_id)?;
+    task.due_date = due_date;
+    task.project_id = check_project(repo.get_ref().as_ref(), req.project_id.clone())?;
+    task.estimate_minutes = check_estimate(req.estimate_minutes)?;
     
     // Обновляем теги
-    task.tags.clear();
-    for tag_name in &req.tags {
-        // Ищем тег по имени среди существующих
-        let existing_tags = repo.get_tags()?;
-        let tag = existing_tags.iter()
-            .find(|t| t.name == *tag_name)
-            .cloned();
-        
-        if let Some(tag) = tag {
-            task.tags.push(tag);
-        } else {
-            // Создаем новый тег
-            let new_tag = Tag::new(tag_name.clone());
-            repo.create_tag(&new_tag)?;
-            task.tags.push(new_tag);
+    task.tags = resolve_tags(repo.get_ref().as_ref(), &req.tags)?;
+    
+    repo.update_task(&task)?;
+    task.version += 1;
+    
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(task.version))
+        .json(TaskResponse::new(task, &calendar, executor.tz(), now)))
+}
+
+#[utoipa::path(
+    tag = "tasks",
+    params(
+        ("id" = String, Path, description = "Идентификатор задачи"),
+        ("If-Match" = String, Header, description = "Версия задачи из заголовка ETag")
+    ),
+    request_body(content = PatchTaskRequest, content_type = "application/merge-patch+json"),
+    responses(
+        (status = 200, description = "Задача обновлена", body = TaskResponse),
+        (status = 400, description = "Некорректный запрос или смена исполнителя без передачи", body = ErrorResponse),
+        (status = 404, description = "Задача, пользователь или проект не найдены", body = ErrorResponse),
+        (status = 409, description = "Колонка нового статуса заполнена до WIP-лимита", body = ErrorResponse),
+        (status = 412, description = "Задача изменена другим пользователем", body = ErrorResponse),
+        (status = 422, description = "Поля не прошли проверку", body = ErrorResponse),
+        (status = 428, description = "Не передан заголовок If-Match", body = ErrorResponse)
+    )
+)]
+#[patch("/tasks/{id}")]
+pub async fn patch_task(
+    repo: web::Data<Arc<dyn Repository>>,
+    calendar: web::Data<BusinessCalendar>,
+    clock: web::Data<Arc<dyn Clock>>,
+    path: web::Path<String>,
+    http_req: HttpRequest,
+    req: web::Json<PatchTaskRequest>,
+) -> Result<impl Responder, ApiError> {
+    let id = path.into_inner();
+    let req = req.into_inner();
+    
+    let mut task = repo.get_task_by_id(&id)?;
+    check_if_match(&http_req, task.version)?;
+    
+    // Меняем только переданные поля, с теми же проверками, что и при полном обновлении
+    if let Some(customer_id) = req.customer_id {
+        let customer = repo.get_user_by_id(&customer_id)?;
+        if customer_id != task.customer_id {
+            check_assignable(&customer)?;
         }
+        task.customer_id = customer_id;
+    }
+    if let Some(executor_id) = req.executor_id {
+        check_executor_unchanged(&task, &executor_id)?;
+    }
+    let executor = repo.get_user_by_id(&task.executor_id)?;
+    
+    // Проверяются только переданные поля
+    let now = clock.now();
+    let due_date = req.due_date.map(|due| due.map(|due| due.resolve(executor.tz())));
+    let mut v = Validator::new();
+    if let Some(title) = &req.title {
+        validation::task_title(&mut v, title);
+    }
+    if let Some(description) = &req.description {
+        validation::task_description(&mut v, description);
+    }
+    if let Some(tags) = &req.tags {
+        validation::task_tags(&mut v, tags);
+    }
+    if let Some(due_date) = due_date.filter(|due| *due != task.due_date) {
+        v.not_in_past("due_date", due_date, now);
+    }
+    v.finish().map_err(ApiError::Validation)?;
+    
+    if let Some(title) = req.title {
+        task.title = title;
+    }
+    if let Some(description) = req.description {
+        task.description = description;
+    }
+    if let Some(status) = req.status {
+        apply_status(repo.get_ref().as_ref(), &mut task, &status, now)?;
+    }
+    if let Some(priority) = req.priority {
+        task.priority = parse_priority(&priority)?;
+    }
+    if let Some(due_date) = due_date {
+        task.due_date = due_date;
+    }
+    if let Some(tags) = req.tags {
+        task.tags = resolve_tags(repo.get_ref().as_ref(), &tags)?;
+    }
+    if let Some(project_id) = req.project_id {
+        task.project_id = check_project(repo.get_ref().as_ref(), project_id)?;
+    }
+    if let Some(estimate_minutes) = req.estimate_minutes {
+        task.estimate_minutes = check_estimate(estimate_minutes)?;
     }
     
     repo.update_task(&task)?;
+    task.version += 1;
     
-    Ok(HttpResponse::Ok().json(task))
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(task.version))
+        .json(TaskResponse::new(task, &calendar, executor.tz(), now)))
 }
 
+#[utoipa::path(
+    tag = "tasks",
+    params(
+        ("id" = String, Path, description = "Идентификатор задачи"),
+        ("If-Match" = String, Header, description = "Версия задачи из заголовка ETag")
+    ),
+    responses(
+        (status = 204, description = "Задача удалена"),
+        (status = 404, description = "Задача не найдена", body = ErrorResponse),
+        (status = 412, description = "Задача изменена другим пользователем", body = ErrorResponse),
+        (status = 428, description = "Не передан заголовок If-Match", body = ErrorResponse)
+    )
+)]
 #[delete("/tasks/{id}")]
 pub async fn delete_task(
     repo: web::Data<Arc<dyn Repository>>,
     path: web::Path<String>,
+    http_req: HttpRequest,
+    clock: web::Data<Arc<dyn Clock>>,
 ) -> Result<impl Responder, ApiError> {
     let id = path.into_inner();
-    repo.delete_task(&id)?;
+    let task = repo.get_task_by_id(&id)?;
+    check_if_match(&http_req, task.version)?;
+    repo.delete_task(&id, clock.now())?;
     Ok(HttpResponse::NoContent().finish())
 }
 
-#[get("/tags")]
-pub async fn get_tags(repo: web::Data<Arc<dyn Repository>>) -> Result<impl Responder, ApiError> {
-    let tags = repo.get_tags()?;
-    Ok(HttpResponse::Ok().json(tags))
+#[utoipa::path(
+    tag = "tasks",
+    params(
+        ("id" = String, Path, description = "Идентификатор задачи"),
+        ("If-Match" = String, Header, description = "Версия задачи из заголовка ETag")
+    ),
+    request_body = ParticipantRequest,
+    responses(
+        (status = 200, description = "Соисполнитель добавлен", body = TaskResponse),
+        (status = 400, description = "Пользователь уже исполнитель задачи или деактивирован", body = ErrorResponse),
+        (status = 404, description = "Задача или пользователь не найдены", body = ErrorResponse),
+        (status = 412, description = "Задача изменена другим пользователем", body = ErrorResponse),
+        (status = 428, description = "Не передан заголовок If-Match", body = ErrorResponse)
+    )
+)]
+#[post("/tasks/{id}/co-executors")]
+pub async fn add_co_executor(
+    repo: web::Data<Arc<dyn Repository>>,
+    calendar: web::Data<BusinessCalendar>,
+    clock: web::Data<Arc<dyn Clock>>,
+    http_req: HttpRequest,
+    path: web::Path<String>,
+    req: web::Json<ParticipantRequest>,
+) -> Result<impl Responder, ApiError> {
+    add_participant(repo.get_ref().as_ref(), &calendar, clock.now(), &http_req, &path, &req.user_id, ParticipantRole::CoExecutor)
+}
+
+#[utoipa::path(
+    tag = "tasks",
+    params(
+        ("id" = String, Path, description = "Идентификатор задачи"),
+        ("user_id" = String, Path, description = "Идентификатор соисполнителя"),
+        ("If-Match" = String, Header, description = "Версия задачи из заголовка ETag")
+    ),
+    responses(
+        (status = 200, description = "Соисполнитель удален", body = TaskResponse),
+        (status = 404, description = "Задача не найдена или пользователь не соисполнитель", body = ErrorResponse),
+        (status = 412, description = "Задача изменена другим пользователем", body = ErrorResponse),
+        (status = 428, description = "Не передан заголовок If-Match", body = ErrorResponse)
+    )
+)]
+#[delete("/tasks/{id}/co-executors/{user_id}")]
+pub async fn remove_co_executor(
+    repo: web::Data<Arc<dyn Repository>>,
+    calendar: web::Data<BusinessCalendar>,
+    clock: web::Data<Arc<dyn Clock>>,
+    http_req: HttpRequest,
+    path: web::Path<(String, String)>,
+) -> Result<impl Responder, ApiError> {
+    let (id, user_id) = path.into_inner();
+    remove_participant(repo.get_ref().as_ref(), &calendar, clock.now(), &http_req, &id, &user_id, ParticipantRole::CoExecutor)
+}
+
+#[utoipa::path(
+    tag = "tasks",
+    params(
+        ("id" = String, Path, description = "Идентификатор задачи"),
+        ("If-Match" = String, Header, description = "Версия задачи из заголовка ETag")
+    ),
+    request_body = ParticipantRequest,
+    responses(
+        (status = 200, description = "Наблюдатель добавлен", body = TaskResponse),
+        (status = 404, description = "Задача или пользователь не найдены", body = ErrorResponse),
+        (status = 412, description = "Задача изменена другим пользователем", body = ErrorResponse),
+        (status = 428, description = "Не передан заголовок If-Match", body = ErrorResponse)
+    )
+)]
+#[post("/tasks/{id}/watchers")]
+pub async fn add_watcher(
+    repo: web::Data<Arc<dyn Repository>>,
+    calendar: web::Data<BusinessCalendar>,
+    clock: web::Data<Arc<dyn Clock>>,
+    http_req: HttpRequest,
+    path: web::Path<String>,
+    req: web::Json<ParticipantRequest>,
+) -> Result<impl Responder, ApiError> {
+    add_participant(repo.get_ref().as_ref(), &calendar, clock.now(), &http_req, &path, &req.user_id, ParticipantRole::Watcher)
+}
+
+#[utoipa::path(
+    tag = "tasks",
+    params(
+        ("id" = String, Path, description = "Идентификатор задачи"),
+        ("user_id" = String, Path, description = "Идентификатор наблюдателя"),
+        ("If-Match" = String, Header, description = "Версия задачи из заголовка ETag")
+    ),
+    responses(
+        (status = 200, description = "Наблюдатель удален", body = TaskResponse),
+        (status = 404, description = "Задача не найдена или пользователь не наблюдатель", body = ErrorResponse),
+        (status = 412, description = "Задача изменена другим пользователем", body = ErrorResponse),
+        (status = 428, description = "Не передан заголовок If-Match", body = ErrorResponse)
+    )
+)]
+#[delete("/tasks/{id}/watchers/{user_id}")]
+pub async fn remove_watcher(
+    repo: web::Data<Arc<dyn Repository>>,
+    calendar: web::Data<BusinessCalendar>,
+    clock: web::Data<Arc<dyn Clock>>,
+    http_req: HttpRequest,
+    path: web::Path<(String, String)>,
+) -> Result<impl Responder, ApiError> {
+    let (id, user_id) = path.into_inner();
+    remove_participant(repo.get_ref().as_ref(), &calendar, clock.now(), &http_req, &id, &user_id, ParticipantRole::Watcher)
+}
+
+#[utoipa::path(
+    tag = "tasks",
+    params(
+        ("id" = String, Path, description = "Идентификатор задачи"),
+        ("If-Match" = String, Header, description = "Версия задачи из заголовка ETag")
+    ),
+    request_body = ReassignRequest,
+    responses(
+        (status = 200, description = "Задача передана новому исполнителю", body = TaskResponse),
+        (status = 400, description = "Не указана причина, передающий не исполнитель и не заказчик или исполнитель не меняется или деактивирован", body = ErrorResponse),
+        (status = 404, description = "Задача или пользователь не найдены", body = ErrorResponse),
+        (status = 409, description = "Задача уже завершена или отменена", body = ErrorResponse),
+        (status = 412, description = "Задача изменена другим пользователем", body = ErrorResponse),
+        (status = 428, description = "Не передан заголовок If-Match", body = ErrorResponse)
+    )
+)]
+#[post("/tasks/{id}/reassign")]
+pub async fn reassign_task(
+    repo: web::Data<Arc<dyn Repository>>,
+    calendar: web::Data<BusinessCalendar>,
+    clock: web::Data<Arc<dyn Clock>>,
+    path: web::Path<String>,
+    http_req: HttpRequest,
+    req: web::Json<ReassignRequest>,
+) -> Result<impl Responder, ApiError> {
+    let id = path.into_inner();
+    let req = req.into_inner();
+    
+    let mut task = repo.get_task_by_id(&id)?;
+    check_if_match(&http_req, task.version)?;
+    
+    let delegation = prepare_reassign(&task, &req, clock.now())?;
+    repo.get_user_by_id(&req.by_user_id)?;
+    let executor = repo.get_user_by_id(&req.to_user_id)?;
+    check_assignable(&executor)?;
+    
+    repo.reassign_task(&task, &delegation)?;
+    
+    task.version += 1;
+    apply_reassign(&mut task, &delegation);
+    
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(task.version))
+        .json(TaskResponse::new(task, &calendar, executor.tz(), clock.now())))
 }
 
-#[post("/tags")]
-pub async fn create_tag(
+#[utoipa::path(
+    tag = "tasks",
+    params(("id" = String, Path, description = "Идентификатор задачи")),
+    responses(
+        (status = 200, description = "Цепочка передач задачи, от первой к последней", body = [Delegation]),
+        (status = 404, description = "Задача не найдена", body = ErrorResponse)
+    )
+)]
+#[get("/tasks/{id}/delegations")]
+pub async fn get_task_delegations(
     repo: web::Data<Arc<dyn Repository>>,
-    req: web::Json<CreateTagRequest>,
+    path: web::Path<String>,
 ) -> Result<impl Responder, ApiError> {
-    let tag = Tag::new(req.name.clone());
-    repo.create_tag(&tag)?;
-    Ok(HttpResponse::Created().json(tag))
+    let id = path.into_inner();
+    repo.get_task_by_id(&id)?;
+    Ok(HttpResponse::Ok().json(repo.get_task_delegations(&id)?))
+}
+
+// Проверяет, может ли by_user_id передать задачу, и готовит запись о передаче.
+// Существование пользователей проверяет вызывающий
+pub fn prepare_reassign(task: &Task, req: &ReassignRequest, now: DateTime<Utc>) -> Result<Delegation, ApiError> {
+    let reason = req.reason.trim();
+    if reason.is_empty() {
+        return Err(ApiError::BadRequest("errors.reassign_reason_required".to_string()));
+    }
+    if req.by_user_id != task.executor_id && req.by_user_id != task.customer_id {
+        return Err(ApiError::BadRequest("errors.reassign_not_allowed".to_string()));
+    }
+    if req.to_user_id == task.executor_id {
+        return Err(ApiError::BadRequest("errors.reassign_same_executor".to_string()));
+    }
+    if matches!(task.status, TaskStatus::Completed | TaskStatus::Cancelled) {
+        return Err(ApiError::Conflict("errors.reassign_task_closed".to_string()));
+    }
+
+    Ok(Delegation::new(
+        task.id.clone(),
+        task.executor_id.clone(),
+        req.to_user_id.clone(),
+        req.by_user_id.clone(),
+        reason.to_string(),
+        now,
+    ))
+}
+
+// Задача после передачи: новый исполнитель перестает быть соисполнителем
+pub fn apply_reassign(task: &mut Task, delegation: &Delegation) {
+    task.executor_id = delegation.to_user_id.clone();
+    task.co_executor_ids.retain(|id| *id != task.executor_id);
+}
+
+// Участники входят в версию задачи: их изменение увеличивает версию и,
+// как и остальные изменения задачи, требует If-Match
+fn add_participant(
+    repo: &dyn Repository,
+    calendar: &BusinessCalendar,
+    now: DateTime<Utc>,
+    http_req: &HttpRequest,
+    task_id: &str,
+    user_id: &str,
+    role: ParticipantRole,
+) -> Result<HttpResponse, ApiError> {
+    let task = repo.get_task_by_id(task_id)?;
+    check_if_match(http_req, task.version)?;
+    check_assignable(&repo.get_user_by_id(user_id)?)?;
+    if role == ParticipantRole::CoExecutor && task.executor_id == user_id {
+        return Err(ApiError::BadRequest("errors.co_executor_is_executor".to_string()));
+    }
+
+    repo.add_task_participant(task_id, user_id, role)?;
+    participant_response(repo, calendar, now, task_id)
+}
+
+fn remove_participant(
+    repo: &dyn Repository,
+    calendar: &BusinessCalendar,
+    now: DateTime<Utc>,
+    http_req: &HttpRequest,
+    task_id: &str,
+    user_id: &str,
+    role: ParticipantRole,
+) -> Result<HttpResponse, ApiError> {
+    let task = repo.get_task_by_id(task_id)?;
+    check_if_match(http_req, task.version)?;
+    repo.remove_task_participant(task_id, user_id, role)?;
+    participant_response(repo, calendar, now, task_id)
+}
+
+// Исполнитель меняется только передачей задачи (POST /tasks/{id}/reassign),
+// чтобы в цепочке делегирования оставались причина и автор
+fn check_executor_unchanged(task: &Task, executor_id: &str) -> Result<(), ApiError> {
+    if task.executor_id != executor_id {
+        return Err(ApiError::BadRequest("errors.executor_change_requires_reassign".to_string()));
+    }
+    Ok(())
+}
+
+fn participant_response(
+    repo: &dyn Repository,
+    calendar: &BusinessCalendar,
+    now: DateTime<Utc>,
+    task_id: &str,
+) -> Result<HttpResponse, ApiError> {
+    let task = repo.get_task_by_id(task_id)?;
+    let tz = load_executor_tz(repo, &task);
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(task.version))
+        .json(TaskResponse::new(task, calendar, tz, now)))
 }
 
 pub fn config(cfg: &mut web::ServiceConfig) {
@@ -204,7 +804,12 @@ pub fn config(cfg: &mut web::ServiceConfig) {
        .service(get_task)
        .service(create_task)
        .service(update_task)
+       .service(patch_task)
        .service(delete_task)
-       .service(get_tags)
-       .service(create_tag);
+       .service(add_co_executor)
+       .service(remove_co_executor)
+       .service(add_watcher)
+       .service(remove_watcher)
+       .service(reassign_task)
+       .service(get_task_delegations);
 }
@@ -0,0 +1,379 @@
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{web, HttpResponse, Responder, get, post, delete};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use crate::calendar::timezone::{default_time_zone, format_local};
+use crate::clock::Clock;
+use crate::db::{ArchiveFilter, Repository, TimeEntryFilter};
+use crate::i18n::Locale;
+use crate::models::TimeEntry;
+use crate::validation::{self, Validator};
+use super::archive::all_users;
+use super::{ApiError, ErrorResponse};
+
+// Запись за прошедший период: либо начало и конец, либо длительность
+// в минутах (начало по умолчанию - столько минут назад)
+#[derive(Deserialize, ToSchema)]
+pub struct CreateTimeEntryRequest {
+    pub user_id: String,
+    pub started_at: Option<DateTime<Utc>>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub minutes: Option<i64>,
+    pub note: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct StartTimerRequest {
+    pub user_id: String,
+    pub note: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct StopTimerRequest {
+    pub user_id: String,
+}
+
+// Табель за период; даты - в часовом поясе пользователя, пустые значения игнорируются
+#[derive(Deserialize, IntoParams, ToSchema)]
+#[into_params(parameter_in = Query)]
+pub struct TimesheetQuery {
+    pub user_id: Option<String>,
+    pub from: Option<NaiveDate>, // Включительно
+    pub to: Option<NaiveDate>,   // Включительно
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TimesheetRow {
+    pub date: NaiveDate,
+    pub user_id: String,
+    pub user_name: String,
+    pub task_id: String,
+    pub task_title: String,
+    pub started_at_local: String,
+    pub ended_at_local: String,
+    pub minutes: i64,
+    pub note: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TimesheetTotal {
+    pub user_id: String,
+    pub user_name: String,
+    pub minutes: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TimesheetResponse {
+    pub rows: Vec<TimesheetRow>,
+    pub by_user: Vec<TimesheetTotal>,
+    pub total_minutes: i64,
+}
+
+#[utoipa::path(
+    tag = "time",
+    params(("id" = String, Path, description = "Идентификатор задачи")),
+    responses(
+        (status = 200, description = "Записи учета времени по задаче, включая запущенные таймеры", body = [TimeEntry]),
+        (status = 404, description = "Задача не найдена", body = ErrorResponse)
+    )
+)]
+#[get("/tasks/{id}/time-entries")]
+pub async fn get_task_time_entries(
+    repo: web::Data<Arc<dyn Repository>>,
+    path: web::Path<String>,
+) -> Result<impl Responder, ApiError> {
+    let id = path.into_inner();
+    repo.get_task_by_id(&id)?;
+
+    let filter = TimeEntryFilter { task_id: Some(id), ..Default::default() };
+    Ok(HttpResponse::Ok().json(repo.get_time_entries(&filter)?))
+}
+
+#[utoipa::path(
+    tag = "time",
+    params(("id" = String, Path, description = "Идентификатор задачи")),
+    request_body = CreateTimeEntryRequest,
+    responses(
+        (status = 201, description = "Запись добавлена", body = TimeEntry),
+        (status = 400, description = "Некорректный период или длительность", body = ErrorResponse),
+        (status = 404, description = "Задача или пользователь не найдены", body = ErrorResponse),
+        (status = 422, description = "Поля не прошли проверку", body = ErrorResponse)
+    )
+)]
+#[post("/tasks/{id}/time-entries")]
+pub async fn create_time_entry(
+    repo: web::Data<Arc<dyn Repository>>,
+    clock: web::Data<Arc<dyn Clock>>,
+    path: web::Path<String>,
+    req: web::Json<CreateTimeEntryRequest>,
+) -> Result<impl Responder, ApiError> {
+    let id = path.into_inner();
+    let req = req.into_inner();
+    repo.get_task_by_id(&id)?;
+    repo.get_user_by_id(&req.user_id)?;
+
+    let note = req.note.unwrap_or_default();
+
+    // Длительность проверяется до вычисления дат, чтобы они не вышли за допустимый диапазон
+    let mut v = Validator::new();
+    validation::time_entry_note(&mut v, &note);
+    if let Some(minutes) = req.minutes {
+        validation::time_entry_minutes(&mut v, "minutes", minutes);
+    }
+    if let (Some(started_at), Some(ended_at)) = (req.started_at, req.ended_at) {
+        validation::time_entry_minutes(&mut v, "ended_at", (ended_at - started_at).num_minutes());
+    }
+    v.finish().map_err(ApiError::Validation)?;
+
+    let invalid = || ApiError::BadRequest("errors.invalid_time_entry".to_string());
+    let (started_at, ended_at) = match (req.started_at, req.ended_at, req.minutes) {
+        (started_at, None, Some(minutes)) if minutes > 0 => {
+            let duration = Duration::minutes(minutes);
+            let started_at = match started_at {
+                Some(started_at) => started_at,
+                None => clock.now().checked_sub_signed(duration).ok_or_else(invalid)?,
+            };
+            (started_at, started_at.checked_add_signed(duration).ok_or_else(invalid)?)
+        }
+        (Some(started_at), Some(ended_at), None) if ended_at > started_at => (started_at, ended_at),
+        _ => return Err(invalid()),
+    };
+
+    let entry = TimeEntry::finished(id, req.user_id, started_at, ended_at, note);
+    repo.create_time_entry(&entry)?;
+    Ok(HttpResponse::Created().json(entry))
+}
+
+#[utoipa::path(
+    tag = "time",
+    params(("id" = String, Path, description = "Идентификатор записи")),
+    responses(
+        (status = 204, description = "Запись удалена"),
+        (status = 404, description = "Запись не найдена", body = ErrorResponse)
+    )
+)]
+#[delete("/time-entries/{id}")]
+pub async fn delete_time_entry(
+    repo: web::Data<Arc<dyn Repository>>,
+    path: web::Path<String>,
+) -> Result<impl Responder, ApiError> {
+    repo.delete_time_entry(&path.into_inner())?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    tag = "time",
+    params(("id" = String, Path, description = "Идентификатор задачи")),
+    request_body = StartTimerRequest,
+    responses(
+        (status = 201, description = "Таймер запущен", body = TimeEntry),
+        (status = 404, description = "Задача или пользователь не найдены", body = ErrorResponse),
+        (status = 409, description = "У пользователя уже запущен таймер", body = ErrorResponse),
+        (status = 422, description = "Поля не прошли проверку", body = ErrorResponse)
+    )
+)]
+#[post("/tasks/{id}/timer/start")]
+pub async fn start_timer(
+    repo: web::Data<Arc<dyn Repository>>,
+    clock: web::Data<Arc<dyn Clock>>,
+    path: web::Path<String>,
+    req: web::Json<StartTimerRequest>,
+) -> Result<impl Responder, ApiError> {
+    let id = path.into_inner();
+    let req = req.into_inner();
+    repo.get_task_by_id(&id)?;
+    repo.get_user_by_id(&req.user_id)?;
+
+    let note = req.note.unwrap_or_default();
+    let mut v = Validator::new();
+    validation::time_entry_note(&mut v, &note);
+    v.finish().map_err(ApiError::Validation)?;
+
+    // Пользователь работает над одной задачей за раз. Одновременные запуски,
+    // прошедшие эту проверку, отклоняет уникальный индекс по запущенным
+    // таймерам: нарушение тоже отвечает 409 timer_already_running
+    if repo.get_running_time_entry(&req.user_id)?.is_some() {
+        return Err(ApiError::Conflict("errors.timer_already_running".to_string()));
+    }
+
+    let entry = TimeEntry::start(id, req.user_id, clock.now(), note);
+    repo.create_time_entry(&entry)?;
+    Ok(HttpResponse::Created().json(entry))
+}
+
+#[utoipa::path(
+    tag = "time",
+    params(("id" = String, Path, description = "Идентификатор задачи")),
+    request_body = StopTimerRequest,
+    responses(
+        (status = 200, description = "Таймер остановлен; время округлено до минут", body = TimeEntry),
+        (status = 404, description = "Таймер пользователя по задаче не запущен", body = ErrorResponse)
+    )
+)]
+#[post("/tasks/{id}/timer/stop")]
+pub async fn stop_timer(
+    repo: web::Data<Arc<dyn Repository>>,
+    clock: web::Data<Arc<dyn Clock>>,
+    path: web::Path<String>,
+    req: web::Json<StopTimerRequest>,
+) -> Result<impl Responder, ApiError> {
+    let id = path.into_inner();
+
+    let mut entry = repo.get_running_time_entry(&req.user_id)?
+        .filter(|entry| entry.task_id == id)
+        .ok_or_else(|| ApiError::NotFound("errors.timer_not_running".to_string()))?;
+    entry.stop(clock.now());
+    repo.stop_time_entry(&entry)?;
+    Ok(HttpResponse::Ok().json(entry))
+}
+
+#[utoipa::path(
+    tag = "reports",
+    params(TimesheetQuery),
+    responses((status = 200, description = "Табель учета времени", body = TimesheetResponse))
+)]
+#[get("/reports/timesheet")]
+pub async fn timesheet_report(
+    repo: web::Data<Arc<dyn Repository>>,
+    query: web::Query<TimesheetQuery>,
+) -> Result<impl Responder, ApiError> {
+    Ok(HttpResponse::Ok().json(timesheet(repo.get_ref().as_ref(), &query)?))
+}
+
+// Выгрузка табеля в CSV с подписями на языке запроса
+#[utoipa::path(
+    tag = "reports",
+    params(TimesheetQuery),
+    responses((status = 200, description = "Табель учета времени в формате CSV", content_type = "text/csv", body = String))
+)]
+#[get("/reports/timesheet/export")]
+pub async fn export_timesheet(
+    repo: web::Data<Arc<dyn Repository>>,
+    query: web::Query<TimesheetQuery>,
+    locale: Locale,
+) -> Result<impl Responder, ApiError> {
+    let report = timesheet(repo.get_ref().as_ref(), &query)?;
+    let csv = timesheet_csv(&report, locale)
+        .map_err(|e| ApiError::Internal(format!("CSV export error: {}", e)))?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename("timesheet.csv".to_string())],
+        })
+        .body(csv))
+}
+
+// Завершенные записи за период. Границы периода относятся к часовому поясу
+// пользователя, поэтому выборка из хранилища берется с запасом в сутки
+fn timesheet(repo: &dyn Repository, query: &TimesheetQuery) -> Result<TimesheetResponse, ApiError> {
+    let start_of_day = |date: NaiveDate| date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+    let user_id = query.user_id.as_ref().map(|v| v.trim()).filter(|v| !v.is_empty()).map(str::to_string);
+    let filter = TimeEntryFilter {
+        task_id: None,
+        user_id,
+        started_from: query.from.and_then(|d| d.pred_opt()).map(start_of_day),
+        started_to: query.to.and_then(|d| d.succ_opt()).and_then(|d| d.succ_opt()).map(start_of_day),
+    };
+    let entries = repo.get_time_entries(&filter)?;
+
+    let users = all_users(repo)?;
+    let names: HashMap<String, String> = users.iter().map(|u| (u.id.clone(), u.name.clone())).collect();
+    let zones: HashMap<String, Tz> = users.iter().map(|u| (u.id.clone(), u.tz())).collect();
+
+    // Время учитывается и по удаленным, и по архивным задачам
+    let mut titles: HashMap<String, String> = HashMap::new();
+    let tasks = repo.get_tasks()?
+        .into_iter()
+        .chain(repo.get_deleted_tasks()?)
+        .chain(repo.get_archived_tasks(&ArchiveFilter::default())?);
+    for task in tasks {
+        titles.insert(task.id, task.title);
+    }
+
+    let mut rows = Vec::new();
+    let mut totals: BTreeMap<String, i64> = BTreeMap::new();
+    for entry in entries {
+        let Some(ended_at) = entry.ended_at else { continue };
+        let tz = zones.get(&entry.user_id).copied().unwrap_or_else(default_time_zone);
+        let date = entry.started_at.with_timezone(&tz).date_naive();
+        if query.from.is_some_and(|from| date < from) || query.to.is_some_and(|to| date > to) {
+            continue;
+        }
+
+        *totals.entry(entry.user_id.clone()).or_insert(0) += entry.minutes;
+        rows.push(TimesheetRow {
+            date,
+            user_name: names.get(&entry.user_id).cloned().unwrap_or_else(|| entry.user_id.clone()),
+            task_title: titles.get(&entry.task_id).cloned().unwrap_or_default(),
+            started_at_local: format_local(entry.started_at, tz),
+            ended_at_local: format_local(ended_at, tz),
+            user_id: entry.user_id,
+            task_id: entry.task_id,
+            minutes: entry.minutes,
+            note: entry.note,
+        });
+    }
+
+    let by_user: Vec<TimesheetTotal> = totals.into_iter()
+        .map(|(user_id, minutes)| TimesheetTotal {
+            user_name: names.get(&user_id).cloned().unwrap_or_else(|| user_id.clone()),
+            user_id,
+            minutes,
+        })
+        .collect();
+    let total_minutes = by_user.iter().map(|t| t.minutes).sum();
+
+    Ok(TimesheetResponse { rows, by_user, total_minutes })
+}
+
+fn timesheet_csv(report: &TimesheetResponse, locale: Locale) -> Result<String, csv::Error> {
+    // BOM нужен, чтобы Excel распознал UTF-8
+    let mut writer = csv::Writer::from_writer("\u{feff}".as_bytes().to_vec());
+
+    writer.write_record([
+        locale.t("timesheet.date"),
+        locale.t("timesheet.user"),
+        locale.t("timesheet.task"),
+        locale.t("timesheet.started_at"),
+        locale.t("timesheet.ended_at"),
+        locale.t("timesheet.hours"),
+        locale.t("timesheet.note"),
+    ])?;
+
+    for row in &report.rows {
+        writer.write_record([
+            row.date.format("%d.%m.%Y").to_string(),
+            row.user_name.clone(),
+            row.task_title.clone(),
+            row.started_at_local.clone(),
+            row.ended_at_local.clone(),
+            hours(row.minutes),
+            row.note.clone(),
+        ])?;
+    }
+
+    let bytes = writer.into_inner().map_err(|e| e.into_error())?;
+    Ok(String::from_utf8(bytes).expect("CSV is built from UTF-8 strings"))
+}
+
+// Часы с двумя знаками после запятой, как их выставляют подразделениям
+fn hours(minutes: i64) -> String {
+    format!("{:.2}", minutes as f64 / 60.0)
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_task_time_entries)
+       .service(create_time_entry)
+       .service(delete_time_entry)
+       .service(start_timer)
+       .service(stop_timer)
+       .service(timesheet_report)
+       .service(export_timesheet);
+}
@@ -0,0 +1,105 @@
+use actix_web::{web, HttpResponse, Responder, get, post};
+use serde::Serialize;
+use utoipa::ToSchema;
+use std::sync::Arc;
+
+use crate::calendar::BusinessCalendar;
+use crate::clock::Clock;
+use crate::db::Repository;
+use crate::models::User;
+use crate::trash::TrashSettings;
+use super::tasks::{executor_tz, load_executor_tz, TaskResponse};
+use super::{etag, ApiError, ErrorResponse};
+
+// Содержимое корзины: удаленные задачи и пользователи, начиная с последних
+#[derive(Serialize, ToSchema)]
+pub struct TrashResponse {
+    pub tasks: Vec<TaskResponse>,
+    pub users: Vec<User>,
+    pub retention_days: Option<u32>, // Через сколько дней записи удаляются окончательно
+}
+
+#[utoipa::path(
+    tag = "trash",
+    responses((status = 200, description = "Содержимое корзины", body = TrashResponse))
+)]
+#[get("/trash")]
+pub async fn get_trash(
+    repo: web::Data<Arc<dyn Repository>>,
+    calendar: web::Data<BusinessCalendar>,
+    clock: web::Data<Arc<dyn Clock>>,
+    settings: web::Data<TrashSettings>,
+) -> Result<impl Responder, ApiError> {
+    let now = clock.now();
+    // Часовые пояса берутся с учетом удаленных пользователей
+    let mut users = repo.get_users()?;
+    let deleted_users = repo.get_deleted_users()?;
+    users.extend(deleted_users.iter().cloned());
+    let zones = users.into_iter().map(|user| (user.id.clone(), user.tz())).collect();
+
+    let tasks = repo.get_deleted_tasks()?
+        .into_iter()
+        .map(|task| {
+            let tz = executor_tz(&zones, &task);
+            TaskResponse::new(task, &calendar, tz, now)
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(TrashResponse {
+        tasks,
+        users: deleted_users,
+        retention_days: settings.retention_days,
+    }))
+}
+
+#[utoipa::path(
+    tag = "trash",
+    params(("id" = String, Path, description = "Идентификатор задачи")),
+    responses(
+        (status = 200, description = "Задача восстановлена", body = TaskResponse),
+        (status = 404, description = "Задача не найдена в корзине", body = ErrorResponse)
+    )
+)]
+#[post("/trash/tasks/{id}/restore")]
+pub async fn restore_task(
+    repo: web::Data<Arc<dyn Repository>>,
+    calendar: web::Data<BusinessCalendar>,
+    clock: web::Data<Arc<dyn Clock>>,
+    path: web::Path<String>,
+) -> Result<impl Responder, ApiError> {
+    let now = clock.now();
+    let id = path.into_inner();
+    repo.restore_task(&id)?;
+
+    let task = repo.get_task_by_id(&id)?;
+    let tz = load_executor_tz(repo.get_ref().as_ref(), &task);
+    Ok(HttpResponse::Ok()
+        .insert_header(etag(task.version))
+        .json(TaskResponse::new(task, &calendar, tz, now)))
+}
+
+#[utoipa::path(
+    tag = "trash",
+    params(("id" = String, Path, description = "Идентификатор пользователя")),
+    responses(
+        (status = 200, description = "Пользователь восстановлен", body = User),
+        (status = 404, description = "Пользователь не найден в корзине", body = ErrorResponse)
+    )
+)]
+#[post("/trash/users/{id}/restore")]
+pub async fn restore_user(
+    repo: web::Data<Arc<dyn Repository>>,
+    path: web::Path<String>,
+) -> Result<impl Responder, ApiError> {
+    let id = path.into_inner();
+    repo.restore_user(&id)?;
+
+    let user = repo.get_user_by_id(&id)?;
+    Ok(HttpResponse::Ok().insert_header(etag(user.version)).json(user))
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_trash)
+       .service(restore_task)
+       .service(restore_user);
+}
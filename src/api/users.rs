@@ -1,31 +1,115 @@
-use actix_web::{web, HttpResponse, Responder, get, post, put, delete};
+use actix_web::{web, HttpRequest, HttpResponse, Responder, get, post, put, patch, delete};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use std::sync::Arc;
 
-use crate::db::Repository;
-use crate::models::{User, UserRole};
-use super::ApiError;
+use crate::calendar::timezone::parse_time_zone;
+use crate::calendar::BusinessCalendar;
+use crate::clock::Clock;
+use crate::db::{Repository, TaskChange};
+use crate::i18n::Locale;
+use crate::models::user::DEFAULT_TIME_ZONE;
+use crate::models::{Delegation, Task, User, UserRole};
+use crate::validation::{self, Validator};
+use super::tasks::{executor_tz, user_time_zones, TaskResponse};
+use super::{check_if_match, etag, non_null, nullable, ApiError, ErrorResponse};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct CreateUserRequest {
     pub name: String,
     pub email: String,
     pub role: String,
+    pub time_zone: Option<String>,
+    pub locale: Option<String>, // ru или en
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct UpdateUserRequest {
     pub name: String,
     pub email: String,
     pub role: String,
+    pub time_zone: Option<String>,
+    pub locale: Option<String>, // ru или en
 }
 
+// Частичное обновление пользователя (JSON Merge Patch): отсутствующие поля
+// не меняются, "time_zone": null возвращает часовой пояс по умолчанию,
+// "locale": null - выбор языка по запросу
+#[derive(Deserialize, ToSchema)]
+pub struct PatchUserRequest {
+    #[serde(default, deserialize_with = "non_null")]
+    pub name: Option<String>,
+    #[serde(default, deserialize_with = "non_null")]
+    pub email: Option<String>,
+    #[serde(default, deserialize_with = "non_null")]
+    pub role: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>)]
+    pub time_zone: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>)]
+    pub locale: Option<Option<String>>,
+}
+
+// Без параметра возвращаются все пользователи, включая деактивированных
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UsersQuery {
+    pub active: Option<bool>,
+}
+
+// Что сделать, если удаляемый пользователь - заказчик или исполнитель задач
+#[derive(Deserialize, ToSchema, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OnTasks {
+    Reassign,   // Передать задачи пользователю to_user_id и удалить
+    Deactivate, // Не удалять, а деактивировать
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeleteUserQuery {
+    #[param(inline)]
+    pub on_tasks: Option<OnTasks>,
+    pub to_user_id: Option<String>, // Получатель задач при on_tasks=reassign
+    pub by_user_id: Option<String>, // Кто передает задачи; записывается в историю передач
+    pub reason: Option<String>,     // Причина передачи для истории задачи
+}
+
+// Задача пользователя с его ролями в ней
+#[derive(Serialize, ToSchema)]
+pub struct UserTaskResponse {
+    #[serde(flatten)]
+    pub task: TaskResponse,
+    pub roles: Vec<&'static str>, // customer, executor, co_executor, watcher
+}
+
+#[utoipa::path(
+    tag = "users",
+    params(UsersQuery),
+    responses((status = 200, description = "Список пользователей", body = [User]))
+)]
 #[get("/users")]
-pub async fn get_users(repo: web::Data<Arc<dyn Repository>>) -> Result<impl Responder, ApiError> {
-    let users = repo.get_users()?;
+pub async fn get_users(
+    repo: web::Data<Arc<dyn Repository>>,
+    query: web::Query<UsersQuery>,
+) -> Result<impl Responder, ApiError> {
+    let users: Vec<User> = repo.get_users()?
+        .into_iter()
+        .filter(|user| query.active.is_none_or(|active| user.active == active))
+        .collect();
     Ok(HttpResponse::Ok().json(users))
 }
 
+#[utoipa::path(
+    tag = "users",
+    params(("id" = String, Path, description = "Идентификатор пользователя")),
+    responses(
+        (status = 200, description = "Пользователь; заголовок ETag содержит его версию", body = User),
+        (status = 404, description = "Пользователь не найден", body = ErrorResponse)
+    )
+)]
 #[get("/users/{id}")]
 pub async fn get_user(
     repo: web::Data<Arc<dyn Repository>>,
@@ -33,63 +117,405 @@ pub async fn get_user(
 ) -> Result<impl Responder, ApiError> {
     let id = path.into_inner();
     let user = repo.get_user_by_id(&id)?;
-    Ok(HttpResponse::Ok().json(user))
+    Ok(HttpResponse::Ok().insert_header(etag(user.version)).json(user))
 }
 
+#[utoipa::path(
+    tag = "users",
+    request_body = CreateUserRequest,
+    responses(
+        (status = 201, description = "Пользователь создан", body = User),
+        (status = 400, description = "Некорректный запрос", body = ErrorResponse),
+        (status = 422, description = "Поля не прошли проверку", body = ErrorResponse)
+    )
+)]
 #[post("/users")]
 pub async fn create_user(
     repo: web::Data<Arc<dyn Repository>>,
     req: web::Json<CreateUserRequest>,
 ) -> Result<impl Responder, ApiError> {
-    let user_role = match req.role.as_str() {
-        "executor" => UserRole::Executor,
-        "customer" => UserRole::Customer,
-        _ => return Err(ApiError::BadRequest("Invalid user role".to_string())),
-    };
+    let user_role = parse_role(&req.role)?;
 
-    let user = User::new(req.name.clone(), req.email.clone(), user_role);
+    let mut v = Validator::new();
+    validation::user_name(&mut v, &req.name);
+    validation::user_email(&mut v, &req.email);
+    v.finish().map_err(ApiError::Validation)?;
+
+    let mut user = User::new(req.name.clone(), req.email.clone(), user_role);
+    if let Some(time_zone) = &req.time_zone {
+        user.time_zone = validate_time_zone(time_zone)?;
+    }
+    if let Some(locale) = &req.locale {
+        user.locale = Some(validate_locale(locale)?);
+    }
     repo.create_user(&user)?;
 
-    Ok(HttpResponse::Created().json(user))
+    Ok(HttpResponse::Created().insert_header(etag(user.version)).json(user))
 }
 
+#[utoipa::path(
+    tag = "users",
+    params(
+        ("id" = String, Path, description = "Идентификатор пользователя"),
+        ("If-Match" = String, Header, description = "Версия пользователя из заголовка ETag")
+    ),
+    request_body = UpdateUserRequest,
+    responses(
+        (status = 200, description = "Пользователь обновлен", body = User),
+        (status = 400, description = "Некорректный запрос", body = ErrorResponse),
+        (status = 404, description = "Пользователь не найден", body = ErrorResponse),
+        (status = 412, description = "Пользователь изменен другим пользователем", body = ErrorResponse),
+        (status = 422, description = "Поля не прошли проверку", body = ErrorResponse),
+        (status = 428, description = "Не передан заголовок If-Match", body = ErrorResponse)
+    )
+)]
 #[put("/users/{id}")]
 pub async fn update_user(
     repo: web::Data<Arc<dyn Repository>>,
     path: web::Path<String>,
+    http_req: HttpRequest,
     req: web::Json<UpdateUserRequest>,
 ) -> Result<impl Responder, ApiError> {
     let id = path.into_inner();
     
     let mut user = repo.get_user_by_id(&id)?;
+    check_if_match(&http_req, user.version)?;
+
+    let mut v = Validator::new();
+    validation::user_name(&mut v, &req.name);
+    validation::user_email(&mut v, &req.email);
+    v.finish().map_err(ApiError::Validation)?;
+
     user.name = req.name.clone();
     user.email = req.email.clone();
     
-    user.role = match req.role.as_str() {
-        "executor" => UserRole::Executor,
-        "customer" => UserRole::Customer,
-        _ => return Err(ApiError::BadRequest("Invalid user role".to_string())),
-    };
+    user.role = parse_role(&req.role)?;
+
+    if let Some(time_zone) = &req.time_zone {
+        user.time_zone = validate_time_zone(time_zone)?;
+    }
+    if let Some(locale) = &req.locale {
+        user.locale = Some(validate_locale(locale)?);
+    }
+
+    repo.update_user(&user)?;
+    user.version += 1;
+
+    Ok(HttpResponse::Ok().insert_header(etag(user.version)).json(user))
+}
+
+#[utoipa::path(
+    tag = "users",
+    params(
+        ("id" = String, Path, description = "Идентификатор пользователя"),
+        ("If-Match" = String, Header, description = "Версия пользователя из заголовка ETag")
+    ),
+    request_body(content = PatchUserRequest, content_type = "application/merge-patch+json"),
+    responses(
+        (status = 200, description = "Пользователь обновлен", body = User),
+        (status = 400, description = "Некорректный запрос", body = ErrorResponse),
+        (status = 404, description = "Пользователь не найден", body = ErrorResponse),
+        (status = 412, description = "Пользователь изменен другим пользователем", body = ErrorResponse),
+        (status = 422, description = "Поля не прошли проверку", body = ErrorResponse),
+        (status = 428, description = "Не передан заголовок If-Match", body = ErrorResponse)
+    )
+)]
+#[patch("/users/{id}")]
+pub async fn patch_user(
+    repo: web::Data<Arc<dyn Repository>>,
+    path: web::Path<String>,
+    http_req: HttpRequest,
+    req: web::Json<PatchUserRequest>,
+) -> Result<impl Responder, ApiError> {
+    let id = path.into_inner();
+    let req = req.into_inner();
+
+    let mut user = repo.get_user_by_id(&id)?;
+    check_if_match(&http_req, user.version)?;
+
+    let mut v = Validator::new();
+    if let Some(name) = &req.name {
+        validation::user_name(&mut v, name);
+    }
+    if let Some(email) = &req.email {
+        validation::user_email(&mut v, email);
+    }
+    v.finish().map_err(ApiError::Validation)?;
+
+    if let Some(name) = req.name {
+        user.name = name;
+    }
+    if let Some(email) = req.email {
+        user.email = email;
+    }
+    if let Some(role) = req.role {
+        user.role = parse_role(&role)?;
+    }
+    if let Some(time_zone) = req.time_zone {
+        user.time_zone = match time_zone {
+            Some(time_zone) => validate_time_zone(&time_zone)?,
+            None => DEFAULT_TIME_ZONE.to_string(),
+        };
+    }
+    if let Some(locale) = req.locale {
+        user.locale = locale.as_deref().map(validate_locale).transpose()?;
+    }
 
     repo.update_user(&user)?;
+    user.version += 1;
 
-    Ok(HttpResponse::Ok().json(user))
+    Ok(HttpResponse::Ok().insert_header(etag(user.version)).json(user))
 }
 
+#[utoipa::path(
+    tag = "users",
+    params(
+        ("id" = String, Path, description = "Идентификатор пользователя"),
+        ("If-Match" = String, Header, description = "Версия пользователя из заголовка ETag"),
+        DeleteUserQuery
+    ),
+    responses(
+        (status = 200, description = "Пользователь деактивирован (on_tasks=deactivate)", body = User),
+        (status = 204, description = "Пользователь удален"),
+        (status = 400, description = "Не указан или не подходит получатель задач", body = ErrorResponse),
+        (status = 404, description = "Пользователь или получатель задач не найден", body = ErrorResponse),
+        (status = 409, description = "Пользователь - заказчик или исполнитель задач, а способ их передачи не выбран, или на него ссылаются задачи в корзине или проекты", body = ErrorResponse),
+        (status = 412, description = "Пользователь изменен другим пользователем", body = ErrorResponse),
+        (status = 428, description = "Не передан заголовок If-Match", body = ErrorResponse)
+    )
+)]
 #[delete("/users/{id}")]
 pub async fn delete_user(
     repo: web::Data<Arc<dyn Repository>>,
     path: web::Path<String>,
+    http_req: HttpRequest,
+    query: web::Query<DeleteUserQuery>,
+    clock: web::Data<Arc<dyn Clock>>,
+    locale: Locale,
 ) -> Result<impl Responder, ApiError> {
+    let now = clock.now();
     let id = path.into_inner();
-    repo.delete_user(&id)?;
+    let user = repo.get_user_by_id(&id)?;
+    check_if_match(&http_req, user.version)?;
+
+    if query.on_tasks == Some(OnTasks::Deactivate) {
+        repo.deactivate_user(&id, now)?;
+        let user = repo.get_user_by_id(&id)?;
+        return Ok(HttpResponse::Ok().insert_header(etag(user.version)).json(user));
+    }
+
+    // Поручения в корзине и проекты при удалении не передаются: пока они
+    // ссылаются на пользователя, его можно только деактивировать
+    if repo.get_deleted_tasks()?.iter().any(|task| task.customer_id == id || task.executor_id == id) {
+        return Err(ApiError::Conflict("errors.user_has_deleted_tasks".to_string()));
+    }
+    if repo.get_projects()?.iter().any(|project| project.owner_id == id || project.customer_id.as_deref() == Some(id.as_str())) {
+        return Err(ApiError::Conflict("errors.user_has_projects".to_string()));
+    }
+
+    // Удалить пользователя, на которого ссылаются задачи, можно только передав их
+    let tasks: Vec<Task> = repo.get_tasks()?
+        .into_iter()
+        .filter(|task| task.customer_id == id || task.executor_id == id)
+        .collect();
+    if tasks.is_empty() {
+        repo.delete_user(&id, now)?;
+        return Ok(HttpResponse::NoContent().finish());
+    }
+    if query.on_tasks.is_none() {
+        return Err(ApiError::Conflict("errors.user_has_tasks".to_string()));
+    }
+
+    let to_user_id = query.to_user_id.as_deref()
+        .filter(|to_user_id| !to_user_id.is_empty())
+        .ok_or_else(|| ApiError::BadRequest("errors.handover_target_required".to_string()))?;
+    if to_user_id == id {
+        return Err(ApiError::BadRequest("errors.handover_same_user".to_string()));
+    }
+    let to_user = repo.get_user_by_id(to_user_id)?;
+    check_assignable(&to_user)?;
+    if to_user.role != user.role {
+        return Err(ApiError::BadRequest("errors.handover_role_mismatch".to_string()));
+    }
+    let by_user_id = query.by_user_id.as_deref()
+        .filter(|by_user_id| !by_user_id.is_empty())
+        .ok_or_else(|| ApiError::BadRequest("errors.handover_actor_required".to_string()))?;
+    if by_user_id == id {
+        return Err(ApiError::BadRequest("errors.handover_actor_deleted".to_string()));
+    }
+    repo.get_user_by_id(by_user_id)?;
+
+    let reason = query.reason.as_deref()
+        .map(str::trim)
+        .filter(|reason| !reason.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| locale.t("users.handover_reason"));
+    let changes = handover_changes(tasks, &id, to_user_id, by_user_id, &reason, now);
+
+    repo.delete_user_with_handover(&id, &changes, now)?;
     Ok(HttpResponse::NoContent().finish())
 }
 
+#[utoipa::path(
+    tag = "users",
+    params(
+        ("id" = String, Path, description = "Идентификатор пользователя"),
+        ("If-Match" = String, Header, description = "Версия пользователя из заголовка ETag")
+    ),
+    responses(
+        (status = 200, description = "Пользователь деактивирован", body = User),
+        (status = 404, description = "Пользователь не найден", body = ErrorResponse),
+        (status = 412, description = "Пользователь изменен другим пользователем", body = ErrorResponse),
+        (status = 428, description = "Не передан заголовок If-Match", body = ErrorResponse)
+    )
+)]
+#[post("/users/{id}/deactivate")]
+pub async fn deactivate_user(
+    repo: web::Data<Arc<dyn Repository>>,
+    path: web::Path<String>,
+    http_req: HttpRequest,
+    clock: web::Data<Arc<dyn Clock>>,
+) -> Result<impl Responder, ApiError> {
+    let id = path.into_inner();
+    let user = repo.get_user_by_id(&id)?;
+    check_if_match(&http_req, user.version)?;
+
+    repo.deactivate_user(&id, clock.now())?;
+    let user = repo.get_user_by_id(&id)?;
+    Ok(HttpResponse::Ok().insert_header(etag(user.version)).json(user))
+}
+
+#[utoipa::path(
+    tag = "users",
+    params(
+        ("id" = String, Path, description = "Идентификатор пользователя"),
+        ("If-Match" = String, Header, description = "Версия пользователя из заголовка ETag")
+    ),
+    responses(
+        (status = 200, description = "Пользователь снова активен", body = User),
+        (status = 404, description = "Пользователь не найден", body = ErrorResponse),
+        (status = 412, description = "Пользователь изменен другим пользователем", body = ErrorResponse),
+        (status = 428, description = "Не передан заголовок If-Match", body = ErrorResponse)
+    )
+)]
+#[post("/users/{id}/reactivate")]
+pub async fn reactivate_user(
+    repo: web::Data<Arc<dyn Repository>>,
+    path: web::Path<String>,
+    http_req: HttpRequest,
+) -> Result<impl Responder, ApiError> {
+    let id = path.into_inner();
+    let user = repo.get_user_by_id(&id)?;
+    check_if_match(&http_req, user.version)?;
+
+    repo.reactivate_user(&id)?;
+    let user = repo.get_user_by_id(&id)?;
+    Ok(HttpResponse::Ok().insert_header(etag(user.version)).json(user))
+}
+
+// Деактивированного пользователя нельзя назначить заказчиком, исполнителем
+// или участником задачи; в истории и отчетах он остается
+pub fn check_assignable(user: &User) -> Result<(), ApiError> {
+    if !user.active {
+        return Err(ApiError::BadRequest("errors.user_inactive".to_string()));
+    }
+    Ok(())
+}
+
+// Изменения задач при передаче от удаляемого пользователя: заказчик меняется
+// обновлением задачи, исполнитель - передачей с записью в историю от имени by_user_id
+fn handover_changes(
+    tasks: Vec<Task>,
+    from_user_id: &str,
+    to_user_id: &str,
+    by_user_id: &str,
+    reason: &str,
+    now: DateTime<Utc>,
+) -> Vec<TaskChange> {
+    let mut changes = Vec::new();
+    for mut task in tasks {
+        if task.customer_id == from_user_id {
+            task.customer_id = to_user_id.to_string();
+            changes.push(TaskChange::Update(task.clone()));
+            task.version += 1;
+        }
+        if task.executor_id == from_user_id {
+            let delegation = Delegation::new(
+                task.id.clone(),
+                from_user_id.to_string(),
+                to_user_id.to_string(),
+                by_user_id.to_string(),
+                reason.to_string(),
+                now,
+            );
+            changes.push(TaskChange::Reassign(task, delegation));
+        }
+    }
+    changes
+}
+
+// "Мои задачи": задачи, в которых пользователь заказчик, исполнитель,
+// соисполнитель или наблюдатель
+#[utoipa::path(
+    tag = "users",
+    params(("id" = String, Path, description = "Идентификатор пользователя")),
+    responses(
+        (status = 200, description = "Задачи с участием пользователя", body = [UserTaskResponse]),
+        (status = 404, description = "Пользователь не найден", body = ErrorResponse)
+    )
+)]
+#[get("/users/{id}/tasks")]
+pub async fn get_user_tasks(
+    repo: web::Data<Arc<dyn Repository>>,
+    calendar: web::Data<BusinessCalendar>,
+    clock: web::Data<Arc<dyn Clock>>,
+    path: web::Path<String>,
+) -> Result<impl Responder, ApiError> {
+    let now = clock.now();
+    let id = path.into_inner();
+    repo.get_user_by_id(&id)?;
+
+    let zones = user_time_zones(repo.get_ref().as_ref())?;
+    let tasks: Vec<UserTaskResponse> = repo.get_tasks()?
+        .into_iter()
+        .filter_map(|task| {
+            let roles = task.roles_of(&id);
+            if roles.is_empty() {
+                return None;
+            }
+            let tz = executor_tz(&zones, &task);
+            Some(UserTaskResponse { task: TaskResponse::new(task, &calendar, tz, now), roles })
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(tasks))
+}
+
+fn parse_role(code: &str) -> Result<UserRole, ApiError> {
+    match code {
+        "executor" => Ok(UserRole::Executor),
+        "customer" => Ok(UserRole::Customer),
+        _ => Err(ApiError::BadRequest("errors.invalid_role".to_string())),
+    }
+}
+
+fn validate_time_zone(name: &str) -> Result<String, ApiError> {
+    parse_time_zone(name)
+        .map(|tz| tz.name().to_string())
+        .ok_or_else(|| ApiError::BadRequest("errors.invalid_time_zone".to_string()))
+}
+
+fn validate_locale(code: &str) -> Result<Locale, ApiError> {
+    Locale::parse(code).ok_or_else(|| ApiError::BadRequest("errors.invalid_locale".to_string()))
+}
+
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(get_users)
        .service(get_user)
        .service(create_user)
        .service(update_user)
-       .service(delete_user);
+       .service(patch_user)
+       .service(delete_user)
+       .service(deactivate_user)
+       .service(reactivate_user)
+       .service(get_user_tasks);
 }
@@ -0,0 +1,319 @@
+use actix_web::{web, HttpResponse, Responder, get, post, put, delete};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use std::sync::Arc;
+
+use crate::clock::Clock;
+use crate::db::{Entity, Repository, RepositoryError};
+use crate::models::{SavedView, TaskPriority, TaskStatus, ViewFilters};
+use super::{ApiError, ErrorResponse};
+
+// Пользователь, от имени которого выполняется запрос
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ViewUserQuery {
+    pub user_id: String,
+}
+
+// Без пользователя доступны только общие виды
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ViewQuery {
+    pub user_id: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct SaveViewRequest {
+    pub owner_id: String, // При изменении и удалении должен совпадать с владельцем вида
+    pub name: String,
+    #[serde(default)]
+    pub filters: ViewFilters,
+    pub sort: Option<String>,
+    #[serde(default)]
+    pub columns: Vec<String>,
+    #[serde(default)]
+    pub shared: bool,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct DefaultViewRequest {
+    pub view_id: Option<String>, // null сбрасывает вид по умолчанию
+}
+
+// Вид с признаком вида по умолчанию и адресом страницы, открывающей его
+#[derive(Serialize, ToSchema)]
+pub struct SavedViewResponse {
+    #[serde(flatten)]
+    pub view: SavedView,
+    pub is_default: bool,
+    pub url: String,
+}
+
+impl SavedViewResponse {
+    fn new(view: SavedView, default_view_id: Option<&str>) -> Self {
+        let is_default = default_view_id == Some(view.id.as_str());
+        let url = format!("/?view={}", view.id);
+        Self { view, is_default, url }
+    }
+}
+
+#[utoipa::path(
+    tag = "views",
+    params(ViewUserQuery),
+    responses(
+        (status = 200, description = "Собственные виды пользователя и общие виды коллег", body = [SavedViewResponse]),
+        (status = 404, description = "Пользователь не найден", body = ErrorResponse)
+    )
+)]
+#[get("/views")]
+pub async fn get_views(
+    repo: web::Data<Arc<dyn Repository>>,
+    query: web::Query<ViewUserQuery>,
+) -> Result<impl Responder, ApiError> {
+    repo.get_user_by_id(&query.user_id)?;
+    let default_view_id = repo.get_default_view_id(&query.user_id)?;
+
+    let views: Vec<SavedViewResponse> = repo.get_saved_views(&query.user_id)?
+        .into_iter()
+        .map(|view| SavedViewResponse::new(view, default_view_id.as_deref()))
+        .collect();
+    Ok(HttpResponse::Ok().json(views))
+}
+
+#[utoipa::path(
+    tag = "views",
+    params(
+        ("id" = String, Path, description = "Идентификатор вида"),
+        ViewQuery
+    ),
+    responses(
+        (status = 200, description = "Вид", body = SavedViewResponse),
+        (status = 404, description = "Вид не найден или недоступен пользователю", body = ErrorResponse)
+    )
+)]
+#[get("/views/{id}")]
+pub async fn get_view(
+    repo: web::Data<Arc<dyn Repository>>,
+    path: web::Path<String>,
+    query: web::Query<ViewQuery>,
+) -> Result<impl Responder, ApiError> {
+    let user_id = query.user_id.as_deref().unwrap_or_default();
+    let view = available_view(repo.get_ref().as_ref(), &path.into_inner(), user_id)?;
+    let default_view_id = repo.get_default_view_id(user_id)?;
+    Ok(HttpResponse::Ok().json(SavedViewResponse::new(view, default_view_id.as_deref())))
+}
+
+#[utoipa::path(
+    tag = "views",
+    request_body = SaveViewRequest,
+    responses(
+        (status = 201, description = "Вид сохранен", body = SavedViewResponse),
+        (status = 400, description = "Некорректные условия, сортировка или колонки", body = ErrorResponse),
+        (status = 404, description = "Владелец не найден", body = ErrorResponse)
+    )
+)]
+#[post("/views")]
+pub async fn create_view(
+    repo: web::Data<Arc<dyn Repository>>,
+    clock: web::Data<Arc<dyn Clock>>,
+    req: web::Json<SaveViewRequest>,
+) -> Result<impl Responder, ApiError> {
+    let req = req.into_inner();
+    repo.get_user_by_id(&req.owner_id)?;
+
+    let mut view = SavedView::new(req.owner_id.clone(), String::new(), clock.now());
+    apply_request(&mut view, req)?;
+
+    repo.create_saved_view(&view)?;
+    Ok(HttpResponse::Created().json(SavedViewResponse::new(view, None)))
+}
+
+#[utoipa::path(
+    tag = "views",
+    params(("id" = String, Path, description = "Идентификатор вида")),
+    request_body = SaveViewRequest,
+    responses(
+        (status = 200, description = "Вид изменен", body = SavedViewResponse),
+        (status = 400, description = "Некорректный запрос или вид принадлежит другому пользователю", body = ErrorResponse),
+        (status = 404, description = "Вид не найден", body = ErrorResponse)
+    )
+)]
+#[put("/views/{id}")]
+pub async fn update_view(
+    repo: web::Data<Arc<dyn Repository>>,
+    path: web::Path<String>,
+    req: web::Json<SaveViewRequest>,
+) -> Result<impl Responder, ApiError> {
+    let req = req.into_inner();
+    let mut view = repo.get_saved_view_by_id(&path.into_inner())?;
+    check_owner(&view, &req.owner_id)?;
+
+    apply_request(&mut view, req)?;
+    repo.update_saved_view(&view)?;
+
+    let default_view_id = repo.get_default_view_id(&view.owner_id)?;
+    Ok(HttpResponse::Ok().json(SavedViewResponse::new(view, default_view_id.as_deref())))
+}
+
+#[utoipa::path(
+    tag = "views",
+    params(
+        ("id" = String, Path, description = "Идентификатор вида"),
+        ViewUserQuery
+    ),
+    responses(
+        (status = 204, description = "Вид удален"),
+        (status = 400, description = "Вид принадлежит другому пользователю", body = ErrorResponse),
+        (status = 404, description = "Вид не найден", body = ErrorResponse)
+    )
+)]
+#[delete("/views/{id}")]
+pub async fn delete_view(
+    repo: web::Data<Arc<dyn Repository>>,
+    path: web::Path<String>,
+    query: web::Query<ViewUserQuery>,
+) -> Result<impl Responder, ApiError> {
+    let view = repo.get_saved_view_by_id(&path.into_inner())?;
+    check_owner(&view, &query.user_id)?;
+    repo.delete_saved_view(&view.id)?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    tag = "views",
+    params(("id" = String, Path, description = "Идентификатор пользователя")),
+    responses(
+        (status = 200, description = "Вид по умолчанию", body = SavedViewResponse),
+        (status = 404, description = "Пользователь не найден или вид по умолчанию не выбран", body = ErrorResponse)
+    )
+)]
+#[get("/users/{id}/default-view")]
+pub async fn get_default_view(
+    repo: web::Data<Arc<dyn Repository>>,
+    path: web::Path<String>,
+) -> Result<impl Responder, ApiError> {
+    let user_id = path.into_inner();
+    repo.get_user_by_id(&user_id)?;
+
+    let view_id = repo.get_default_view_id(&user_id)?
+        .ok_or_else(|| ApiError::NotFound("errors.default_view_not_set".to_string()))?;
+    let view = repo.get_saved_view_by_id(&view_id)?;
+    Ok(HttpResponse::Ok().json(SavedViewResponse::new(view, Some(&view_id))))
+}
+
+#[utoipa::path(
+    tag = "views",
+    params(("id" = String, Path, description = "Идентификатор пользователя")),
+    request_body = DefaultViewRequest,
+    responses(
+        (status = 204, description = "Вид по умолчанию изменен"),
+        (status = 404, description = "Пользователь или доступный ему вид не найден", body = ErrorResponse)
+    )
+)]
+#[put("/users/{id}/default-view")]
+pub async fn set_default_view(
+    repo: web::Data<Arc<dyn Repository>>,
+    path: web::Path<String>,
+    req: web::Json<DefaultViewRequest>,
+) -> Result<impl Responder, ApiError> {
+    let user_id = path.into_inner();
+    repo.get_user_by_id(&user_id)?;
+
+    if let Some(view_id) = &req.view_id {
+        available_view(repo.get_ref().as_ref(), view_id, &user_id)?;
+    }
+
+    repo.set_default_view(&user_id, req.view_id.as_deref())?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// Чужой необщий вид для пользователя не существует
+fn available_view(repo: &dyn Repository, id: &str, user_id: &str) -> Result<SavedView, ApiError> {
+    let view = repo.get_saved_view_by_id(id)?;
+    if !view.is_available_to(user_id) {
+        return Err(RepositoryError::not_found(Entity::SavedView, id).into());
+    }
+    Ok(view)
+}
+
+fn check_owner(view: &SavedView, user_id: &str) -> Result<(), ApiError> {
+    if view.owner_id != user_id {
+        return Err(ApiError::BadRequest("errors.view_not_owner".to_string()));
+    }
+    Ok(())
+}
+
+fn apply_request(view: &mut SavedView, req: SaveViewRequest) -> Result<(), ApiError> {
+    let name = req.name.trim();
+    if name.is_empty() {
+        return Err(ApiError::BadRequest("errors.view_name_required".to_string()));
+    }
+
+    view.name = name.to_string();
+    view.filters = validate_filters(req.filters)?;
+    view.sort = validate_sort(req.sort)?;
+    view.columns = validate_columns(req.columns)?;
+    view.shared = req.shared;
+    Ok(())
+}
+
+// Пустые значения не ограничивают выборку и не сохраняются
+fn validate_filters(filters: ViewFilters) -> Result<ViewFilters, ApiError> {
+    let non_empty = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+
+    let status = non_empty(filters.status);
+    if let Some(status) = &status {
+        if !TaskStatus::ALL.iter().any(|s| s.code() == status) {
+            return Err(ApiError::BadRequest("errors.invalid_status".to_string()));
+        }
+    }
+    let priority = non_empty(filters.priority);
+    if let Some(priority) = &priority {
+        if !TaskPriority::ALL.iter().any(|p| p.code() == priority) {
+            return Err(ApiError::BadRequest("errors.invalid_priority".to_string()));
+        }
+    }
+
+    Ok(ViewFilters {
+        status,
+        priority,
+        customer_id: non_empty(filters.customer_id),
+        executor_id: non_empty(filters.executor_id),
+        participant_id: non_empty(filters.participant_id),
+        project_id: non_empty(filters.project_id),
+        tags: filters.tags.into_iter().filter_map(|tag| non_empty(Some(tag))).collect(),
+        search: non_empty(filters.search),
+    })
+}
+
+fn validate_sort(sort: Option<String>) -> Result<Option<String>, ApiError> {
+    let Some(sort) = sort.filter(|s| !s.is_empty()) else {
+        return Ok(None);
+    };
+    let field = sort.strip_prefix('-').unwrap_or(&sort);
+    if !SavedView::SORT_FIELDS.contains(&field) {
+        return Err(ApiError::BadRequest("errors.invalid_view_sort".to_string()));
+    }
+    Ok(Some(sort))
+}
+
+fn validate_columns(columns: Vec<String>) -> Result<Vec<String>, ApiError> {
+    if columns.iter().any(|c| !SavedView::COLUMNS.contains(&c.as_str())) {
+        return Err(ApiError::BadRequest("errors.invalid_view_columns".to_string()));
+    }
+    // Колонки хранятся в порядке таблицы, без повторов
+    Ok(SavedView::COLUMNS.iter()
+        .filter(|c| columns.iter().any(|column| column == *c))
+        .map(|c| c.to_string())
+        .collect())
+}
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_views)
+       .service(get_view)
+       .service(create_view)
+       .service(update_view)
+       .service(delete_view)
+       .service(get_default_view)
+       .service(set_default_view);
+}
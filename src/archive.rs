@@ -0,0 +1,32 @@
+use std::sync::Arc;
+
+use crate::clock::Clock;
+use crate::db::Repository;
+use crate::jobs;
+
+// Через сколько дней после завершения или отмены задача переносится в архив
+// (ARCHIVE_AFTER_DAYS, по умолчанию 365 дней). 0 отключает автоматическую архивацию
+#[derive(Debug, Clone, Copy)]
+pub struct ArchiveSettings {
+    pub after_days: Option<u32>,
+}
+
+impl ArchiveSettings {
+    pub const DEFAULT_AFTER_DAYS: u32 = 365;
+
+    pub fn from_env() -> Result<Self, String> {
+        let after_days = jobs::days_from_env("ARCHIVE_AFTER_DAYS", Self::DEFAULT_AFTER_DAYS)?;
+        Ok(Self { after_days })
+    }
+}
+
+// Фоновая архивация завершенных и отмененных задач
+pub fn spawn_archiver(repo: Arc<dyn Repository>, clock: Arc<dyn Clock>, settings: ArchiveSettings) {
+    jobs::spawn_periodic(
+        settings.after_days,
+        clock,
+        "Перенесено в архив задач",
+        "Ошибка архивации задач",
+        move |cutoff, now| repo.archive_tasks(cutoff, now),
+    );
+}
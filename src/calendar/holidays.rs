@@ -0,0 +1,56 @@
+use chrono::NaiveDate;
+
+use super::CalendarError;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DayKind {
+    Holiday, // Нерабочий праздничный день
+    Workday, // Перенесенный рабочий день (например, рабочая суббота)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayEntry {
+    pub date: NaiveDate,
+    pub kind: DayKind,
+}
+
+// Разбирает файл производственного календаря.
+// Формат: одна дата на строку в виде "YYYY-MM-DD [holiday|workday]",
+// по умолчанию дата считается праздником; "#" начинает комментарий.
+//
+//     2025-01-01            # Новый год
+//     2025-11-01 workday    # рабочая суббота
+pub fn parse(content: &str) -> Result<Vec<DayEntry>, CalendarError> {
+    let mut entries = Vec::new();
+
+    for (index, raw_line) in content.lines().enumerate() {
+        let line = raw_line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut parts = line.split_whitespace();
+        let date_str = parts.next().unwrap_or("");
+        let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d").map_err(|_| {
+            CalendarError::Parse {
+                line: index + 1,
+                message: format!("invalid date {}", date_str),
+            }
+        })?;
+
+        let kind = match parts.next() {
+            None | Some("holiday") => DayKind::Holiday,
+            Some("workday") => DayKind::Workday,
+            Some(other) => {
+                return Err(CalendarError::Parse {
+                    line: index + 1,
+                    message: format!("unknown day kind {}", other),
+                })
+            }
+        };
+
+        entries.push(DayEntry { date, kind });
+    }
+
+    Ok(entries)
+}
@@ -0,0 +1,227 @@
+pub mod holidays;
+pub mod timezone;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use std::collections::HashSet;
+use std::env;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CalendarError {
+    #[error("failed to read calendar file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("invalid calendar entry at line {line}: {message}")]
+    Parse { line: usize, message: String },
+
+    #[error("invalid calendar setting: {0}")]
+    Config(String),
+}
+
+// Производственный календарь: окончание рабочего дня, выходные дни недели,
+// праздники и перенесенные рабочие дни
+#[derive(Debug, Clone)]
+pub struct BusinessCalendar {
+    pub work_end: NaiveTime,
+    pub weekends: Vec<Weekday>,
+    pub holidays: HashSet<NaiveDate>,
+    pub working_days: HashSet<NaiveDate>,
+}
+
+impl Default for BusinessCalendar {
+    fn default() -> Self {
+        Self {
+            work_end: NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+            weekends: vec![Weekday::Sat, Weekday::Sun],
+            holidays: HashSet::new(),
+            working_days: HashSet::new(),
+        }
+    }
+}
+
+impl BusinessCalendar {
+    // Загружает настройки календаря из переменных среды:
+    // WORKDAY_END, WEEKEND_DAYS и HOLIDAYS_FILE
+    pub fn from_env() -> Result<Self, CalendarError> {
+        let mut calendar = Self::default();
+
+        if let Ok(value) = env::var("WORKDAY_END") {
+            calendar.work_end = parse_time(&value)?;
+        }
+
+        if let Ok(value) = env::var("WEEKEND_DAYS") {
+            calendar.weekends = value
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(|s| {
+                    s.parse::<Weekday>()
+                        .map_err(|_| CalendarError::Config(format!("unknown weekday: {}", s)))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if calendar.weekends.len() >= 7 {
+                return Err(CalendarError::Config(
+                    "WEEKEND_DAYS must leave at least one working day".to_string(),
+                ));
+            }
+        }
+
+        if let Ok(path) = env::var("HOLIDAYS_FILE") {
+            calendar.load_file(&path)?;
+        }
+
+        Ok(calendar)
+    }
+
+    // Добавляет в календарь праздники и рабочие дни из файла
+    pub fn load_file(&mut self, path: &str) -> Result<(), CalendarError> {
+        let content = std::fs::read_to_string(path)?;
+        let entries = holidays::parse(&content)?;
+
+        for entry in entries {
+            match entry.kind {
+                holidays::DayKind::Holiday => {
+                    self.working_days.remove(&entry.date);
+                    self.holidays.insert(entry.date);
+                }
+                holidays::DayKind::Workday => {
+                    self.holidays.remove(&entry.date);
+                    self.working_days.insert(entry.date);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn is_working_day(&self, date: NaiveDate) -> bool {
+        if self.working_days.contains(&date) {
+            return true;
+        }
+
+        if self.holidays.contains(&date) {
+            return false;
+        }
+
+        !self.weekends.contains(&date.weekday())
+    }
+
+    // Количество рабочих дней в интервале (from, to]
+    pub fn working_days_between(&self, from: NaiveDate, to: NaiveDate) -> i64 {
+        let mut count = 0;
+        let mut date = from;
+
+        while date < to {
+            date += Duration::days(1);
+            if self.is_working_day(date) {
+                count += 1;
+            }
+        }
+
+        count
+    }
+
+    // Количество рабочих дней, прошедших после срока.
+    // Даты определяются в часовом поясе исполнителя
+    pub fn overdue_working_days(&self, due: DateTime<Utc>, now: DateTime<Utc>, tz: Tz) -> i64 {
+        if now <= due {
+            return 0;
+        }
+
+        let due_local = due.with_timezone(&tz).date_naive();
+        let now_local = now.with_timezone(&tz).date_naive();
+        self.working_days_between(due_local, now_local)
+    }
+
+    // Срок выполнения по SLA: конец рабочего дня, наступающего через
+    // указанное количество рабочих дней после начала отсчета.
+    // При нулевом SLA срок - конец текущего (или ближайшего) рабочего дня.
+    // Окончание рабочего дня отсчитывается в часовом поясе исполнителя
+    pub fn sla_due_date(&self, start: DateTime<Utc>, working_days: u32, tz: Tz) -> DateTime<Utc> {
+        let local = start.with_timezone(&tz).naive_local();
+        let mut date = local.date();
+
+        // Если рабочий день уже закончился или сегодня выходной,
+        // отсчет начинается со следующего рабочего дня
+        if !self.is_working_day(date) || local.time() >= self.work_end {
+            date = self.next_working_day(date);
+        }
+
+        for _ in 0..working_days {
+            date = self.next_working_day(date);
+        }
+
+        timezone::local_to_utc(date.and_time(self.work_end), tz)
+    }
+
+    fn next_working_day(&self, date: NaiveDate) -> NaiveDate {
+        let mut next = date + Duration::days(1);
+        while !self.is_working_day(next) {
+            next += Duration::days(1);
+        }
+        next
+    }
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, CalendarError> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M")
+        .map_err(|_| CalendarError::Config(format!("invalid time {}, expected HH:MM", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use chrono_tz::{Asia::Vladivostok, Europe::Moscow};
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, day).unwrap()
+    }
+
+    fn utc(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap()
+    }
+
+    // 13.03.2026 - пятница, 16.03.2026 - понедельник
+    #[test]
+    fn working_days_between_skips_weekends_and_holidays() {
+        let mut calendar = BusinessCalendar::default();
+        assert_eq!(calendar.working_days_between(date(13), date(16)), 1);
+        assert_eq!(calendar.working_days_between(date(13), date(20)), 5);
+        assert_eq!(calendar.working_days_between(date(16), date(16)), 0);
+        assert_eq!(calendar.working_days_between(date(16), date(13)), 0);
+
+        calendar.holidays.insert(date(16));
+        calendar.working_days.insert(date(14));
+        assert_eq!(calendar.working_days_between(date(13), date(16)), 1);
+        assert_eq!(calendar.working_days_between(date(13), date(17)), 2);
+    }
+
+    #[test]
+    fn sla_due_date_ends_on_working_day() {
+        let mut calendar = BusinessCalendar::default();
+
+        // Понедельник 10:00 МСК: нулевой SLA - конец того же дня
+        assert_eq!(calendar.sla_due_date(utc(16, 7), 0, Moscow), utc(16, 15));
+        assert_eq!(calendar.sla_due_date(utc(16, 7), 2, Moscow), utc(18, 15));
+
+        // Пятница 19:00 МСК: отсчет начинается с понедельника
+        assert_eq!(calendar.sla_due_date(utc(13, 16), 0, Moscow), utc(16, 15));
+        assert_eq!(calendar.sla_due_date(utc(13, 16), 1, Moscow), utc(17, 15));
+
+        calendar.holidays.insert(date(16));
+        assert_eq!(calendar.sla_due_date(utc(13, 16), 0, Moscow), utc(17, 15));
+    }
+
+    #[test]
+    fn sla_due_date_uses_executor_time_zone() {
+        let calendar = BusinessCalendar::default();
+
+        // 07:00 UTC - 10:00 в Москве, но 17:00 во Владивостоке
+        assert_eq!(calendar.sla_due_date(utc(16, 7), 0, Vladivostok), utc(16, 8));
+        // 09:00 UTC - уже 19:00 во Владивостоке, срок переходит на вторник
+        assert_eq!(calendar.sla_due_date(utc(16, 9), 0, Vladivostok), utc(17, 8));
+    }
+}
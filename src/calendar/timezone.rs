@@ -0,0 +1,115 @@
+use chrono::{DateTime, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+use crate::models::user::DEFAULT_TIME_ZONE;
+
+// Срок выполнения в запросе: либо точный момент времени,
+// либо только дата, которая означает конец дня в поясе исполнителя
+#[derive(Debug, Clone, Copy, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum DueDateInput {
+    DateTime(DateTime<Utc>),
+    Date(NaiveDate),
+}
+
+impl DueDateInput {
+    pub fn resolve(&self, tz: Tz) -> DateTime<Utc> {
+        match self {
+            DueDateInput::DateTime(dt) => *dt,
+            DueDateInput::Date(date) => end_of_day(*date, tz),
+        }
+    }
+}
+
+pub fn parse_time_zone(name: &str) -> Option<Tz> {
+    name.parse().ok()
+}
+
+// Пояс для пользователей с некорректным поясом и задач без исполнителя
+pub fn default_time_zone() -> Tz {
+    parse_time_zone(DEFAULT_TIME_ZONE).expect("DEFAULT_TIME_ZONE must be a valid IANA name")
+}
+
+// Последняя секунда указанного дня в заданном поясе
+pub fn end_of_day(date: NaiveDate, tz: Tz) -> DateTime<Utc> {
+    let end = NaiveTime::from_hms_opt(23, 59, 59).unwrap();
+    local_to_utc(date.and_time(end), tz)
+}
+
+// Переводит локальное время в UTC. Несуществующее из-за перехода
+// на летнее время локальное время сдвигается на час вперед
+pub fn local_to_utc(local: NaiveDateTime, tz: Tz) -> DateTime<Utc> {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(dt) | LocalResult::Ambiguous(dt, _) => dt.with_timezone(&Utc),
+        LocalResult::None => tz
+            .from_local_datetime(&(local + Duration::hours(1)))
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|| local.and_utc()),
+    }
+}
+
+// Дата и время в поясе пользователя для отображения
+pub fn format_local(dt: DateTime<Utc>, tz: Tz) -> String {
+    dt.with_timezone(&tz).format("%d.%m.%Y %H:%M").to_string()
+}
+
+// Фильтр Tera: {{ value | local_datetime(tz="Asia/Novosibirsk") }}
+// выводит дату в формате RFC 3339 в указанном часовом поясе
+pub fn tera_local_datetime(
+    value: &tera::Value,
+    args: &HashMap<String, tera::Value>,
+) -> tera::Result<tera::Value> {
+    let raw = match value.as_str() {
+        Some(raw) => raw,
+        None => return Ok(tera::Value::String(String::new())),
+    };
+
+    let dt = DateTime::parse_from_rfc3339(raw)
+        .map_err(|e| tera::Error::msg(format!("local_datetime: {}", e)))?
+        .with_timezone(&Utc);
+
+    let tz = args
+        .get("tz")
+        .and_then(|v| v.as_str())
+        .and_then(parse_time_zone)
+        .unwrap_or_else(default_time_zone);
+
+    Ok(tera::Value::String(format_local(dt, tz)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::{America::New_York, Asia::Vladivostok, Europe::Moscow};
+
+    fn input(json: &str) -> DueDateInput {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn date_resolves_to_end_of_day_in_time_zone() {
+        let due = input("\"2026-03-16\"");
+        assert!(matches!(due, DueDateInput::Date(_)));
+        assert_eq!(due.resolve(Moscow), Utc.with_ymd_and_hms(2026, 3, 16, 20, 59, 59).unwrap());
+        assert_eq!(due.resolve(Vladivostok), Utc.with_ymd_and_hms(2026, 3, 16, 13, 59, 59).unwrap());
+    }
+
+    #[test]
+    fn date_time_resolves_as_is() {
+        let due = input("\"2026-03-16T12:30:00Z\"");
+        let expected = Utc.with_ymd_and_hms(2026, 3, 16, 12, 30, 0).unwrap();
+        assert_eq!(due.resolve(Moscow), expected);
+        assert_eq!(due.resolve(Vladivostok), expected);
+    }
+
+    // 08.03.2026 в Нью-Йорке часы переводятся с 02:00 на 03:00
+    #[test]
+    fn missing_local_time_is_shifted_forward() {
+        let local = NaiveDate::from_ymd_opt(2026, 3, 8).unwrap().and_hms_opt(2, 30, 0).unwrap();
+        assert_eq!(local_to_utc(local, New_York), Utc.with_ymd_and_hms(2026, 3, 8, 7, 30, 0).unwrap());
+    }
+}
@@ -0,0 +1,886 @@
+// Общий набор проверок поведения Repository. Каждая проверка получает пустое
+// хранилище; набор выполняется для хранилища в памяти, SQLite и, при сборке
+// с feature "postgres", для PostgreSQL на локально запущенном сервере
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use super::{ArchiveFilter, InMemoryRepository, Repository, RepositoryError, TaskChange, TimeEntryFilter};
+use crate::i18n::Locale;
+use crate::models::{
+    Delegation, ParticipantRole, Project, SavedView, Tag, Task, TaskPriority, TaskStatus, TimeEntry, User, UserRole,
+};
+
+type Case = (&'static str, fn(&dyn Repository));
+
+const CASES: &[Case] = &[
+    ("users_round_trip", users_round_trip),
+    ("user_email_is_unique", user_email_is_unique),
+    ("user_trash_and_restore", user_trash_and_restore),
+    ("user_deactivation", user_deactivation),
+    ("tasks_round_trip", tasks_round_trip),
+    ("task_version_conflict", task_version_conflict),
+    ("references_must_exist", references_must_exist),
+    ("task_trash_and_purge", task_trash_and_purge),
+    ("purge_keeps_referenced_users", purge_keeps_referenced_users),
+    ("participants", participants),
+    ("reassign_records_delegation", reassign_records_delegation),
+    ("task_changes_are_atomic", task_changes_are_atomic),
+    ("delete_user_with_handover", delete_user_with_handover),
+    ("move_task_renumbers_column", move_task_renumbers_column),
+    ("wip_limits", wip_limits),
+    ("time_entries", time_entries),
+    ("one_running_time_entry_per_user", one_running_time_entry_per_user),
+    ("projects", projects),
+    ("saved_views", saved_views),
+    ("tags_by_names", tags_by_names),
+    ("tag_management", tag_management),
+    ("archive_and_unarchive", archive_and_unarchive),
+    ("archive_counts_from_cancellation", archive_counts_from_cancellation),
+];
+
+// Выполняет все проверки; fresh создает новое пустое хранилище для каждой.
+// Упавшие проверки перечисляются в сообщении об ошибке
+pub fn run_suite(backend: &str, mut fresh: impl FnMut() -> Arc<dyn Repository>) {
+    let mut failed = Vec::new();
+    for (name, case) in CASES {
+        let repo = fresh();
+        if panic::catch_unwind(AssertUnwindSafe(|| case(repo.as_ref()))).is_err() {
+            failed.push(*name);
+        }
+    }
+    assert!(failed.is_empty(), "{}: failed conformance cases: {:?}", backend, failed);
+}
+
+// Время отсчитывается от фиксированного момента: хранилища сами часы не читают,
+// поэтому записанные ими отметки времени проверяются точно
+fn days_ago(days: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 3, 16, 12, 0, 0).unwrap() - Duration::days(days)
+}
+
+fn user(repo: &dyn Repository, name: &str, role: UserRole) -> User {
+    let user = User::new(name.to_string(), format!("{}@example.com", name), role);
+    repo.create_user(&user).unwrap();
+    user
+}
+
+fn task(repo: &dyn Repository, title: &str, customer: &User, executor: &User) -> Task {
+    let mut task = Task::new(
+        title.to_string(),
+        String::new(),
+        customer.id.clone(),
+        executor.id.clone(),
+        TaskPriority::Medium,
+        None,
+        Vec::new(),
+        days_ago(0),
+    );
+    task.created_at = days_ago(0);
+    repo.create_task(&task).unwrap();
+    task
+}
+
+fn users_round_trip(repo: &dyn Repository) {
+    let mut alice = user(repo, "alice", UserRole::Customer);
+    user(repo, "bob", UserRole::Executor);
+
+    let stored = repo.get_user_by_id(&alice.id).unwrap();
+    assert_eq!(stored.name, "alice");
+    assert_eq!(stored.role, UserRole::Customer);
+    assert_eq!(stored.version, 1);
+    assert!(stored.active);
+    assert!(stored.locale.is_none());
+    assert_eq!(repo.get_users().unwrap().len(), 2);
+
+    alice.name = "Alice".to_string();
+    alice.time_zone = "Asia/Novosibirsk".to_string();
+    alice.locale = Some(Locale::En);
+    repo.update_user(&alice).unwrap();
+    let stored = repo.get_user_by_id(&alice.id).unwrap();
+    assert_eq!(stored.name, "Alice");
+    assert_eq!(stored.time_zone, "Asia/Novosibirsk");
+    assert_eq!(stored.locale, Some(Locale::En));
+    assert_eq!(stored.version, 2);
+
+    // Повторное сохранение со старой версией
+    match repo.update_user(&alice) {
+        Err(RepositoryError::VersionConflict { expected: 1, current: 2, .. }) => {}
+        other => panic!("expected version conflict, got {:?}", other),
+    }
+    assert!(matches!(repo.get_user_by_id("missing"), Err(RepositoryError::NotFound { .. })));
+}
+
+fn user_email_is_unique(repo: &dyn Repository) {
+    user(repo, "alice", UserRole::Customer);
+    let duplicate = User::new("Other".to_string(), "alice@example.com".to_string(), UserRole::Executor);
+
+    match repo.create_user(&duplicate) {
+        Err(RepositoryError::Constraint(message)) => assert!(message.contains("users.email"), "{}", message),
+        other => panic!("expected constraint violation, got {:?}", other),
+    }
+}
+
+fn user_trash_and_restore(repo: &dyn Repository) {
+    let alice = user(repo, "alice", UserRole::Customer);
+
+    repo.delete_user(&alice.id, days_ago(0)).unwrap();
+    assert!(matches!(repo.get_user_by_id(&alice.id), Err(RepositoryError::NotFound { .. })));
+    assert!(repo.get_users().unwrap().is_empty());
+    let deleted = repo.get_deleted_users().unwrap();
+    assert_eq!(deleted.len(), 1);
+    assert_eq!(deleted[0].deleted_at, Some(days_ago(0)));
+    assert!(matches!(repo.delete_user(&alice.id, days_ago(0)), Err(RepositoryError::NotFound { .. })));
+
+    repo.restore_user(&alice.id).unwrap();
+    assert!(repo.get_user_by_id(&alice.id).unwrap().deleted_at.is_none());
+    assert!(matches!(repo.restore_user(&alice.id), Err(RepositoryError::NotFound { .. })));
+}
+
+fn user_deactivation(repo: &dyn Repository) {
+    let bob = user(repo, "bob", UserRole::Executor);
+
+    repo.deactivate_user(&bob.id, days_ago(1)).unwrap();
+    let stored = repo.get_user_by_id(&bob.id).unwrap();
+    assert!(!stored.active);
+    assert_eq!(stored.deactivated_at, Some(days_ago(1)));
+
+    // Повторная деактивация сохраняет время первой
+    repo.deactivate_user(&bob.id, days_ago(0)).unwrap();
+    assert_eq!(repo.get_user_by_id(&bob.id).unwrap().deactivated_at, Some(days_ago(1)));
+    // Деактивированный пользователь остается в общем списке
+    assert_eq!(repo.get_users().unwrap().len(), 1);
+
+    repo.reactivate_user(&bob.id).unwrap();
+    let stored = repo.get_user_by_id(&bob.id).unwrap();
+    assert!(stored.active);
+    assert!(stored.deactivated_at.is_none());
+    assert!(matches!(repo.deactivate_user("missing", days_ago(0)), Err(RepositoryError::NotFound { .. })));
+}
+
+fn tasks_round_trip(repo: &dyn Repository) {
+    let alice = user(repo, "alice", UserRole::Customer);
+    let bob = user(repo, "bob", UserRole::Executor);
+    let tags = repo.get_or_create_tags_by_names(&["urgent".to_string(), "backend".to_string()]).unwrap();
+
+    let mut task = Task::new(
+        "Отчет".to_string(),
+        "Квартальный отчет".to_string(),
+        alice.id.clone(),
+        bob.id.clone(),
+        TaskPriority::High,
+        Some(days_ago(-3)),
+        tags,
+        days_ago(1),
+    );
+    task.estimate_minutes = Some(90);
+    repo.create_task(&task).unwrap();
+
+    let stored = repo.get_task_by_id(&task.id).unwrap();
+    assert_eq!(stored.title, "Отчет");
+    assert_eq!(stored.priority, TaskPriority::High);
+    assert_eq!(stored.status, TaskStatus::New);
+    assert_eq!(stored.created_at, task.created_at);
+    assert_eq!(stored.due_date, task.due_date);
+    assert_eq!(stored.estimate_minutes, Some(90));
+    assert_eq!(stored.version, 1);
+    // Теги возвращаются по алфавиту
+    let names: Vec<_> = stored.tags.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, ["backend", "urgent"]);
+
+    let mut update = stored.clone();
+    update.status = TaskStatus::Completed;
+    update.completed_at = Some(days_ago(0));
+    update.tags.retain(|t| t.name == "urgent");
+    repo.update_task(&update).unwrap();
+
+    let stored = repo.get_task_by_id(&task.id).unwrap();
+    assert_eq!(stored.status, TaskStatus::Completed);
+    assert_eq!(stored.completed_at, update.completed_at);
+    assert_eq!(stored.tags.len(), 1);
+    assert_eq!(stored.version, 2);
+    assert_eq!(repo.get_tasks().unwrap().len(), 1);
+    assert_eq!(repo.get_tags_for_task(&task.id).unwrap().len(), 1);
+}
+
+fn task_version_conflict(repo: &dyn Repository) {
+    let alice = user(repo, "alice", UserRole::Customer);
+    let bob = user(repo, "bob", UserRole::Executor);
+    let mut task = task(repo, "t", &alice, &bob);
+
+    task.title = "first".to_string();
+    repo.update_task(&task).unwrap();
+    task.title = "second".to_string();
+    match repo.update_task(&task) {
+        Err(RepositoryError::VersionConflict { expected: 1, current: 2, .. }) => {}
+        other => panic!("expected version conflict, got {:?}", other),
+    }
+    assert_eq!(repo.get_task_by_id(&task.id).unwrap().title, "first");
+
+    task.id = "missing".to_string();
+    assert!(matches!(repo.update_task(&task), Err(RepositoryError::NotFound { .. })));
+}
+
+fn references_must_exist(repo: &dyn Repository) {
+    let alice = user(repo, "alice", UserRole::Customer);
+    let is_foreign_key = |result: Result<(), RepositoryError>| {
+        matches!(result, Err(RepositoryError::Constraint(message)) if message.contains("FOREIGN KEY"))
+    };
+
+    let mut orphan = Task::new(
+        "orphan".to_string(),
+        String::new(),
+        alice.id.clone(),
+        "missing".to_string(),
+        TaskPriority::Low,
+        None,
+        Vec::new(),
+        days_ago(0),
+    );
+    assert!(is_foreign_key(repo.create_task(&orphan)));
+    orphan.executor_id = alice.id.clone();
+    orphan.project_id = Some("missing".to_string());
+    assert!(is_foreign_key(repo.create_task(&orphan)));
+    assert!(repo.get_tasks().unwrap().is_empty());
+
+    let task = task(repo, "t", &alice, &alice);
+    assert!(is_foreign_key(repo.add_task_participant(&task.id, "missing", ParticipantRole::Watcher)));
+    assert!(is_foreign_key(repo.create_project(&Project::new("p".to_string(), String::new(), "missing".to_string(), days_ago(0)))));
+    assert!(is_foreign_key(repo.create_saved_view(&SavedView::new("missing".to_string(), "v".to_string(), days_ago(0)))));
+    assert!(is_foreign_key(repo.set_default_view(&alice.id, Some("missing"))));
+
+    // Пользователь в корзине остается допустимой ссылкой
+    repo.delete_user(&alice.id, days_ago(0)).unwrap();
+    repo.add_task_participant(&task.id, &alice.id, ParticipantRole::Watcher).unwrap();
+}
+
+fn task_trash_and_purge(repo: &dyn Repository) {
+    let alice = user(repo, "alice", UserRole::Customer);
+    let bob = user(repo, "bob", UserRole::Executor);
+    let kept = task(repo, "kept", &alice, &bob);
+    let purged = task(repo, "purged", &alice, &bob);
+
+    repo.delete_task(&kept.id, days_ago(1)).unwrap();
+    repo.delete_task(&purged.id, days_ago(1)).unwrap();
+    assert!(repo.get_tasks().unwrap().is_empty());
+    assert!(matches!(repo.get_task_by_id(&kept.id), Err(RepositoryError::NotFound { .. })));
+    assert_eq!(repo.get_deleted_tasks().unwrap().len(), 2);
+
+    repo.restore_task(&kept.id).unwrap();
+    assert_eq!(repo.get_task_by_id(&kept.id).unwrap().version, 3);
+
+    // Удаляются только записи, перемещенные в корзину раньше момента очистки
+    assert_eq!(repo.purge_deleted(days_ago(1)).unwrap(), 0);
+    assert_eq!(repo.purge_deleted(days_ago(0)).unwrap(), 1);
+    assert!(repo.get_deleted_tasks().unwrap().is_empty());
+    assert!(matches!(repo.restore_task(&purged.id), Err(RepositoryError::NotFound { .. })));
+}
+
+fn purge_keeps_referenced_users(repo: &dyn Repository) {
+    let alice = user(repo, "alice", UserRole::Customer);
+    let bob = user(repo, "bob", UserRole::Executor);
+    let carol = user(repo, "carol", UserRole::Executor);
+    let task = task(repo, "t", &alice, &bob);
+    repo.add_task_participant(&task.id, &carol.id, ParticipantRole::Watcher).unwrap();
+
+    repo.delete_user(&bob.id, days_ago(1)).unwrap();
+    repo.delete_user(&carol.id, days_ago(1)).unwrap();
+    let purged = repo.purge_deleted(days_ago(0)).unwrap();
+
+    // bob - исполнитель задачи и остается в корзине; carol только наблюдала
+    assert_eq!(purged, 1);
+    let deleted: Vec<_> = repo.get_deleted_users().unwrap().into_iter().map(|u| u.id).collect();
+    assert_eq!(deleted, [bob.id]);
+    assert!(repo.get_task_by_id(&task.id).unwrap().watcher_ids.is_empty());
+}
+
+fn participants(repo: &dyn Repository) {
+    let alice = user(repo, "alice", UserRole::Customer);
+    let bob = user(repo, "bob", UserRole::Executor);
+    let carol = user(repo, "carol", UserRole::Executor);
+    let dave = user(repo, "dave", UserRole::Executor);
+    let task = task(repo, "t", &alice, &bob);
+
+    repo.add_task_participant(&task.id, &carol.id, ParticipantRole::CoExecutor).unwrap();
+    repo.add_task_participant(&task.id, &dave.id, ParticipantRole::CoExecutor).unwrap();
+    repo.add_task_participant(&task.id, &alice.id, ParticipantRole::Watcher).unwrap();
+    let stored = repo.get_task_by_id(&task.id).unwrap();
+    assert_eq!(stored.co_executor_ids, [carol.id.clone(), dave.id.clone()]);
+    assert_eq!(stored.watcher_ids, [alice.id.as_str()]);
+    // Участники входят в представление задачи: каждое изменение меняет версию
+    assert_eq!(stored.version, 4);
+
+    // Новая роль заменяет прежнюю
+    repo.add_task_participant(&task.id, &carol.id, ParticipantRole::Watcher).unwrap();
+    let stored = repo.get_task_by_id(&task.id).unwrap();
+    assert_eq!(stored.co_executor_ids, [dave.id.as_str()]);
+    assert_eq!(stored.watcher_ids.len(), 2);
+    assert!(stored.watcher_ids.contains(&carol.id));
+
+    repo.remove_task_participant(&task.id, &carol.id, ParticipantRole::Watcher).unwrap();
+    assert_eq!(repo.get_task_by_id(&task.id).unwrap().version, 6);
+    assert!(matches!(
+        repo.remove_task_participant(&task.id, &carol.id, ParticipantRole::Watcher),
+        Err(RepositoryError::NotFound { .. })
+    ));
+    assert!(matches!(
+        repo.remove_task_participant(&task.id, &dave.id, ParticipantRole::Watcher),
+        Err(RepositoryError::NotFound { .. })
+    ));
+}
+
+fn reassign_records_delegation(repo: &dyn Repository) {
+    let alice = user(repo, "alice", UserRole::Customer);
+    let bob = user(repo, "bob", UserRole::Executor);
+    let carol = user(repo, "carol", UserRole::Executor);
+    let task = task(repo, "t", &alice, &bob);
+    repo.add_task_participant(&task.id, &carol.id, ParticipantRole::CoExecutor).unwrap();
+    let task = repo.get_task_by_id(&task.id).unwrap();
+
+    let first = Delegation::new(task.id.clone(), bob.id.clone(), carol.id.clone(), bob.id.clone(), "отпуск".to_string(), days_ago(0));
+    repo.reassign_task(&task, &first).unwrap();
+
+    let stored = repo.get_task_by_id(&task.id).unwrap();
+    assert_eq!(stored.executor_id, carol.id);
+    assert_eq!(stored.version, 3);
+    // Новый исполнитель перестает быть соисполнителем
+    assert!(stored.co_executor_ids.is_empty());
+
+    // Повторная передача со старой версией отклоняется
+    let stale = Delegation::new(task.id.clone(), carol.id.clone(), bob.id.clone(), alice.id.clone(), String::new(), days_ago(0));
+    assert!(matches!(repo.reassign_task(&task, &stale), Err(RepositoryError::VersionConflict { .. })));
+
+    let second = Delegation::new(task.id.clone(), carol.id.clone(), bob.id.clone(), alice.id.clone(), String::new(), days_ago(0));
+    repo.reassign_task(&stored, &second).unwrap();
+
+    // Передачи с одинаковым временем идут в порядке записи
+    let chain: Vec<_> = repo.get_task_delegations(&task.id).unwrap().into_iter().map(|d| d.id).collect();
+    assert_eq!(chain, [first.id.clone(), second.id.clone()]);
+    assert_eq!(repo.get_delegations().unwrap().len(), 2);
+    assert_eq!(repo.get_task_delegations(&task.id).unwrap()[0].reason, "отпуск");
+}
+
+fn task_changes_are_atomic(repo: &dyn Repository) {
+    let alice = user(repo, "alice", UserRole::Customer);
+    let bob = user(repo, "bob", UserRole::Executor);
+    let carol = user(repo, "carol", UserRole::Executor);
+    let first = task(repo, "first", &alice, &bob);
+    let second = task(repo, "second", &alice, &bob);
+    let third = task(repo, "third", &alice, &bob);
+
+    let mut renamed = first.clone();
+    renamed.title = "renamed".to_string();
+    let mut stale = second.clone();
+    stale.version = 5;
+    let result = repo.apply_task_changes(&[TaskChange::Update(renamed.clone()), TaskChange::Delete(stale)], days_ago(0));
+    assert!(matches!(result, Err(RepositoryError::VersionConflict { .. })));
+    // Первое изменение откатывается вместе со вторым
+    assert_eq!(repo.get_task_by_id(&first.id).unwrap().title, "first");
+
+    let delegation = Delegation::new(third.id.clone(), bob.id.clone(), carol.id.clone(), alice.id.clone(), String::new(), days_ago(0));
+    repo.apply_task_changes(&[
+        TaskChange::Update(renamed),
+        TaskChange::Delete(second.clone()),
+        TaskChange::Reassign(third.clone(), delegation),
+    ], days_ago(0))
+    .unwrap();
+    assert_eq!(repo.get_task_by_id(&first.id).unwrap().title, "renamed");
+    assert!(matches!(repo.get_task_by_id(&second.id), Err(RepositoryError::NotFound { .. })));
+    assert_eq!(repo.get_task_by_id(&third.id).unwrap().executor_id, carol.id);
+}
+
+fn delete_user_with_handover(repo: &dyn Repository) {
+    let alice = user(repo, "alice", UserRole::Customer);
+    let bob = user(repo, "bob", UserRole::Executor);
+    let carol = user(repo, "carol", UserRole::Executor);
+    let task = task(repo, "t", &alice, &bob);
+
+    // Передача со старой версией: пользователь не удаляется
+    let mut stale = task.clone();
+    stale.version = 7;
+    let delegation = Delegation::new(task.id.clone(), bob.id.clone(), carol.id.clone(), bob.id.clone(), String::new(), days_ago(0));
+    let result = repo.delete_user_with_handover(&bob.id, &[TaskChange::Reassign(stale, delegation.clone())], days_ago(0));
+    assert!(matches!(result, Err(RepositoryError::VersionConflict { .. })));
+    assert!(repo.get_user_by_id(&bob.id).is_ok());
+
+    repo.delete_user_with_handover(&bob.id, &[TaskChange::Reassign(task.clone(), delegation)], days_ago(0)).unwrap();
+    assert!(matches!(repo.get_user_by_id(&bob.id), Err(RepositoryError::NotFound { .. })));
+    assert_eq!(repo.get_task_by_id(&task.id).unwrap().executor_id, carol.id);
+    assert_eq!(repo.get_task_delegations(&task.id).unwrap().len(), 1);
+}
+
+fn move_task_renumbers_column(repo: &dyn Repository) {
+    let alice = user(repo, "alice", UserRole::Customer);
+    let bob = user(repo, "bob", UserRole::Executor);
+    let a = task(repo, "a", &alice, &bob);
+    let b = task(repo, "b", &alice, &bob);
+    let c = task(repo, "c", &alice, &bob);
+
+    for (task, rank) in [(&a, 1), (&b, 2), (&c, 3)] {
+        let mut task = repo.get_task_by_id(&task.id).unwrap();
+        task.rank = rank;
+        repo.update_task(&task).unwrap();
+    }
+
+    // c перед a
+    let moved = repo.get_task_by_id(&c.id).unwrap();
+    assert_eq!(repo.move_task(&moved, Some(&a.id)).unwrap(), 1);
+    let rank = |id: &str| repo.get_task_by_id(id).unwrap().rank;
+    assert_eq!((rank(&c.id), rank(&a.id), rank(&b.id)), (1, 2, 3));
+
+    // В другую колонку, в конец
+    let mut moved = repo.get_task_by_id(&a.id).unwrap();
+    moved.status = TaskStatus::InProgress;
+    assert_eq!(repo.move_task(&moved, None).unwrap(), 1);
+    let stored = repo.get_task_by_id(&a.id).unwrap();
+    assert_eq!(stored.status, TaskStatus::InProgress);
+    assert_eq!(stored.rank, 1);
+
+    // Старая версия
+    assert!(matches!(repo.move_task(&moved, None), Err(RepositoryError::VersionConflict { .. })));
+}
+
+fn wip_limits(repo: &dyn Repository) {
+    assert!(repo.get_wip_limits().unwrap().is_empty());
+
+    repo.set_wip_limit("in_progress", Some(3)).unwrap();
+    repo.set_wip_limit("in_progress", Some(5)).unwrap();
+    repo.set_wip_limit("new", Some(10)).unwrap();
+    let limits = repo.get_wip_limits().unwrap();
+    assert_eq!(limits.get("in_progress"), Some(&5));
+    assert_eq!(limits.get("new"), Some(&10));
+
+    repo.set_wip_limit("new", None).unwrap();
+    assert_eq!(repo.get_wip_limits().unwrap().len(), 1);
+}
+
+fn time_entries(repo: &dyn Repository) {
+    let alice = user(repo, "alice", UserRole::Customer);
+    let bob = user(repo, "bob", UserRole::Executor);
+    let task = task(repo, "t", &alice, &bob);
+
+    let finished = TimeEntry::finished(
+        task.id.clone(),
+        bob.id.clone(),
+        days_ago(2),
+        days_ago(2) + Duration::minutes(45),
+        "анализ".to_string(),
+    );
+    repo.create_time_entry(&finished).unwrap();
+
+    let mut running = TimeEntry::start(task.id.clone(), bob.id.clone(), days_ago(0) - Duration::hours(1), String::new());
+    repo.create_time_entry(&running).unwrap();
+    assert_eq!(repo.get_running_time_entry(&bob.id).unwrap().map(|e| e.id), Some(running.id.clone()));
+    assert!(repo.get_running_time_entry(&alice.id).unwrap().is_none());
+    // Запущенный таймер не учитывается
+    assert_eq!(repo.get_task_by_id(&task.id).unwrap().spent_minutes, 45);
+    // Каждое изменение учета времени увеличивает версию задачи
+    assert_eq!(repo.get_task_by_id(&task.id).unwrap().version, 3);
+
+    running.stop(days_ago(0));
+    repo.stop_time_entry(&running).unwrap();
+    assert!(matches!(repo.stop_time_entry(&running), Err(RepositoryError::NotFound { .. })));
+    assert!(repo.get_running_time_entry(&bob.id).unwrap().is_none());
+    assert_eq!(repo.get_task_by_id(&task.id).unwrap().spent_minutes, 105);
+    assert_eq!(repo.get_task_by_id(&task.id).unwrap().version, 4);
+
+    let all = repo.get_time_entries(&TimeEntryFilter::default()).unwrap();
+    assert_eq!(all.iter().map(|e| e.id.clone()).collect::<Vec<_>>(), [finished.id.clone(), running.id.clone()]);
+    assert_eq!(all[0].note, "анализ");
+    let recent = repo
+        .get_time_entries(&TimeEntryFilter {
+            user_id: Some(bob.id.clone()),
+            started_from: Some(days_ago(1)),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(recent.len(), 1);
+    assert_eq!(recent[0].minutes, 60);
+
+    repo.delete_time_entry(&finished.id).unwrap();
+    assert!(matches!(repo.delete_time_entry(&finished.id), Err(RepositoryError::NotFound { .. })));
+    let task = repo.get_task_by_id(&task.id).unwrap();
+    assert_eq!((task.spent_minutes, task.version), (60, 5));
+}
+
+fn one_running_time_entry_per_user(repo: &dyn Repository) {
+    let alice = user(repo, "alice", UserRole::Customer);
+    let bob = user(repo, "bob", UserRole::Executor);
+    let first = task(repo, "first", &alice, &bob);
+    let second = task(repo, "second", &alice, &bob);
+
+    let running = TimeEntry::start(first.id.clone(), bob.id.clone(), days_ago(0), String::new());
+    repo.create_time_entry(&running).unwrap();
+    // Второй запущенный таймер отклоняется и по той же, и по другой задаче
+    for task_id in [&first.id, &second.id] {
+        let entry = TimeEntry::start(task_id.clone(), bob.id.clone(), days_ago(0), String::new());
+        match repo.create_time_entry(&entry) {
+            Err(RepositoryError::Constraint(message)) => assert!(message.contains("time_entries.user_id"), "{}", message),
+            other => panic!("expected constraint violation, got {:?}", other),
+        }
+    }
+    assert_eq!(repo.get_task_by_id(&second.id).unwrap().version, 1);
+
+    // Завершенные записи и таймеры других пользователей не мешают
+    let finished = TimeEntry::finished(first.id.clone(), bob.id.clone(), days_ago(1), days_ago(1) + Duration::minutes(30), String::new());
+    repo.create_time_entry(&finished).unwrap();
+    repo.create_time_entry(&TimeEntry::start(first.id.clone(), alice.id.clone(), days_ago(0), String::new())).unwrap();
+}
+
+fn projects(repo: &dyn Repository) {
+    let alice = user(repo, "alice", UserRole::Customer);
+    let bob = user(repo, "bob", UserRole::Executor);
+    let mut project = Project::new("Сайт".to_string(), String::new(), alice.id.clone(), days_ago(0));
+    repo.create_project(&project).unwrap();
+    repo.create_project(&Project::new("Альфа".to_string(), String::new(), alice.id.clone(), days_ago(0))).unwrap();
+
+    // Проекты по алфавиту
+    let names: Vec<_> = repo.get_projects().unwrap().into_iter().map(|p| p.name).collect();
+    assert_eq!(names, ["Альфа", "Сайт"]);
+
+    project.customer_id = Some(alice.id.clone());
+    repo.update_project(&project).unwrap();
+    assert!(matches!(repo.update_project(&project), Err(RepositoryError::VersionConflict { .. })));
+    let stored = repo.get_project_by_id(&project.id).unwrap();
+    assert_eq!(stored.customer_id, Some(alice.id.clone()));
+    assert_eq!(stored.version, 2);
+
+    let mut linked = task(repo, "t", &alice, &bob);
+    linked.project_id = Some(project.id.clone());
+    repo.update_task(&linked).unwrap();
+    task(repo, "other", &alice, &bob);
+    assert_eq!(repo.get_tasks_by_project(&project.id).unwrap().len(), 1);
+
+    // Задачи удаленного проекта остаются без проекта
+    repo.delete_project(&project.id).unwrap();
+    let unlinked = repo.get_task_by_id(&linked.id).unwrap();
+    assert!(unlinked.project_id.is_none());
+    assert_eq!(unlinked.version, 3);
+    assert!(matches!(repo.get_project_by_id(&project.id), Err(RepositoryError::NotFound { .. })));
+    assert!(matches!(repo.delete_project(&project.id), Err(RepositoryError::NotFound { .. })));
+}
+
+fn saved_views(repo: &dyn Repository) {
+    let alice = user(repo, "alice", UserRole::Customer);
+    let bob = user(repo, "bob", UserRole::Executor);
+
+    let mut shared = SavedView::new(alice.id.clone(), "Общий".to_string(), days_ago(0));
+    shared.shared = true;
+    shared.filters.status = Some("new".to_string());
+    shared.filters.tags = vec!["urgent".to_string()];
+    shared.columns = vec!["title".to_string(), "due_date".to_string()];
+    shared.sort = Some("-due_date".to_string());
+    repo.create_saved_view(&shared).unwrap();
+    let private = SavedView::new(alice.id.clone(), "Личный".to_string(), days_ago(0));
+    repo.create_saved_view(&private).unwrap();
+
+    assert_eq!(repo.get_saved_views(&alice.id).unwrap().len(), 2);
+    let visible = repo.get_saved_views(&bob.id).unwrap();
+    assert_eq!(visible.len(), 1);
+    assert_eq!(visible[0].filters.tags, ["urgent"]);
+    assert_eq!(visible[0].columns, ["title", "due_date"]);
+    assert_eq!(visible[0].sort.as_deref(), Some("-due_date"));
+
+    repo.set_default_view(&alice.id, Some(&shared.id)).unwrap();
+    repo.set_default_view(&bob.id, Some(&private.id)).unwrap();
+    repo.set_default_view(&bob.id, Some(&shared.id)).unwrap();
+    assert_eq!(repo.get_default_view_id(&bob.id).unwrap(), Some(shared.id.clone()));
+
+    // Вид перестал быть общим: у коллег он больше не вид по умолчанию, у владельца остается
+    shared.shared = false;
+    repo.update_saved_view(&shared).unwrap();
+    assert_eq!(repo.get_default_view_id(&bob.id).unwrap(), None);
+    assert_eq!(repo.get_default_view_id(&alice.id).unwrap(), Some(shared.id.clone()));
+
+    repo.delete_saved_view(&shared.id).unwrap();
+    assert_eq!(repo.get_default_view_id(&alice.id).unwrap(), None);
+    assert!(matches!(repo.get_saved_view_by_id(&shared.id), Err(RepositoryError::NotFound { .. })));
+    assert!(matches!(repo.delete_saved_view(&shared.id), Err(RepositoryError::NotFound { .. })));
+
+    repo.set_default_view(&alice.id, Some(&private.id)).unwrap();
+    repo.set_default_view(&alice.id, None).unwrap();
+    assert_eq!(repo.get_default_view_id(&alice.id).unwrap(), None);
+}
+
+fn tags_by_names(repo: &dyn Repository) {
+    let names: Vec<String> = ["Ёлка", " срочно ", "СРОЧНО", "елка", "", "новый  год"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let tags = repo.get_or_create_tags_by_names(&names).unwrap();
+
+    // Порядок запроса; одинаковые ключи дают один тег с первым написанием
+    let created: Vec<_> = tags.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(created, ["Ёлка", "срочно", "новый год"]);
+
+    let again = repo.get_or_create_tags_by_names(&["ЕЛКА".to_string()]).unwrap();
+    assert_eq!(again[0].id, tags[0].id);
+    assert_eq!(repo.get_tags().unwrap().len(), 3);
+    assert!(repo.get_or_create_tags_by_names(&[]).unwrap().is_empty());
+}
+
+fn tag_management(repo: &dyn Repository) {
+    let alice = user(repo, "alice", UserRole::Customer);
+    let bob = user(repo, "bob", UserRole::Executor);
+
+    let mut bug = Tag::new("bug".to_string());
+    bug.color = Some("#ff0000".to_string());
+    repo.create_tag(&bug).unwrap();
+    let defect = Tag::new("defect".to_string());
+    repo.create_tag(&defect).unwrap();
+    match repo.create_tag(&Tag::new("BUG".to_string())) {
+        Err(RepositoryError::Constraint(message)) => assert!(message.contains("tags.name"), "{}", message),
+        other => panic!("expected constraint violation, got {:?}", other),
+    }
+
+    bug.description = Some("Ошибки".to_string());
+    repo.update_tag(&bug).unwrap();
+    let stored = repo.get_tag_by_id(&bug.id).unwrap();
+    assert_eq!(stored.color.as_deref(), Some("#ff0000"));
+    assert_eq!(stored.description.as_deref(), Some("Ошибки"));
+
+    let both = task(repo, "both", &alice, &bob);
+    let one = task(repo, "one", &alice, &bob);
+    let trashed = task(repo, "trashed", &alice, &bob);
+    for (task, tag) in [(&both, &bug), (&both, &defect), (&one, &defect), (&trashed, &defect)] {
+        repo.add_tag_to_task(&task.id, &tag.id).unwrap();
+    }
+    // Повторная связь не дублируется
+    repo.add_tag_to_task(&one.id, &defect.id).unwrap();
+    repo.delete_task(&trashed.id, days_ago(0)).unwrap();
+    assert!(matches!(repo.add_tag_to_task(&trashed.id, &bug.id), Err(RepositoryError::NotFound { .. })));
+    assert!(matches!(repo.add_tag_to_task(&one.id, "missing"), Err(RepositoryError::NotFound { .. })));
+
+    // Задачи в корзине не учитываются
+    let counts = repo.get_tag_usage_counts().unwrap();
+    assert_eq!(counts.get(&bug.id), Some(&1));
+    assert_eq!(counts.get(&defect.id), Some(&2));
+
+    // Переименование, объединение и удаление тега меняют версии его задач
+    let mut renamed = repo.get_tag_by_id(&bug.id).unwrap();
+    renamed.name = "Bug".to_string();
+    repo.update_tag(&renamed).unwrap();
+    assert_eq!(repo.get_task_by_id(&both.id).unwrap().version, 2);
+    assert_eq!(repo.get_task_by_id(&one.id).unwrap().version, 1);
+
+    repo.merge_tags(&defect.id, &bug.id).unwrap();
+    assert_eq!(repo.get_task_by_id(&both.id).unwrap().version, 3);
+    assert_eq!(repo.get_task_by_id(&one.id).unwrap().version, 2);
+    assert!(matches!(repo.get_tag_by_id(&defect.id), Err(RepositoryError::NotFound { .. })));
+    assert_eq!(repo.get_tags_for_task(&both.id).unwrap().len(), 1);
+    assert_eq!(repo.get_tag_usage_counts().unwrap().get(&bug.id), Some(&2));
+    assert!(matches!(repo.merge_tags("missing", &bug.id), Err(RepositoryError::NotFound { .. })));
+
+    repo.remove_tag_from_task(&one.id, &bug.id).unwrap();
+    assert!(matches!(repo.remove_tag_from_task(&one.id, &bug.id), Err(RepositoryError::NotFound { .. })));
+
+    repo.delete_tag(&bug.id).unwrap();
+    assert!(repo.get_tags_for_task(&both.id).unwrap().is_empty());
+    assert_eq!(repo.get_task_by_id(&both.id).unwrap().version, 4);
+    assert!(repo.get_tags().unwrap().is_empty());
+    assert!(matches!(repo.delete_tag(&bug.id), Err(RepositoryError::NotFound { .. })));
+}
+
+// Отмененная задача архивируется по времени отмены, а не создания
+// и не прежнего завершения
+fn archive_counts_from_cancellation(repo: &dyn Repository) {
+    let alice = user(repo, "alice", UserRole::Customer);
+    let bob = user(repo, "bob", UserRole::Executor);
+    let cancelled = |title: &str, completed: Option<i64>, cancelled: i64| {
+        let mut task = Task::new(
+            title.to_string(),
+            String::new(),
+            alice.id.clone(),
+            bob.id.clone(),
+            TaskPriority::Medium,
+            None,
+            Vec::new(),
+            days_ago(60),
+        );
+        task.status = TaskStatus::Cancelled;
+        task.completed_at = completed.map(days_ago);
+        task.cancelled_at = Some(days_ago(cancelled));
+        repo.create_task(&task).unwrap();
+        task
+    };
+
+    let recent = cancelled("recent", None, 1);
+    let reopened = cancelled("reopened", Some(50), 1);
+    let stale = cancelled("stale", None, 40);
+    assert_eq!(repo.get_task_by_id(&recent.id).unwrap().cancelled_at, Some(days_ago(1)));
+
+    assert_eq!(repo.archive_tasks(days_ago(30), days_ago(0)).unwrap(), 1);
+    assert!(repo.get_task_by_id(&recent.id).is_ok());
+    assert!(repo.get_task_by_id(&reopened.id).is_ok());
+
+    let archived = repo.get_archived_tasks(&ArchiveFilter::default()).unwrap();
+    assert_eq!(archived.len(), 1);
+    assert_eq!(archived[0].id, stale.id);
+    assert_eq!(archived[0].cancelled_at, Some(days_ago(40)));
+
+    let filter = ArchiveFilter { finished_from: Some(days_ago(41)), finished_to: Some(days_ago(39)), ..Default::default() };
+    assert_eq!(repo.get_archived_tasks(&filter).unwrap().len(), 1);
+}
+
+fn archive_and_unarchive(repo: &dyn Repository) {
+    let alice = user(repo, "alice", UserRole::Customer);
+    let bob = user(repo, "bob", UserRole::Executor);
+    let tags = repo.get_or_create_tags_by_names(&["Отчеты".to_string()]).unwrap();
+
+    let mut done = task(repo, "Годовой ОТЧЕТ", &alice, &bob);
+    done.status = TaskStatus::Completed;
+    done.completed_at = Some(days_ago(40));
+    done.tags = tags;
+    repo.update_task(&done).unwrap();
+    repo.add_task_participant(&done.id, &alice.id, ParticipantRole::Watcher).unwrap();
+
+    let mut recent = task(repo, "recent", &alice, &bob);
+    recent.status = TaskStatus::Completed;
+    recent.completed_at = Some(days_ago(1));
+    repo.update_task(&recent).unwrap();
+    task(repo, "open", &alice, &bob);
+
+    assert_eq!(repo.archive_tasks(days_ago(30), days_ago(0)).unwrap(), 1);
+    assert!(matches!(repo.get_task_by_id(&done.id), Err(RepositoryError::NotFound { .. })));
+    assert_eq!(repo.get_tasks().unwrap().len(), 2);
+
+    let archived = repo.get_archived_tasks(&ArchiveFilter::default()).unwrap();
+    assert_eq!(archived.len(), 1);
+    assert_eq!(archived[0].archived_at, Some(days_ago(0)));
+    assert_eq!(archived[0].tags.len(), 1);
+    assert_eq!(archived[0].watcher_ids, [alice.id.as_str()]);
+
+    // Поиск без учета регистра, в том числе кириллицы; тег по ключу имени
+    let found = |filter: ArchiveFilter| repo.get_archived_tasks(&filter).unwrap().len();
+    assert_eq!(found(ArchiveFilter { search: Some("отчет".to_string()), ..Default::default() }), 1);
+    assert_eq!(found(ArchiveFilter { search: Some("план".to_string()), ..Default::default() }), 0);
+    assert_eq!(found(ArchiveFilter { tag: Some(" ОТЧЕТЫ".to_string()), ..Default::default() }), 1);
+    assert_eq!(found(ArchiveFilter { executor_id: Some(alice.id.clone()), ..Default::default() }), 0);
+    assert_eq!(found(ArchiveFilter { finished_from: Some(days_ago(41)), finished_to: Some(days_ago(39)), ..Default::default() }), 1);
+    assert_eq!(found(ArchiveFilter { finished_from: Some(days_ago(39)), ..Default::default() }), 0);
+
+    repo.unarchive_task(&done.id, days_ago(0)).unwrap();
+    let restored = repo.get_task_by_id(&done.id).unwrap();
+    assert_eq!(restored.tags.len(), 1);
+    assert!(restored.archived_at.is_none());
+    assert!(repo.get_archived_tasks(&ArchiveFilter::default()).unwrap().is_empty());
+    assert!(matches!(repo.unarchive_task(&done.id, days_ago(0)), Err(RepositoryError::NotFound { .. })));
+
+    // Срок архивации возвращенной задачи отсчитывается заново
+    assert_eq!(repo.archive_tasks(days_ago(30), days_ago(0)).unwrap(), 0);
+}
+
+// Временный каталог, удаляемый после выполнения набора
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(prefix: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("{}-{}", prefix, uuid::Uuid::new_v4().simple()));
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn memory_conformance() {
+    run_suite("memory", || Arc::new(InMemoryRepository::new()));
+}
+
+#[test]
+fn sqlite_conformance() {
+    let dir = TempDir::new("business-tasks-sqlite");
+    let mut databases = 0;
+    run_suite("sqlite", || {
+        databases += 1;
+        let path = dir.0.join(format!("{}.db", databases));
+        super::connect(path.to_str().unwrap()).unwrap()
+    });
+}
+
+// Сервер PostgreSQL в отдельном каталоге: принимает соединения только через
+// сокет в этом каталоге и останавливается вместе с набором. Программы initdb
+// и pg_ctl ищутся в PG_BIN или в PATH; initdb не запускается от root
+#[cfg(feature = "postgres")]
+struct LocalPostgres {
+    dir: TempDir,
+    bin: PathBuf,
+}
+
+#[cfg(feature = "postgres")]
+impl LocalPostgres {
+    // Ошибка - сервер запустить нельзя: нет initdb и pg_ctl или тесты
+    // запущены от root, которому PostgreSQL работать не позволяет
+    fn start() -> Result<Self, String> {
+        let dir = TempDir::new("business-tasks-pg");
+        let bin = std::env::var_os("PG_BIN").map(PathBuf::from).unwrap_or_default();
+        let server = Self { dir, bin };
+
+        let data = server.dir.0.join("data");
+        server.run("initdb", &["-D", data.to_str().unwrap(), "-U", "postgres", "-A", "trust", "-E", "UTF8", "--no-sync"])?;
+        let options = format!("-k {} -c listen_addresses= -F", server.dir.0.display());
+        let log = server.dir.0.join("server.log");
+        server.run(
+            "pg_ctl",
+            &["-D", data.to_str().unwrap(), "-o", &options, "-l", log.to_str().unwrap(), "-w", "start"],
+        )?;
+        Ok(server)
+    }
+
+    fn run(&self, program: &str, args: &[&str]) -> Result<(), String> {
+        let status = std::process::Command::new(self.bin.join(program))
+            .args(args)
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .map_err(|e| format!("{} failed to start: {}", program, e))?;
+        if !status.success() {
+            return Err(format!("{} exited with {}", program, status));
+        }
+        Ok(())
+    }
+
+    fn url(&self, database: &str) -> String {
+        format!("postgresql:///{}?host={}&user=postgres", database, self.dir.0.display())
+    }
+
+    // Новая пустая база данных на сервере
+    fn create_database(&self, name: &str) -> String {
+        let mut admin = ::postgres::Client::connect(&self.url("postgres"), ::postgres::NoTls).unwrap();
+        admin.batch_execute(&format!("CREATE DATABASE {}", name)).unwrap();
+        self.url(name)
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl Drop for LocalPostgres {
+    fn drop(&mut self) {
+        let data = self.dir.0.join("data");
+        let _ = std::process::Command::new(self.bin.join("pg_ctl"))
+            .args(["-D", data.to_str().unwrap(), "-m", "immediate", "-w", "stop"])
+            .stdout(std::process::Stdio::null())
+            .status();
+    }
+}
+
+// Запускается явно (--ignored) там, где есть PostgreSQL: пропуск не должен
+// выглядеть как успешная проверка
+#[cfg(feature = "postgres")]
+#[test]
+#[ignore = "starts a local PostgreSQL server; run with --ignored where initdb and pg_ctl are available"]
+fn postgres_conformance() {
+    let server = LocalPostgres::start()
+        .unwrap_or_else(|e| panic!("cannot start PostgreSQL ({}); set PG_BIN and run as a non-root user", e));
+    let mut databases = 0;
+    run_suite("postgres", || {
+        databases += 1;
+        let url = server.create_database(&format!("conformance_{}", databases));
+        super::connect(&url).unwrap()
+    });
+}
@@ -0,0 +1,1071 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::models::tag::{clean_tag_name, normalize_tag_name};
+use crate::models::{Delegation, ParticipantRole, Project, SavedView, Task, TaskStatus, Tag, TimeEntry, User};
+use super::{ArchiveFilter, Entity, Repository, RepositoryError, Result, TaskChange, TimeEntryFilter};
+
+// Хранилище в памяти для тестов и демонстрации. Повторяет поведение SqliteRepository:
+// те же ошибки NotFound и VersionConflict, ограничения уникальности и внешних ключей
+// (с текстом в формате SQLite), перенос в корзину и архив. Порядок записей без
+// явной сортировки - порядок добавления, как rowid в SQLite
+pub struct InMemoryRepository {
+    state: Mutex<State>,
+}
+
+// Таблицы хранилища. Задачи хранятся без тегов, участников и учтенного времени:
+// они собираются из связей при чтении
+#[derive(Default, Clone)]
+struct State {
+    users: Vec<User>,
+    tasks: Vec<StoredTask>,
+    task_tags: Vec<(String, String)>, // (task_id, tag_id)
+    participants: Vec<(String, String, ParticipantRole)>, // (task_id, user_id, role)
+    delegations: Vec<Delegation>,
+    wip_limits: HashMap<String, u32>,
+    time_entries: Vec<TimeEntry>,
+    projects: Vec<Project>,
+    saved_views: Vec<SavedView>,
+    default_views: Vec<(String, String)>, // (user_id, view_id)
+    archived_tasks: Vec<Task>,
+    archived_task_tags: Vec<(String, String)>,
+    tags: Vec<Tag>,
+}
+
+#[derive(Clone)]
+struct StoredTask {
+    task: Task,
+    unarchived_at: Option<DateTime<Utc>>, // Время возврата из архива
+}
+
+impl InMemoryRepository {
+    pub fn new() -> Self {
+        Self { state: Mutex::new(State::default()) }
+    }
+
+    fn state(&self) -> Result<MutexGuard<'_, State>> {
+        self.state.lock().map_err(|e| RepositoryError::Internal(e.to_string()))
+    }
+
+    // Изменения применяются к копии состояния, которая заменяет исходное
+    // только при успехе всех изменений
+    fn transaction<T>(&self, f: impl FnOnce(&mut State) -> Result<T>) -> Result<T> {
+        let mut state = self.state()?;
+        let mut draft = state.clone();
+        let result = f(&mut draft)?;
+        *state = draft;
+        Ok(result)
+    }
+}
+
+impl Default for InMemoryRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Repository for InMemoryRepository {
+    // User methods
+    fn get_users(&self) -> Result<Vec<User>> {
+        let state = self.state()?;
+        Ok(state.users.iter().filter(|u| u.deleted_at.is_none()).cloned().collect())
+    }
+
+    fn get_user_by_id(&self, id: &str) -> Result<User> {
+        let state = self.state()?;
+        state.active_user(id).cloned().ok_or_else(|| RepositoryError::not_found(Entity::User, id))
+    }
+
+    fn create_user(&self, user: &User) -> Result<()> {
+        let mut state = self.state()?;
+
+        if state.users.iter().any(|u| u.id == user.id) {
+            return Err(unique("users.id"));
+        }
+        if state.users.iter().any(|u| u.email == user.email) {
+            return Err(unique("users.email"));
+        }
+
+        state.users.push(User {
+            deleted_at: None,
+            active: true,
+            deactivated_at: None,
+            ..user.clone()
+        });
+        Ok(())
+    }
+
+    fn update_user(&self, user: &User) -> Result<()> {
+        let mut state = self.state()?;
+
+        let current = state.active_user(&user.id).ok_or_else(|| RepositoryError::not_found(Entity::User, &user.id))?;
+        check_version(&user.id, user.version, current.version)?;
+        if state.users.iter().any(|u| u.id != user.id && u.email == user.email) {
+            return Err(unique("users.email"));
+        }
+
+        let stored = state.active_user_mut(&user.id).unwrap();
+        stored.name = user.name.clone();
+        stored.email = user.email.clone();
+        stored.role = user.role.clone();
+        stored.time_zone = user.time_zone.clone();
+        stored.locale = user.locale;
+        stored.version += 1;
+        Ok(())
+    }
+
+    fn delete_user(&self, id: &str, now: DateTime<Utc>) -> Result<()> {
+        self.state()?.soft_delete_user(id, now)
+    }
+
+    fn delete_user_with_handover(&self, id: &str, changes: &[TaskChange], now: DateTime<Utc>) -> Result<()> {
+        self.transaction(|state| {
+            for change in changes {
+                state.write_task_change(change, now)?;
+            }
+            state.soft_delete_user(id, now)
+        })
+    }
+
+    // Повторная деактивация сохраняет время первой
+    fn deactivate_user(&self, id: &str, now: DateTime<Utc>) -> Result<()> {
+        let mut state = self.state()?;
+        let user = state.active_user_mut(id).ok_or_else(|| RepositoryError::not_found(Entity::User, id))?;
+
+        if user.active {
+            user.deactivated_at = Some(now);
+        }
+        user.active = false;
+        user.version += 1;
+        Ok(())
+    }
+
+    fn reactivate_user(&self, id: &str) -> Result<()> {
+        let mut state = self.state()?;
+        let user = state.active_user_mut(id).ok_or_else(|| RepositoryError::not_found(Entity::User, id))?;
+
+        user.active = true;
+        user.deactivated_at = None;
+        user.version += 1;
+        Ok(())
+    }
+
+    // Task methods
+    fn get_tasks(&self) -> Result<Vec<Task>> {
+        let state = self.state()?;
+        Ok(state
+            .tasks
+            .iter()
+            .filter(|t| t.task.deleted_at.is_none())
+            .map(|t| state.assemble(&t.task, &state.task_tags))
+            .collect())
+    }
+
+    fn get_task_by_id(&self, id: &str) -> Result<Task> {
+        let state = self.state()?;
+        let task = state.active_task(id).ok_or_else(|| RepositoryError::not_found(Entity::Task, id))?;
+        Ok(state.assemble(&task.task, &state.task_tags))
+    }
+
+    // Как и в SQLite, задача сохраняется до проверки тегов: при ошибке
+    // в теге она остается без него
+    fn create_task(&self, task: &Task) -> Result<()> {
+        let mut state = self.state()?;
+
+        if state.tasks.iter().any(|t| t.task.id == task.id) {
+            return Err(unique("tasks.id"));
+        }
+        state.check_task_references(task)?;
+
+        state.tasks.push(StoredTask {
+            task: Task { deleted_at: None, ..bare_task(task) },
+            unarchived_at: None,
+        });
+
+        for tag in &task.tags {
+            state.add_tag_to_task(&task.id, &tag.id)?;
+        }
+        Ok(())
+    }
+
+    fn update_task(&self, task: &Task) -> Result<()> {
+        self.state()?.write_task_update(task)
+    }
+
+    fn delete_task(&self, id: &str, now: DateTime<Utc>) -> Result<()> {
+        let mut state = self.state()?;
+        let stored = state.active_task_mut(id).ok_or_else(|| RepositoryError::not_found(Entity::Task, id))?;
+
+        stored.task.deleted_at = Some(now);
+        stored.task.version += 1;
+        Ok(())
+    }
+
+    // Как INSERT OR REPLACE: участник с новой ролью переносится в конец списка
+    fn add_task_participant(&self, task_id: &str, user_id: &str, role: ParticipantRole) -> Result<()> {
+        let mut state = self.state()?;
+
+        if !state.user_exists(user_id) {
+            return Err(foreign_key());
+        }
+
+        state.participants.retain(|(t, u, _)| !(t == task_id && u == user_id));
+        state.participants.push((task_id.to_string(), user_id.to_string(), role));
+        state.bump_versions(&[task_id.to_string()]);
+        Ok(())
+    }
+
+    fn remove_task_participant(&self, task_id: &str, user_id: &str, role: ParticipantRole) -> Result<()> {
+        let mut state = self.state()?;
+
+        let before = state.participants.len();
+        state.participants.retain(|(t, u, r)| !(t == task_id && u == user_id && *r == role));
+
+        if state.participants.len() == before {
+            return Err(RepositoryError::not_found(Entity::Participant, user_id));
+        }
+        state.bump_versions(&[task_id.to_string()]);
+
+        Ok(())
+    }
+
+    fn move_task(&self, task: &Task, before_id: Option<&str>) -> Result<i64> {
+        let mut state = self.state()?;
+
+        let current = state.active_task(&task.id).ok_or_else(|| RepositoryError::not_found(Entity::Task, &task.id))?;
+        check_version(&task.id, task.version, current.task.version)?;
+
+        let stored = state.active_task_mut(&task.id).unwrap();
+        stored.task.status = task.status.clone();
+        stored.task.completed_at = task.completed_at;
+        stored.task.cancelled_at = task.cancelled_at;
+        stored.task.version += 1;
+
+        // Колонка перенумеровывается целиком: ранги идут с 1 без пропусков
+        let mut column: Vec<(i64, DateTime<Utc>, String)> = state
+            .tasks
+            .iter()
+            .filter(|t| t.task.deleted_at.is_none() && t.task.status == task.status && t.task.id != task.id)
+            .map(|t| (t.task.rank, t.task.created_at, t.task.id.clone()))
+            .collect();
+        column.sort_by_key(|(rank, created_at, _)| (*rank, *created_at));
+        let mut ids: Vec<String> = column.into_iter().map(|(_, _, id)| id).collect();
+
+        let position = before_id
+            .and_then(|before_id| ids.iter().position(|id| id == before_id))
+            .unwrap_or(ids.len());
+        ids.insert(position, task.id.clone());
+
+        for (index, id) in ids.iter().enumerate() {
+            if let Some(stored) = state.tasks.iter_mut().find(|t| t.task.id == *id) {
+                stored.task.rank = index as i64 + 1;
+            }
+        }
+
+        Ok(position as i64 + 1)
+    }
+
+    fn reassign_task(&self, task: &Task, delegation: &Delegation) -> Result<()> {
+        self.state()?.write_task_reassign(task, delegation)
+    }
+
+    fn apply_task_changes(&self, changes: &[TaskChange], now: DateTime<Utc>) -> Result<()> {
+        self.transaction(|state| {
+            for change in changes {
+                state.write_task_change(change, now)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn get_task_delegations(&self, task_id: &str) -> Result<Vec<Delegation>> {
+        let state = self.state()?;
+        let mut delegations: Vec<Delegation> =
+            state.delegations.iter().filter(|d| d.task_id == task_id).cloned().collect();
+        delegations.sort_by_key(|d| d.created_at);
+        Ok(delegations)
+    }
+
+    fn get_delegations(&self) -> Result<Vec<Delegation>> {
+        let state = self.state()?;
+        let mut delegations = state.delegations.clone();
+        delegations.sort_by_key(|d| d.created_at);
+        Ok(delegations)
+    }
+
+    // Board methods
+    fn get_wip_limits(&self) -> Result<HashMap<String, u32>> {
+        Ok(self.state()?.wip_limits.clone())
+    }
+
+    fn set_wip_limit(&self, status: &str, limit: Option<u32>) -> Result<()> {
+        let mut state = self.state()?;
+        match limit {
+            Some(limit) => state.wip_limits.insert(status.to_string(), limit),
+            None => state.wip_limits.remove(status),
+        };
+        Ok(())
+    }
+
+    // Time tracking methods
+    fn create_time_entry(&self, entry: &TimeEntry) -> Result<()> {
+        let mut state = self.state()?;
+
+        if state.time_entries.iter().any(|e| e.id == entry.id) {
+            return Err(unique("time_entries.id"));
+        }
+        if !state.user_exists(&entry.user_id) {
+            return Err(foreign_key());
+        }
+        // Как уникальный индекс по запущенным таймерам в SQL-хранилищах
+        let running = |e: &TimeEntry| e.user_id == entry.user_id && e.ended_at.is_none();
+        if entry.ended_at.is_none() && state.time_entries.iter().any(running) {
+            return Err(unique("time_entries.user_id"));
+        }
+
+        state.time_entries.push(entry.clone());
+        state.bump_versions(std::slice::from_ref(&entry.task_id));
+        Ok(())
+    }
+
+    fn stop_time_entry(&self, entry: &TimeEntry) -> Result<()> {
+        let mut state = self.state()?;
+        let stored = state
+            .time_entries
+            .iter_mut()
+            .find(|e| e.id == entry.id && e.ended_at.is_none())
+            .ok_or_else(|| RepositoryError::not_found(Entity::RunningTimeEntry, &entry.id))?;
+
+        stored.ended_at = entry.ended_at;
+        stored.minutes = entry.minutes;
+        let task_id = stored.task_id.clone();
+        state.bump_versions(&[task_id]);
+        Ok(())
+    }
+
+    fn delete_time_entry(&self, id: &str) -> Result<()> {
+        let mut state = self.state()?;
+
+        let index = state
+            .time_entries
+            .iter()
+            .position(|e| e.id == id)
+            .ok_or_else(|| RepositoryError::not_found(Entity::TimeEntry, id))?;
+        let entry = state.time_entries.remove(index);
+        state.bump_versions(&[entry.task_id]);
+        Ok(())
+    }
+
+    fn get_running_time_entry(&self, user_id: &str) -> Result<Option<TimeEntry>> {
+        let state = self.state()?;
+        Ok(state.time_entries.iter().find(|e| e.user_id == user_id && e.ended_at.is_none()).cloned())
+    }
+
+    fn get_time_entries(&self, filter: &TimeEntryFilter) -> Result<Vec<TimeEntry>> {
+        let state = self.state()?;
+        let mut entries: Vec<TimeEntry> = state
+            .time_entries
+            .iter()
+            .filter(|e| filter.task_id.as_ref().is_none_or(|id| e.task_id == *id))
+            .filter(|e| filter.user_id.as_ref().is_none_or(|id| e.user_id == *id))
+            .filter(|e| filter.started_from.is_none_or(|from| e.started_at >= from))
+            .filter(|e| filter.started_to.is_none_or(|to| e.started_at < to))
+            .cloned()
+            .collect();
+        entries.sort_by_key(|e| e.started_at);
+        Ok(entries)
+    }
+
+    // Project methods
+    fn get_projects(&self) -> Result<Vec<Project>> {
+        let mut projects = self.state()?.projects.clone();
+        projects.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(projects)
+    }
+
+    fn get_project_by_id(&self, id: &str) -> Result<Project> {
+        let state = self.state()?;
+        state.projects.iter().find(|p| p.id == id).cloned().ok_or_else(|| RepositoryError::not_found(Entity::Project, id))
+    }
+
+    fn create_project(&self, project: &Project) -> Result<()> {
+        let mut state = self.state()?;
+
+        if state.projects.iter().any(|p| p.id == project.id) {
+            return Err(unique("projects.id"));
+        }
+        state.check_project_references(project)?;
+
+        state.projects.push(project.clone());
+        Ok(())
+    }
+
+    fn update_project(&self, project: &Project) -> Result<()> {
+        let mut state = self.state()?;
+
+        let current = state.projects.iter().find(|p| p.id == project.id).ok_or_else(|| RepositoryError::not_found(Entity::Project, &project.id))?;
+        check_version(&project.id, project.version, current.version)?;
+        state.check_project_references(project)?;
+
+        let stored = state.projects.iter_mut().find(|p| p.id == project.id).unwrap();
+        stored.name = project.name.clone();
+        stored.description = project.description.clone();
+        stored.owner_id = project.owner_id.clone();
+        stored.customer_id = project.customer_id.clone();
+        stored.status = project.status.clone();
+        stored.version += 1;
+        Ok(())
+    }
+
+    // Задачи проекта, включая архивные и удаленные, остаются без проекта
+    fn delete_project(&self, id: &str) -> Result<()> {
+        let mut state = self.state()?;
+
+        if !state.projects.iter().any(|p| p.id == id) {
+            return Err(RepositoryError::not_found(Entity::Project, id));
+        }
+
+        for stored in state.tasks.iter_mut().filter(|t| t.task.project_id.as_deref() == Some(id)) {
+            stored.task.project_id = None;
+            stored.task.version += 1;
+        }
+        for task in state.archived_tasks.iter_mut().filter(|t| t.project_id.as_deref() == Some(id)) {
+            task.project_id = None;
+        }
+        state.projects.retain(|p| p.id != id);
+        Ok(())
+    }
+
+    fn get_tasks_by_project(&self, project_id: &str) -> Result<Vec<Task>> {
+        let state = self.state()?;
+        Ok(state
+            .tasks
+            .iter()
+            .filter(|t| t.task.deleted_at.is_none() && t.task.project_id.as_deref() == Some(project_id))
+            .map(|t| state.assemble(&t.task, &state.task_tags))
+            .collect())
+    }
+
+    // Saved view methods
+    fn get_saved_views(&self, user_id: &str) -> Result<Vec<SavedView>> {
+        let state = self.state()?;
+        let mut views: Vec<SavedView> =
+            state.saved_views.iter().filter(|v| v.owner_id == user_id || v.shared).cloned().collect();
+        views.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(views)
+    }
+
+    fn get_saved_view_by_id(&self, id: &str) -> Result<SavedView> {
+        let state = self.state()?;
+        state.saved_views.iter().find(|v| v.id == id).cloned().ok_or_else(|| RepositoryError::not_found(Entity::SavedView, id))
+    }
+
+    fn create_saved_view(&self, view: &SavedView) -> Result<()> {
+        let mut state = self.state()?;
+
+        if state.saved_views.iter().any(|v| v.id == view.id) {
+            return Err(unique("saved_views.id"));
+        }
+        if !state.user_exists(&view.owner_id) {
+            return Err(foreign_key());
+        }
+
+        state.saved_views.push(view.clone());
+        Ok(())
+    }
+
+    // Если вид перестал быть общим, он перестает быть видом по умолчанию у других пользователей
+    fn update_saved_view(&self, view: &SavedView) -> Result<()> {
+        let mut state = self.state()?;
+        let stored = state
+            .saved_views
+            .iter_mut()
+            .find(|v| v.id == view.id)
+            .ok_or_else(|| RepositoryError::not_found(Entity::SavedView, &view.id))?;
+
+        stored.name = view.name.clone();
+        stored.filters = view.filters.clone();
+        stored.sort = view.sort.clone();
+        stored.columns = view.columns.clone();
+        stored.shared = view.shared;
+
+        if !view.shared {
+            state.default_views.retain(|(user_id, view_id)| !(*view_id == view.id && *user_id != view.owner_id));
+        }
+
+        Ok(())
+    }
+
+    fn delete_saved_view(&self, id: &str) -> Result<()> {
+        let mut state = self.state()?;
+
+        if !state.saved_views.iter().any(|v| v.id == id) {
+            return Err(RepositoryError::not_found(Entity::SavedView, id));
+        }
+
+        state.default_views.retain(|(_, view_id)| view_id != id);
+        state.saved_views.retain(|v| v.id != id);
+        Ok(())
+    }
+
+    fn get_default_view_id(&self, user_id: &str) -> Result<Option<String>> {
+        let state = self.state()?;
+        Ok(state.default_views.iter().find(|(u, _)| u == user_id).map(|(_, view_id)| view_id.clone()))
+    }
+
+    fn set_default_view(&self, user_id: &str, view_id: Option<&str>) -> Result<()> {
+        let mut state = self.state()?;
+
+        if let Some(view_id) = view_id {
+            if !state.user_exists(user_id) || !state.saved_views.iter().any(|v| v.id == view_id) {
+                return Err(foreign_key());
+            }
+        }
+
+        state.default_views.retain(|(u, _)| u != user_id);
+        if let Some(view_id) = view_id {
+            state.default_views.push((user_id.to_string(), view_id.to_string()));
+        }
+        Ok(())
+    }
+
+    // Trash methods
+    fn get_deleted_tasks(&self) -> Result<Vec<Task>> {
+        let state = self.state()?;
+        let mut tasks: Vec<Task> = state
+            .tasks
+            .iter()
+            .filter(|t| t.task.deleted_at.is_some())
+            .map(|t| state.assemble(&t.task, &state.task_tags))
+            .collect();
+        tasks.sort_by_key(|t| std::cmp::Reverse(t.deleted_at));
+        Ok(tasks)
+    }
+
+    fn get_deleted_users(&self) -> Result<Vec<User>> {
+        let state = self.state()?;
+        let mut users: Vec<User> = state.users.iter().filter(|u| u.deleted_at.is_some()).cloned().collect();
+        users.sort_by_key(|u| std::cmp::Reverse(u.deleted_at));
+        Ok(users)
+    }
+
+    fn restore_task(&self, id: &str) -> Result<()> {
+        let mut state = self.state()?;
+        let stored = state
+            .tasks
+            .iter_mut()
+            .find(|t| t.task.id == id && t.task.deleted_at.is_some())
+            .ok_or_else(|| RepositoryError::not_found(Entity::DeletedTask, id))?;
+
+        stored.task.deleted_at = None;
+        stored.task.version += 1;
+        Ok(())
+    }
+
+    fn restore_user(&self, id: &str) -> Result<()> {
+        let mut state = self.state()?;
+        let user = state
+            .users
+            .iter_mut()
+            .find(|u| u.id == id && u.deleted_at.is_some())
+            .ok_or_else(|| RepositoryError::not_found(Entity::DeletedUser, id))?;
+
+        user.deleted_at = None;
+        user.version += 1;
+        Ok(())
+    }
+
+    fn purge_deleted(&self, deleted_before: DateTime<Utc>) -> Result<usize> {
+        let mut state = self.state()?;
+        let expired = |deleted_at: Option<DateTime<Utc>>| deleted_at.is_some_and(|at| at < deleted_before);
+
+        let purged_tasks: Vec<String> = state
+            .tasks
+            .iter()
+            .filter(|t| expired(t.task.deleted_at))
+            .map(|t| t.task.id.clone())
+            .collect();
+        state.task_tags.retain(|(task_id, _)| !purged_tasks.contains(task_id));
+        state.participants.retain(|(task_id, _, _)| !purged_tasks.contains(task_id));
+        state.delegations.retain(|d| !purged_tasks.contains(&d.task_id));
+        state.time_entries.retain(|e| !purged_tasks.contains(&e.task_id));
+        state.tasks.retain(|t| !purged_tasks.contains(&t.task.id));
+
+        // Пользователи, на которых еще ссылаются задачи, проекты, история передач
+        // или учет времени, остаются в корзине
+        let purged_users: Vec<String> = state
+            .users
+            .iter()
+            .filter(|u| expired(u.deleted_at) && !state.user_is_referenced(&u.id))
+            .map(|u| u.id.clone())
+            .collect();
+        state.users.retain(|u| !purged_users.contains(&u.id));
+        // Участие в задачах и виды списка удаляются вместе с пользователем
+        state.participants.retain(|(_, user_id, _)| !purged_users.contains(user_id));
+        let purged_views: Vec<String> = state
+            .saved_views
+            .iter()
+            .filter(|v| purged_users.contains(&v.owner_id))
+            .map(|v| v.id.clone())
+            .collect();
+        state.default_views.retain(|(user_id, view_id)| {
+            !purged_users.contains(user_id) && !purged_views.contains(view_id)
+        });
+        state.saved_views.retain(|v| !purged_views.contains(&v.id));
+
+        Ok(purged_tasks.len() + purged_users.len())
+    }
+
+    // Archive methods
+    fn archive_tasks(&self, finished_before: DateTime<Utc>, now: DateTime<Utc>) -> Result<usize> {
+        let mut state = self.state()?;
+        let archived_at = now;
+
+        let (archived, kept): (Vec<StoredTask>, Vec<StoredTask>) =
+            std::mem::take(&mut state.tasks).into_iter().partition(|t| {
+                t.task.deleted_at.is_none()
+                    && matches!(t.task.status, TaskStatus::Completed | TaskStatus::Cancelled)
+                    && t.unarchived_at.unwrap_or_else(|| t.task.finished_at()) < finished_before
+            });
+        state.tasks = kept;
+
+        for stored in &archived {
+            let id = &stored.task.id;
+            let links: Vec<(String, String)> = state.task_tags.iter().filter(|(t, _)| t == id).cloned().collect();
+            state.task_tags.retain(|(t, _)| t != id);
+            state.archived_task_tags.extend(links);
+            state.archived_tasks.push(Task { archived_at: Some(archived_at), ..stored.task.clone() });
+        }
+
+        Ok(archived.len())
+    }
+
+    fn get_archived_tasks(&self, filter: &ArchiveFilter) -> Result<Vec<Task>> {
+        let state = self.state()?;
+        let tag_key = filter.tag.as_deref().map(normalize_tag_name);
+        let needle = filter.search.as_ref().map(|s| s.to_lowercase());
+
+        let mut tasks: Vec<Task> = state
+            .archived_tasks
+            .iter()
+            .filter(|t| filter.status.as_ref().is_none_or(|status| t.status.code() == status))
+            .filter(|t| filter.customer_id.as_ref().is_none_or(|id| t.customer_id == *id))
+            .filter(|t| filter.executor_id.as_ref().is_none_or(|id| t.executor_id == *id))
+            .filter(|t| {
+                tag_key.as_ref().is_none_or(|key| {
+                    state.archived_task_tags.iter().any(|(task_id, tag_id)| {
+                        *task_id == t.id
+                            && state.tags.iter().any(|tag| tag.id == *tag_id && normalize_tag_name(&tag.name) == *key)
+                    })
+                })
+            })
+            .filter(|t| filter.finished_from.is_none_or(|from| t.finished_at() >= from))
+            .filter(|t| filter.finished_to.is_none_or(|to| t.finished_at() < to))
+            .filter(|t| {
+                needle.as_ref().is_none_or(|needle| {
+                    t.title.to_lowercase().contains(needle) || t.description.to_lowercase().contains(needle)
+                })
+            })
+            .map(|t| state.assemble(t, &state.archived_task_tags))
+            .collect();
+        tasks.sort_by_key(|t| std::cmp::Reverse(t.finished_at()));
+        Ok(tasks)
+    }
+
+    fn unarchive_task(&self, id: &str, now: DateTime<Utc>) -> Result<()> {
+        let mut state = self.state()?;
+
+        let position = state
+            .archived_tasks
+            .iter()
+            .position(|t| t.id == id)
+            .ok_or_else(|| RepositoryError::not_found(Entity::ArchivedTask, id))?;
+        if state.tasks.iter().any(|t| t.task.id == id) {
+            return Err(unique("tasks.id"));
+        }
+
+        let task = state.archived_tasks.remove(position);
+        let links: Vec<(String, String)> = state.archived_task_tags.iter().filter(|(t, _)| t == id).cloned().collect();
+        state.archived_task_tags.retain(|(t, _)| t != id);
+        state.task_tags.extend(links);
+        state.tasks.push(StoredTask {
+            task: Task { archived_at: None, ..task },
+            unarchived_at: Some(now),
+        });
+        Ok(())
+    }
+
+    // Tag methods
+    fn get_tags(&self) -> Result<Vec<Tag>> {
+        let mut tags = self.state()?.tags.clone();
+        tags.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(tags)
+    }
+
+    fn get_tag_by_id(&self, id: &str) -> Result<Tag> {
+        let state = self.state()?;
+        state.tags.iter().find(|t| t.id == id).cloned().ok_or_else(|| RepositoryError::not_found(Entity::Tag, id))
+    }
+
+    fn create_tag(&self, tag: &Tag) -> Result<()> {
+        let mut state = self.state()?;
+
+        if state.tags.iter().any(|t| t.id == tag.id) {
+            return Err(unique("tags.id"));
+        }
+        state.check_tag_name(tag)?;
+
+        state.tags.push(tag.clone());
+        Ok(())
+    }
+
+    fn get_or_create_tags_by_names(&self, names: &[String]) -> Result<Vec<Tag>> {
+        let mut state = self.state()?;
+
+        // Имена, различающиеся только регистром, пробелами или ё/е, дают один тег
+        let mut wanted: Vec<(String, String)> = Vec::new();
+        for name in names {
+            let key = normalize_tag_name(name);
+            if !key.is_empty() && !wanted.iter().any(|(k, _)| *k == key) {
+                wanted.push((key, clean_tag_name(name)));
+            }
+        }
+
+        let mut tags = Vec::new();
+        for (key, name) in wanted {
+            match state.tags.iter().find(|t| normalize_tag_name(&t.name) == key) {
+                Some(tag) => tags.push(tag.clone()),
+                None => {
+                    let tag = Tag::new(name);
+                    state.tags.push(tag.clone());
+                    tags.push(tag);
+                }
+            }
+        }
+
+        Ok(tags)
+    }
+
+    fn update_tag(&self, tag: &Tag) -> Result<()> {
+        let mut state = self.state()?;
+
+        if !state.tags.iter().any(|t| t.id == tag.id) {
+            return Err(RepositoryError::not_found(Entity::Tag, &tag.id));
+        }
+        state.check_tag_name(tag)?;
+
+        let stored = state.tags.iter_mut().find(|t| t.id == tag.id).unwrap();
+        *stored = tag.clone();
+        let tagged = state.tagged_tasks(&tag.id);
+        state.bump_versions(&tagged);
+        Ok(())
+    }
+
+    // Тег удаляется вместе со связями с задачами, в том числе архивными
+    fn delete_tag(&self, id: &str) -> Result<()> {
+        let mut state = self.state()?;
+
+        if !state.tags.iter().any(|t| t.id == id) {
+            return Err(RepositoryError::not_found(Entity::Tag, id));
+        }
+
+        let tagged = state.tagged_tasks(id);
+        state.bump_versions(&tagged);
+        state.task_tags.retain(|(_, tag_id)| tag_id != id);
+        state.archived_task_tags.retain(|(_, tag_id)| tag_id != id);
+        state.tags.retain(|t| t.id != id);
+        Ok(())
+    }
+
+    // Задачи, у которых были оба тега, получают одну связь с целевым
+    fn merge_tags(&self, source_id: &str, target_id: &str) -> Result<()> {
+        let mut state = self.state()?;
+
+        for id in [source_id, target_id] {
+            if !state.tags.iter().any(|t| t.id == id) {
+                return Err(RepositoryError::not_found(Entity::Tag, id));
+            }
+        }
+
+        let tagged = state.tagged_tasks(source_id);
+        state.bump_versions(&tagged);
+        let state = &mut *state;
+        for links in [&mut state.task_tags, &mut state.archived_task_tags] {
+            let moved: Vec<String> = links
+                .iter()
+                .filter(|(_, tag_id)| tag_id == source_id)
+                .map(|(task_id, _)| task_id.clone())
+                .collect();
+            links.retain(|(_, tag_id)| tag_id != source_id);
+            for task_id in moved {
+                if !links.iter().any(|(t, tag_id)| *t == task_id && tag_id == target_id) {
+                    links.push((task_id, target_id.to_string()));
+                }
+            }
+        }
+        state.tags.retain(|t| t.id != source_id);
+        Ok(())
+    }
+
+    // Учитываются только задачи основного списка: без корзины и архива
+    fn get_tag_usage_counts(&self) -> Result<HashMap<String, usize>> {
+        let state = self.state()?;
+        let mut counts = HashMap::new();
+        for (task_id, tag_id) in &state.task_tags {
+            if state.active_task(task_id).is_some() {
+                *counts.entry(tag_id.clone()).or_insert(0) += 1;
+            }
+        }
+        Ok(counts)
+    }
+
+    fn get_tags_for_task(&self, task_id: &str) -> Result<Vec<Tag>> {
+        let state = self.state()?;
+        Ok(state.tags_for_task(&state.task_tags, task_id))
+    }
+
+    fn add_tag_to_task(&self, task_id: &str, tag_id: &str) -> Result<()> {
+        self.state()?.add_tag_to_task(task_id, tag_id)
+    }
+
+    fn remove_tag_from_task(&self, task_id: &str, tag_id: &str) -> Result<()> {
+        let mut state = self.state()?;
+
+        let before = state.task_tags.len();
+        state.task_tags.retain(|(t, g)| !(t == task_id && g == tag_id));
+
+        if state.task_tags.len() == before {
+            return Err(RepositoryError::not_found(Entity::TaskTag, tag_id));
+        }
+
+        Ok(())
+    }
+}
+
+impl State {
+    fn user_exists(&self, id: &str) -> bool {
+        self.users.iter().any(|u| u.id == id)
+    }
+
+    fn active_user(&self, id: &str) -> Option<&User> {
+        self.users.iter().find(|u| u.id == id && u.deleted_at.is_none())
+    }
+
+    fn active_user_mut(&mut self, id: &str) -> Option<&mut User> {
+        self.users.iter_mut().find(|u| u.id == id && u.deleted_at.is_none())
+    }
+
+    fn active_task(&self, id: &str) -> Option<&StoredTask> {
+        self.tasks.iter().find(|t| t.task.id == id && t.task.deleted_at.is_none())
+    }
+
+    fn active_task_mut(&mut self, id: &str) -> Option<&mut StoredTask> {
+        self.tasks.iter_mut().find(|t| t.task.id == id && t.task.deleted_at.is_none())
+    }
+
+    // Задачи основного списка с тегом tag_id
+    fn tagged_tasks(&self, tag_id: &str) -> Vec<String> {
+        self.task_tags.iter().filter(|(_, t)| t == tag_id).map(|(task_id, _)| task_id.clone()).collect()
+    }
+
+    // Изменение тегов, участников или проекта меняет представление задачи,
+    // поэтому растет и ее версия
+    fn bump_versions(&mut self, task_ids: &[String]) {
+        for stored in self.tasks.iter_mut().filter(|t| task_ids.contains(&t.task.id)) {
+            stored.task.version += 1;
+        }
+    }
+
+    fn soft_delete_user(&mut self, id: &str, now: DateTime<Utc>) -> Result<()> {
+        let user = self.active_user_mut(id).ok_or_else(|| RepositoryError::not_found(Entity::User, id))?;
+        user.deleted_at = Some(now);
+        user.version += 1;
+        Ok(())
+    }
+
+    // Ссылки, из-за которых пользователь не удаляется из корзины окончательно
+    fn user_is_referenced(&self, id: &str) -> bool {
+        self.tasks.iter().any(|t| t.task.customer_id == id || t.task.executor_id == id)
+            || self.archived_tasks.iter().any(|t| t.customer_id == id || t.executor_id == id)
+            || self.projects.iter().any(|p| p.owner_id == id || p.customer_id.as_deref() == Some(id))
+            || self.delegations.iter().any(|d| d.from_user_id == id || d.to_user_id == id || d.by_user_id == id)
+            || self.time_entries.iter().any(|e| e.user_id == id)
+    }
+
+    // Внешние ключи задачи: заказчик, исполнитель и проект
+    fn check_task_references(&self, task: &Task) -> Result<()> {
+        let project_exists = task
+            .project_id
+            .as_ref()
+            .is_none_or(|id| self.projects.iter().any(|p| p.id == *id));
+        if !self.user_exists(&task.customer_id) || !self.user_exists(&task.executor_id) || !project_exists {
+            return Err(foreign_key());
+        }
+        Ok(())
+    }
+
+    fn check_project_references(&self, project: &Project) -> Result<()> {
+        let customer_exists = project.customer_id.as_ref().is_none_or(|id| self.user_exists(id));
+        if !self.user_exists(&project.owner_id) || !customer_exists {
+            return Err(foreign_key());
+        }
+        Ok(())
+    }
+
+    // Уникальность имени тега и его ключа среди остальных тегов
+    fn check_tag_name(&self, tag: &Tag) -> Result<()> {
+        let others = || self.tags.iter().filter(|t| t.id != tag.id);
+        if others().any(|t| t.name == tag.name) {
+            return Err(unique("tags.name"));
+        }
+        let key = normalize_tag_name(&tag.name);
+        if others().any(|t| normalize_tag_name(&t.name) == key) {
+            return Err(unique("tags.name_key"));
+        }
+        Ok(())
+    }
+
+    fn add_tag_to_task(&mut self, task_id: &str, tag_id: &str) -> Result<()> {
+        if self.active_task(task_id).is_none() {
+            return Err(RepositoryError::not_found(Entity::Task, task_id));
+        }
+        if !self.tags.iter().any(|t| t.id == tag_id) {
+            return Err(RepositoryError::not_found(Entity::Tag, tag_id));
+        }
+
+        if !self.task_tags.iter().any(|(t, g)| t == task_id && g == tag_id) {
+            self.task_tags.push((task_id.to_string(), tag_id.to_string()));
+        }
+        Ok(())
+    }
+
+    // Теги задачи из связей task_tags или archived_task_tags, по алфавиту
+    fn tags_for_task(&self, links: &[(String, String)], task_id: &str) -> Vec<Tag> {
+        let mut tags: Vec<Tag> = links
+            .iter()
+            .filter(|(t, _)| t == task_id)
+            .filter_map(|(_, tag_id)| self.tags.iter().find(|tag| tag.id == *tag_id).cloned())
+            .collect();
+        tags.sort_by(|a, b| a.name.cmp(&b.name));
+        tags
+    }
+
+    // Задача с тегами, участниками и временем по завершенным записям учета
+    fn assemble(&self, task: &Task, links: &[(String, String)]) -> Task {
+        let mut task = task.clone();
+        task.tags = self.tags_for_task(links, &task.id);
+        for (task_id, user_id, role) in &self.participants {
+            if *task_id == task.id {
+                match role {
+                    ParticipantRole::CoExecutor => task.co_executor_ids.push(user_id.clone()),
+                    ParticipantRole::Watcher => task.watcher_ids.push(user_id.clone()),
+                }
+            }
+        }
+        task.spent_minutes = self
+            .time_entries
+            .iter()
+            .filter(|e| e.task_id == task.id && e.ended_at.is_some())
+            .map(|e| e.minutes)
+            .sum();
+        task
+    }
+
+    // Сохраняет поля и теги задачи; task.version - версия, которую видел клиент
+    fn write_task_update(&mut self, task: &Task) -> Result<()> {
+        let current = self.active_task(&task.id).ok_or_else(|| RepositoryError::not_found(Entity::Task, &task.id))?;
+        check_version(&task.id, task.version, current.task.version)?;
+        self.check_task_references(task)?;
+        if task.tags.iter().any(|tag| !self.tags.iter().any(|t| t.id == tag.id)) {
+            return Err(foreign_key());
+        }
+
+        let stored = self.active_task_mut(&task.id).unwrap();
+        let version = stored.task.version + 1;
+        stored.task = Task {
+            version,
+            deleted_at: None,
+            created_at: stored.task.created_at,
+            ..bare_task(task)
+        };
+
+        // Обновляем теги: сначала удаляем все, потом добавляем заново
+        self.task_tags.retain(|(t, _)| *t != task.id);
+        for tag in &task.tags {
+            if !self.task_tags.iter().any(|(t, g)| *t == task.id && *g == tag.id) {
+                self.task_tags.push((task.id.clone(), tag.id.clone()));
+            }
+        }
+
+        Ok(())
+    }
+
+    // Меняет исполнителя и записывает передачу в цепочку делегирования
+    fn write_task_reassign(&mut self, task: &Task, delegation: &Delegation) -> Result<()> {
+        let current = self.active_task(&task.id).ok_or_else(|| RepositoryError::not_found(Entity::Task, &task.id))?;
+        check_version(&task.id, task.version, current.task.version)?;
+        if self.delegations.iter().any(|d| d.id == delegation.id) {
+            return Err(unique("task_delegations.id"));
+        }
+        let users = [&delegation.from_user_id, &delegation.to_user_id, &delegation.by_user_id];
+        if users.iter().any(|id| !self.user_exists(id)) {
+            return Err(foreign_key());
+        }
+
+        let stored = self.active_task_mut(&task.id).unwrap();
+        stored.task.executor_id = delegation.to_user_id.clone();
+        stored.task.version += 1;
+
+        self.delegations.push(delegation.clone());
+        // Новый исполнитель перестает быть соисполнителем
+        self.participants.retain(|(t, u, role)| {
+            !(*t == task.id && *u == delegation.to_user_id && *role == ParticipantRole::CoExecutor)
+        });
+
+        Ok(())
+    }
+
+    fn write_task_change(&mut self, change: &TaskChange, now: DateTime<Utc>) -> Result<()> {
+        match change {
+            TaskChange::Update(task) => self.write_task_update(task),
+            TaskChange::Reassign(task, delegation) => self.write_task_reassign(task, delegation),
+            TaskChange::Delete(task) => {
+                let current = self.active_task(&task.id).ok_or_else(|| RepositoryError::not_found(Entity::Task, &task.id))?;
+                check_version(&task.id, task.version, current.task.version)?;
+
+                let stored = self.active_task_mut(&task.id).unwrap();
+                stored.task.deleted_at = Some(now);
+                stored.task.version += 1;
+                Ok(())
+            }
+        }
+    }
+}
+
+// Строка задачи без данных, которые хранятся в связях
+fn bare_task(task: &Task) -> Task {
+    Task {
+        tags: Vec::new(),
+        co_executor_ids: Vec::new(),
+        watcher_ids: Vec::new(),
+        spent_minutes: 0,
+        archived_at: None,
+        ..task.clone()
+    }
+}
+
+fn check_version(id: &str, expected: i64, current: i64) -> Result<()> {
+    if expected != current {
+        return Err(RepositoryError::VersionConflict { id: id.to_string(), expected, current });
+    }
+    Ok(())
+}
+
+fn unique(column: &str) -> RepositoryError {
+    RepositoryError::Constraint(format!("UNIQUE constraint failed: {}", column))
+}
+
+fn foreign_key() -> RepositoryError {
+    RepositoryError::Constraint("FOREIGN KEY constraint failed".to_string())
+}
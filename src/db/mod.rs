@@ -1,18 +1,42 @@
 pub mod sqlite;
+pub mod memory;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+#[cfg(test)]
+mod conformance;
 
 pub use sqlite::SqliteRepository;
 pub use sqlite::DbPool;
+pub use memory::InMemoryRepository;
+#[cfg(feature = "postgres")]
+pub use self::postgres::PostgresRepository;
 
-use crate::models::{Task, User, Tag};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::models::{Delegation, ParticipantRole, Project, SavedView, Task, TimeEntry, User, Tag};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum RepositoryError {
     #[error("database error: {0}")]
-    Database(#[from] rusqlite::Error),
+    Database(rusqlite::Error),
+    
+    #[cfg(feature = "postgres")]
+    #[error("database error: {0}")]
+    Postgres(::postgres::Error),
+    
+    // Нарушено ограничение схемы (UNIQUE, FOREIGN KEY, CHECK); текст в формате сообщений
+    // SQLite, например "UNIQUE constraint failed: users.email"
+    #[error("constraint violation: {0}")]
+    Constraint(String),
     
-    #[error("entity not found: {0}")]
-    NotFound(String),
+    #[error("{entity} with id {id} not found")]
+    NotFound { entity: Entity, id: String },
+    
+    #[error("version conflict for {id}: expected {expected}, current {current}")]
+    VersionConflict { id: String, expected: i64, current: i64 },
     
     #[error("internal error: {0}")]
     Internal(String),
@@ -20,25 +44,229 @@ pub enum RepositoryError {
 
 pub type Result<T> = std::result::Result<T, RepositoryError>;
 
+impl RepositoryError {
+    pub fn not_found(entity: Entity, id: &str) -> Self {
+        RepositoryError::NotFound { entity, id: id.to_string() }
+    }
+}
+
+// Вид ненайденной записи; сообщение для клиента берется из каталога
+// по ключу errors.missing.<code> с подстановкой идентификатора
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Entity {
+    User,
+    DeletedUser,
+    Task,
+    DeletedTask,
+    ArchivedTask,
+    Participant, // Пользователь не участвует в задаче в указанной роли
+    TaskTag,     // Тег не назначен задаче
+    Tag,
+    Project,
+    SavedView,
+    TimeEntry,
+    RunningTimeEntry,
+}
+
+impl Entity {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Entity::User => "user",
+            Entity::DeletedUser => "deleted_user",
+            Entity::Task => "task",
+            Entity::DeletedTask => "deleted_task",
+            Entity::ArchivedTask => "archived_task",
+            Entity::Participant => "participant",
+            Entity::TaskTag => "task_tag",
+            Entity::Tag => "tag",
+            Entity::Project => "project",
+            Entity::SavedView => "saved_view",
+            Entity::TimeEntry => "time_entry",
+            Entity::RunningTimeEntry => "running_time_entry",
+        }
+    }
+}
+
+impl std::fmt::Display for Entity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+impl From<rusqlite::Error> for RepositoryError {
+    fn from(err: rusqlite::Error) -> Self {
+        match err {
+            rusqlite::Error::SqliteFailure(failure, message)
+                if failure.code == rusqlite::ErrorCode::ConstraintViolation =>
+            {
+                RepositoryError::Constraint(message.unwrap_or_else(|| failure.to_string()))
+            }
+            err => RepositoryError::Database(err),
+        }
+    }
+}
+
+// Открывает хранилище по DATABASE_URL: адреса postgres:// и postgresql:// -
+// PostgreSQL (сборка с feature "postgres"), memory: - хранилище в памяти,
+// остальное - путь к файлу SQLite
+pub fn connect(database_url: &str) -> Result<Arc<dyn Repository>> {
+    // Данные в памяти теряются при перезапуске: режим для демонстрации
+    if database_url == "memory:" {
+        return Ok(Arc::new(InMemoryRepository::new()));
+    }
+
+    if database_url.starts_with("postgres://") || database_url.starts_with("postgresql://") {
+        #[cfg(feature = "postgres")]
+        return Ok(Arc::new(PostgresRepository::connect(database_url)?));
+        #[cfg(not(feature = "postgres"))]
+        return Err(RepositoryError::Internal(
+            "PostgreSQL support is not enabled, build with --features postgres".to_string(),
+        ));
+    }
+
+    let pool = r2d2::Pool::new(SqliteRepository::connection_manager(database_url))
+        .map_err(|e| RepositoryError::Internal(e.to_string()))?;
+    {
+        let conn = pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        SqliteRepository::init_db(&conn)?;
+    }
+    Ok(Arc::new(SqliteRepository::new(Arc::new(pool))))
+}
+
+// Условия поиска по архиву; пустые поля не ограничивают выборку
+#[derive(Debug, Default, Clone)]
+pub struct ArchiveFilter {
+    pub search: Option<String>,          // Подстрока названия или описания
+    pub status: Option<String>,          // Код статуса
+    pub customer_id: Option<String>,
+    pub executor_id: Option<String>,
+    pub tag: Option<String>,             // Имя тега
+    pub finished_from: Option<DateTime<Utc>>,
+    pub finished_to: Option<DateTime<Utc>>,
+}
+
+// Условия выборки записей учета времени; пустые поля не ограничивают выборку
+#[derive(Debug, Default, Clone)]
+pub struct TimeEntryFilter {
+    pub task_id: Option<String>,
+    pub user_id: Option<String>,
+    pub started_from: Option<DateTime<Utc>>,
+    pub started_to: Option<DateTime<Utc>>,
+}
+
+// Изменение задачи в составе группового изменения; версия задачи - та,
+// которую видел клиент
+#[derive(Debug, Clone)]
+pub enum TaskChange {
+    Update(Task),               // Как update_task
+    Reassign(Task, Delegation), // Как reassign_task
+    Delete(Task),               // Как delete_task, с проверкой версии
+}
+
+// now в изменяющих методах - момент операции по часам приложения; он записывается
+// в deleted_at, deactivated_at, archived_at и unarchived_at
 pub trait Repository: Send + Sync + 'static {
     // User methods
     fn get_users(&self) -> Result<Vec<User>>;
     fn get_user_by_id(&self, id: &str) -> Result<User>;
     fn create_user(&self, user: &User) -> Result<()>;
     fn update_user(&self, user: &User) -> Result<()>;
-    fn delete_user(&self, id: &str) -> Result<()>;
+    fn delete_user(&self, id: &str, now: DateTime<Utc>) -> Result<()>;
+    // Удаление пользователя с передачей его задач: изменения задач и перемещение
+    // пользователя в корзину выполняются атомарно
+    fn delete_user_with_handover(&self, id: &str, changes: &[TaskChange], now: DateTime<Utc>) -> Result<()>;
+    // Пользователь остается в истории задач, но больше не назначается
+    fn deactivate_user(&self, id: &str, now: DateTime<Utc>) -> Result<()>;
+    fn reactivate_user(&self, id: &str) -> Result<()>;
     
     // Task methods
     fn get_tasks(&self) -> Result<Vec<Task>>;
     fn get_task_by_id(&self, id: &str) -> Result<Task>;
     fn create_task(&self, task: &Task) -> Result<()>;
     fn update_task(&self, task: &Task) -> Result<()>;
-    fn delete_task(&self, id: &str) -> Result<()>;
+    fn delete_task(&self, id: &str, now: DateTime<Utc>) -> Result<()>;
+    // Соисполнители и наблюдатели; у пользователя в задаче одна такая роль,
+    // повторное добавление с другой ролью заменяет прежнюю
+    fn add_task_participant(&self, task_id: &str, user_id: &str, role: ParticipantRole) -> Result<()>;
+    fn remove_task_participant(&self, task_id: &str, user_id: &str, role: ParticipantRole) -> Result<()>;
+    // Переносит задачу в колонку task.status перед задачей before_id (None или
+    // задача из другой колонки - в конец) и перенумеровывает колонку.
+    // task.version - версия, которую видел клиент. Возвращает новый rank задачи
+    fn move_task(&self, task: &Task, before_id: Option<&str>) -> Result<i64>;
+    // Передает задачу исполнителю delegation.to_user_id и сохраняет запись о передаче.
+    // task.version - версия, которую видел клиент
+    fn reassign_task(&self, task: &Task, delegation: &Delegation) -> Result<()>;
+    // Применяет изменения атомарно: при ошибке любого из них не применяется ни одно
+    fn apply_task_changes(&self, changes: &[TaskChange], now: DateTime<Utc>) -> Result<()>;
+    // Цепочка передач задачи, от первой к последней
+    fn get_task_delegations(&self, task_id: &str) -> Result<Vec<Delegation>>;
+    fn get_delegations(&self) -> Result<Vec<Delegation>>;
+    
+    // Board methods
+    // Ограничения числа задач в колонках доски: код статуса -> лимит
+    fn get_wip_limits(&self) -> Result<HashMap<String, u32>>;
+    // None снимает ограничение
+    fn set_wip_limit(&self, status: &str, limit: Option<u32>) -> Result<()>;
+    
+    // Time tracking methods
+    fn create_time_entry(&self, entry: &TimeEntry) -> Result<()>;
+    // Останавливает запущенный таймер: сохраняет ended_at и minutes
+    fn stop_time_entry(&self, entry: &TimeEntry) -> Result<()>;
+    fn delete_time_entry(&self, id: &str) -> Result<()>;
+    // Запущенный таймер пользователя; у пользователя он не больше одного
+    fn get_running_time_entry(&self, user_id: &str) -> Result<Option<TimeEntry>>;
+    fn get_time_entries(&self, filter: &TimeEntryFilter) -> Result<Vec<TimeEntry>>;
+    
+    // Project methods
+    fn get_projects(&self) -> Result<Vec<Project>>;
+    fn get_project_by_id(&self, id: &str) -> Result<Project>;
+    fn create_project(&self, project: &Project) -> Result<()>;
+    fn update_project(&self, project: &Project) -> Result<()>;
+    // Удаляет проект; его задачи, включая архивные и удаленные, остаются без проекта
+    fn delete_project(&self, id: &str) -> Result<()>;
+    // Задачи основного списка, относящиеся к проекту
+    fn get_tasks_by_project(&self, project_id: &str) -> Result<Vec<Task>>;
+    
+    // Saved view methods
+    // Виды, доступные пользователю: его собственные и общие виды коллег
+    fn get_saved_views(&self, user_id: &str) -> Result<Vec<SavedView>>;
+    fn get_saved_view_by_id(&self, id: &str) -> Result<SavedView>;
+    fn create_saved_view(&self, view: &SavedView) -> Result<()>;
+    // Если вид перестал быть общим, он перестает быть видом по умолчанию у других пользователей
+    fn update_saved_view(&self, view: &SavedView) -> Result<()>;
+    fn delete_saved_view(&self, id: &str) -> Result<()>;
+    fn get_default_view_id(&self, user_id: &str) -> Result<Option<String>>;
+    // None сбрасывает вид по умолчанию
+    fn set_default_view(&self, user_id: &str, view_id: Option<&str>) -> Result<()>;
+    
+    // Trash methods: delete_user и delete_task только помечают запись удаленной
+    fn get_deleted_tasks(&self) -> Result<Vec<Task>>;
+    fn get_deleted_users(&self) -> Result<Vec<User>>;
+    fn restore_task(&self, id: &str) -> Result<()>;
+    fn restore_user(&self, id: &str) -> Result<()>;
+    // Окончательно удаляет записи, перемещенные в корзину раньше указанного момента;
+    // возвращает число удаленных записей
+    fn purge_deleted(&self, deleted_before: DateTime<Utc>) -> Result<usize>;
+    
+    // Archive methods: завершенные и отмененные задачи переносятся в архивные таблицы
+    // и не возвращаются get_tasks. Возвращает число перенесенных задач
+    fn archive_tasks(&self, finished_before: DateTime<Utc>, now: DateTime<Utc>) -> Result<usize>;
+    fn get_archived_tasks(&self, filter: &ArchiveFilter) -> Result<Vec<Task>>;
+    fn unarchive_task(&self, id: &str, now: DateTime<Utc>) -> Result<()>;
     
     // Tag methods
     fn get_tags(&self) -> Result<Vec<Tag>>;
     fn get_tag_by_id(&self, id: &str) -> Result<Tag>;
     fn create_tag(&self, tag: &Tag) -> Result<()>;
+    // Теги по именам без учета регистра, пробелов и ё/е; недостающие создаются.
+    // Повторы в names дают один тег
+    fn get_or_create_tags_by_names(&self, names: &[String]) -> Result<Vec<Tag>>;
+    fn update_tag(&self, tag: &Tag) -> Result<()>;
+    fn delete_tag(&self, id: &str) -> Result<()>;
+    // Переносит связи с задачами на целевой тег и удаляет исходный
+    fn merge_tags(&self, source_id: &str, target_id: &str) -> Result<()>;
+    // Число задач основного списка по идентификатору тега
+    fn get_tag_usage_counts(&self) -> Result<HashMap<String, usize>>;
     fn get_tags_for_task(&self, task_id: &str) -> Result<Vec<Tag>>;
     fn add_tag_to_task(&self, task_id: &str, tag_id: &str) -> Result<()>;
     fn remove_tag_from_task(&self, task_id: &str, tag_id: &str) -> Result<()>;
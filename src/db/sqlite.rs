@@ -1,12 +1,15 @@
-use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use chrono::{DateTime, Utc};
 use r2d2::Pool;
 use r2d2_sqlite::SqliteConnectionManager;
-use rusqlite::{params, Connection, Row};
+use rusqlite::{params, params_from_iter, Connection, Row};
 use serde_json;
+use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::models::{Task, TaskPriority, TaskStatus, Tag, User, UserRole};
-use super::{Repository, RepositoryError, Result};
+use crate::i18n::Locale;
+use crate::models::tag::{clean_tag_name, normalize_tag_name};
+use crate::models::{Delegation, ParticipantRole, Project, ProjectStatus, SavedView, Task, TaskPriority, TaskStatus, Tag, TimeEntry, User, UserRole};
+use super::{ArchiveFilter, Entity, Repository, RepositoryError, Result, TaskChange, TimeEntryFilter};
 
 pub type DbPool = Pool<SqliteConnectionManager>;
 
@@ -19,21 +22,64 @@ impl SqliteRepository {
         Self { pool }
     }
 
+    // Менеджер соединений пула. PRAGMA foreign_keys действует только на соединение,
+    // в котором выполнена, поэтому включается при открытии каждого соединения
+    pub fn connection_manager(path: &str) -> SqliteConnectionManager {
+        SqliteConnectionManager::file(path).with_init(|conn| conn.execute_batch("PRAGMA foreign_keys = ON;"))
+    }
+
     pub fn init_db(conn: &Connection) -> rusqlite::Result<()> {
         conn.execute(
             "CREATE TABLE IF NOT EXISTS users (
                 id TEXT PRIMARY KEY,
                 name TEXT NOT NULL,
                 email TEXT NOT NULL UNIQUE,
-                role TEXT NOT NULL
+                role TEXT NOT NULL,
+                time_zone TEXT NOT NULL DEFAULT 'Europe/Moscow',
+                version INTEGER NOT NULL DEFAULT 1,
+                deleted_at TEXT,
+                active INTEGER NOT NULL DEFAULT 1,
+                deactivated_at TEXT,
+                locale TEXT
             )",
             [],
         )?;
 
+        // Колонки, добавленные после первой версии схемы
+        ensure_column(conn, "users", "time_zone", "TEXT NOT NULL DEFAULT 'Europe/Moscow'")?;
+        ensure_column(conn, "users", "version", "INTEGER NOT NULL DEFAULT 1")?;
+        ensure_column(conn, "users", "deleted_at", "TEXT")?;
+        ensure_column(conn, "users", "active", "INTEGER NOT NULL DEFAULT 1")?;
+        ensure_column(conn, "users", "deactivated_at", "TEXT")?;
+        ensure_column(conn, "users", "locale", "TEXT")?;
+
         conn.execute(
             "CREATE TABLE IF NOT EXISTS tags (
                 id TEXT PRIMARY KEY,
-                name TEXT NOT NULL UNIQUE
+                name TEXT NOT NULL UNIQUE,
+                name_key TEXT,
+                color TEXT,
+                description TEXT
+            )",
+            [],
+        )?;
+
+        ensure_column(conn, "tags", "color", "TEXT")?;
+        ensure_column(conn, "tags", "description", "TEXT")?;
+        ensure_column(conn, "tags", "name_key", "TEXT")?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS projects (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                description TEXT NOT NULL,
+                owner_id TEXT NOT NULL,
+                customer_id TEXT,
+                status TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                version INTEGER NOT NULL DEFAULT 1,
+                FOREIGN KEY (owner_id) REFERENCES users (id),
+                FOREIGN KEY (customer_id) REFERENCES users (id)
             )",
             [],
         )?;
@@ -50,12 +96,36 @@ impl SqliteRepository {
                 created_at TEXT NOT NULL,
                 due_date TEXT,
                 completed_at TEXT,
+                version INTEGER NOT NULL DEFAULT 1,
+                deleted_at TEXT,
+                unarchived_at TEXT,
+                project_id TEXT,
+                estimate_minutes INTEGER,
+                rank INTEGER NOT NULL DEFAULT 0,
+                cancelled_at TEXT,
                 FOREIGN KEY (customer_id) REFERENCES users (id),
-                FOREIGN KEY (executor_id) REFERENCES users (id)
+                FOREIGN KEY (executor_id) REFERENCES users (id),
+                FOREIGN KEY (project_id) REFERENCES projects (id)
             )",
             [],
         )?;
 
+        ensure_column(conn, "tasks", "version", "INTEGER NOT NULL DEFAULT 1")?;
+        ensure_column(conn, "tasks", "deleted_at", "TEXT")?;
+        // Время возврата из архива; отсчет срока архивации начинается заново
+        ensure_column(conn, "tasks", "unarchived_at", "TEXT")?;
+        ensure_column(conn, "tasks", "project_id", "TEXT")?;
+        ensure_column(conn, "tasks", "estimate_minutes", "INTEGER")?;
+        ensure_column(conn, "tasks", "rank", "INTEGER NOT NULL DEFAULT 0")?;
+        ensure_column(conn, "tasks", "cancelled_at", "TEXT")?;
+        // Время отмены ранее отмененных задач неизвестно: срок архивации
+        // для них отсчитывается с момента обновления схемы
+        conn.execute(
+            "UPDATE tasks SET cancelled_at = strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now')
+             WHERE status = 'cancelled' AND cancelled_at IS NULL",
+            [],
+        )?;
+
         conn.execute(
             "CREATE TABLE IF NOT EXISTS task_tags (
                 task_id TEXT NOT NULL,
@@ -67,6 +137,154 @@ impl SqliteRepository {
             [],
         )?;
 
+        // Соисполнители и наблюдатели. Ссылка на задачу не ограничена внешним
+        // ключом: участники сохраняются и при переносе задачи в архив
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS task_participants (
+                task_id TEXT NOT NULL,
+                user_id TEXT NOT NULL,
+                role TEXT NOT NULL,
+                PRIMARY KEY (task_id, user_id),
+                FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+
+        // Цепочка передач задачи между исполнителями; как и участники,
+        // сохраняется при переносе задачи в архив
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS task_delegations (
+                id TEXT PRIMARY KEY,
+                task_id TEXT NOT NULL,
+                from_user_id TEXT NOT NULL,
+                to_user_id TEXT NOT NULL,
+                by_user_id TEXT NOT NULL,
+                reason TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (from_user_id) REFERENCES users (id),
+                FOREIGN KEY (to_user_id) REFERENCES users (id),
+                FOREIGN KEY (by_user_id) REFERENCES users (id)
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_task_delegations_task ON task_delegations (task_id)",
+            [],
+        )?;
+
+        // Ограничения числа задач в колонках доски (WIP-лимиты)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS wip_limits (
+                status TEXT PRIMARY KEY,
+                wip_limit INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        // Сохраненные виды списка задач; условия фильтрации и колонки хранятся в JSON
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS saved_views (
+                id TEXT PRIMARY KEY,
+                owner_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                filters TEXT NOT NULL,
+                sort TEXT,
+                columns TEXT NOT NULL,
+                shared INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (owner_id) REFERENCES users (id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS default_views (
+                user_id TEXT PRIMARY KEY,
+                view_id TEXT NOT NULL,
+                FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE,
+                FOREIGN KEY (view_id) REFERENCES saved_views (id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+
+        // Учет времени по задачам. Запись без ended_at - запущенный таймер;
+        // записи остаются у архивных задач
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS time_entries (
+                id TEXT PRIMARY KEY,
+                task_id TEXT NOT NULL,
+                user_id TEXT NOT NULL,
+                started_at TEXT NOT NULL,
+                ended_at TEXT,
+                minutes INTEGER NOT NULL DEFAULT 0,
+                note TEXT NOT NULL DEFAULT '',
+                FOREIGN KEY (user_id) REFERENCES users (id)
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_time_entries_task ON time_entries (task_id)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_time_entries_user ON time_entries (user_id, started_at)",
+            [],
+        )?;
+        // У пользователя не больше одного запущенного таймера, даже при
+        // одновременных запросах на запуск
+        conn.execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_time_entries_running ON time_entries (user_id)
+             WHERE ended_at IS NULL",
+            [],
+        )?;
+
+        // Архив завершенных задач: та же структура, что у tasks и task_tags
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS archived_tasks (
+                id TEXT PRIMARY KEY,
+                title TEXT NOT NULL,
+                description TEXT NOT NULL,
+                status TEXT NOT NULL,
+                priority TEXT NOT NULL,
+                customer_id TEXT NOT NULL,
+                executor_id TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                due_date TEXT,
+                completed_at TEXT,
+                version INTEGER NOT NULL DEFAULT 1,
+                deleted_at TEXT,
+                project_id TEXT,
+                estimate_minutes INTEGER,
+                rank INTEGER NOT NULL DEFAULT 0,
+                cancelled_at TEXT,
+                archived_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        ensure_column(conn, "archived_tasks", "project_id", "TEXT")?;
+        ensure_column(conn, "archived_tasks", "estimate_minutes", "INTEGER")?;
+        ensure_column(conn, "archived_tasks", "rank", "INTEGER NOT NULL DEFAULT 0")?;
+        ensure_column(conn, "archived_tasks", "cancelled_at", "TEXT")?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS archived_task_tags (
+                task_id TEXT NOT NULL,
+                tag_id TEXT NOT NULL,
+                PRIMARY KEY (task_id, tag_id),
+                FOREIGN KEY (task_id) REFERENCES archived_tasks (id),
+                FOREIGN KEY (tag_id) REFERENCES tags (id)
+            )",
+            [],
+        )?;
+
+        // Ключи имен тегов вычисляются в приложении: lower() в SQLite
+        // не работает с кириллицей
+        merge_duplicate_tags(conn)?;
+        conn.execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_tags_name_key ON tags (name_key)",
+            [],
+        )?;
+
         Ok(())
     }
 }
@@ -76,15 +294,8 @@ impl Repository for SqliteRepository {
     fn get_users(&self) -> Result<Vec<User>> {
         let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
         
-        let mut stmt = conn.prepare("SELECT id, name, email, role FROM users")?;
-        let rows = stmt.query_map([], |row| {
-            Ok(User {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                email: row.get(2)?,
-                role: UserRole::from(row.get::<_, String>(3)?.as_str()),
-            })
-        })?;
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM users WHERE deleted_at IS NULL", USER_COLUMNS))?;
+        let rows = stmt.query_map([], user_from_row)?;
 
         let mut users = Vec::new();
         for row in rows {
@@ -97,15 +308,8 @@ impl Repository for SqliteRepository {
     fn get_user_by_id(&self, id: &str) -> Result<User> {
         let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
         
-        let mut stmt = conn.prepare("SELECT id, name, email, role FROM users WHERE id = ?")?;
-        let user = stmt.query_row(params![id], |row| {
-            Ok(User {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                email: row.get(2)?,
-                role: UserRole::from(row.get::<_, String>(3)?.as_str()),
-            })
-        }).map_err(|_| RepositoryError::NotFound(format!("User with id {} not found", id)))?;
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM users WHERE id = ? AND deleted_at IS NULL", USER_COLUMNS))?;
+        let user = stmt.query_row(params![id], user_from_row).map_err(|_| RepositoryError::not_found(Entity::User, id))?;
 
         Ok(user)
     }
@@ -114,12 +318,15 @@ impl Repository for SqliteRepository {
         let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
         
         conn.execute(
-            "INSERT INTO users (id, name, email, role) VALUES (?, ?, ?, ?)",
+            "INSERT INTO users (id, name, email, role, time_zone, version, locale) VALUES (?, ?, ?, ?, ?, ?, ?)",
             params![
                 user.id,
                 user.name,
                 user.email,
-                format!("{:?}", user.role).to_lowercase(),
+                user.role.code(),
+                user.time_zone,
+                user.version,
+                user.locale.map(|locale| locale.code()),
             ],
         )?;
 
@@ -130,29 +337,93 @@ impl Repository for SqliteRepository {
         let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
         
         let rows_affected = conn.execute(
-            "UPDATE users SET name = ?, email = ?, role = ? WHERE id = ?",
+            "UPDATE users SET name = ?, email = ?, role = ?, time_zone = ?, locale = ?, version = version + 1
+             WHERE id = ? AND version = ? AND deleted_at IS NULL",
             params![
                 user.name,
                 user.email,
-                format!("{:?}", user.role).to_lowercase(),
+                user.role.code(),
+                user.time_zone,
+                user.locale.map(|locale| locale.code()),
                 user.id,
+                user.version,
             ],
         )?;
 
         if rows_affected == 0 {
-            return Err(RepositoryError::NotFound(format!("User with id {} not found", user.id)));
+            // Либо пользователя нет, либо его уже изменили
+            return Err(version_conflict_or_not_found(&conn, "users", Entity::User, &user.id, user.version));
         }
 
         Ok(())
     }
 
-    fn delete_user(&self, id: &str) -> Result<()> {
+    // Пользователь перемещается в корзину; окончательно удаляется в purge_deleted
+    fn delete_user(&self, id: &str, now: DateTime<Utc>) -> Result<()> {
         let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
         
-        let rows_affected = conn.execute("DELETE FROM users WHERE id = ?", params![id])?;
+        let rows_affected = conn.execute(
+            "UPDATE users SET deleted_at = ?, version = version + 1 WHERE id = ? AND deleted_at IS NULL",
+            params![now.to_rfc3339(), id],
+        )?;
+
+        if rows_affected == 0 {
+            return Err(RepositoryError::not_found(Entity::User, id));
+        }
+
+        Ok(())
+    }
+
+    // Задачи передаются и пользователь перемещается в корзину в одной транзакции
+    fn delete_user_with_handover(&self, id: &str, changes: &[TaskChange], now: DateTime<Utc>) -> Result<()> {
+        let mut conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        let tx = conn.transaction()?;
+
+        for change in changes {
+            write_task_change(&tx, change, now)?;
+        }
+
+        let rows_affected = tx.execute(
+            "UPDATE users SET deleted_at = ?, version = version + 1 WHERE id = ? AND deleted_at IS NULL",
+            params![now.to_rfc3339(), id],
+        )?;
+        if rows_affected == 0 {
+            return Err(RepositoryError::not_found(Entity::User, id));
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    // Повторная деактивация сохраняет время первой
+    fn deactivate_user(&self, id: &str, now: DateTime<Utc>) -> Result<()> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+
+        let rows_affected = conn.execute(
+            "UPDATE users SET deactivated_at = CASE WHEN active = 1 THEN ? ELSE deactivated_at END,
+                active = 0, version = version + 1
+             WHERE id = ? AND deleted_at IS NULL",
+            params![now.to_rfc3339(), id],
+        )?;
+
+        if rows_affected == 0 {
+            return Err(RepositoryError::not_found(Entity::User, id));
+        }
+
+        Ok(())
+    }
+
+    fn reactivate_user(&self, id: &str) -> Result<()> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+
+        let rows_affected = conn.execute(
+            "UPDATE users SET active = 1, deactivated_at = NULL, version = version + 1
+             WHERE id = ? AND deleted_at IS NULL",
+            params![id],
+        )?;
 
         if rows_affected == 0 {
-            return Err(RepositoryError::NotFound(format!("User with id {} not found", id)));
+            return Err(RepositoryError::not_found(Entity::User, id));
         }
 
         Ok(())
@@ -162,35 +433,16 @@ impl Repository for SqliteRepository {
     fn get_tasks(&self) -> Result<Vec<Task>> {
         let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
         
-        let mut stmt = conn.prepare(
-            "SELECT id, title, description, status, priority, customer_id, executor_id, 
-                    created_at, due_date, completed_at 
-             FROM tasks"
-        )?;
-        
-        let rows = stmt.query_map([], |row| {
-            let id: String = row.get(0)?;
-            let task = Task {
-                id: id.clone(),
-                title: row.get(1)?,
-                description: row.get(2)?,
-                status: TaskStatus::from(row.get::<_, String>(3)?.as_str()),
-                priority: TaskPriority::from(row.get::<_, String>(4)?.as_str()),
-                customer_id: row.get(5)?,
-                executor_id: row.get(6)?,
-                created_at: parse_datetime(row.get::<_, String>(7)?)?,
-                due_date: row.get::<_, Option<String>>(8)?.map(parse_datetime).transpose()?,
-                completed_at: row.get::<_, Option<String>>(9)?.map(parse_datetime).transpose()?,
-                tags: Vec::new(), // Заполним позже
-            };
-            Ok(task)
-        })?;
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM tasks WHERE deleted_at IS NULL", TASK_COLUMNS))?;
+        let rows = stmt.query_map([], task_from_row)?;
 
         let mut tasks = Vec::new();
         for row in rows {
             let mut task = row?;
-            // Получаем теги для задачи
+            // Получаем теги, участников и учтенное время задачи
             task.tags = self.get_tags_for_task(&task.id)?;
+            load_participants(&conn, &mut task)?;
+            load_time_spent(&conn, &mut task)?;
             tasks.push(task);
         }
 
@@ -200,34 +452,16 @@ impl Repository for SqliteRepository {
     fn get_task_by_id(&self, id: &str) -> Result<Task> {
         let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
         
-        let mut stmt = conn.prepare(
-            "SELECT id, title, description, status, priority, customer_id, executor_id, 
-                    created_at, due_date, completed_at 
-             FROM tasks WHERE id = ?"
-        )?;
-        
-        let task = stmt.query_row(params![id], |row| {
-            let task_id: String = row.get(0)?;
-            let task = Task {
-                id: task_id.clone(),
-                title: row.get(1)?,
-                description: row.get(2)?,
-                status: TaskStatus::from(row.get::<_, String>(3)?.as_str()),
-                priority: TaskPriority::from(row.get::<_, String>(4)?.as_str()),
-                customer_id: row.get(5)?,
-                executor_id: row.get(6)?,
-                created_at: parse_datetime(row.get::<_, String>(7)?)?,
-                due_date: row.get::<_, Option<String>>(8)?.map(parse_datetime).transpose()?,
-                completed_at: row.get::<_, Option<String>>(9)?.map(parse_datetime).transpose()?,
-                tags: Vec::new(), // Заполним позже
-            };
-            Ok(task)
-        }).map_err(|_| RepositoryError::NotFound(format!("Task with id {} not found", id)))?;
-
-        // Получаем теги для задачи
-        let tags = self.get_tags_for_task(id)?;
-        
-        Ok(Task { tags, ..task })
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM tasks WHERE id = ? AND deleted_at IS NULL", TASK_COLUMNS))?;
+        let mut task = stmt.query_row(params![id], task_from_row)
+            .map_err(|_| RepositoryError::not_found(Entity::Task, id))?;
+
+        // Получаем теги, участников и учтенное время задачи
+        task.tags = self.get_tags_for_task(id)?;
+        load_participants(&conn, &mut task)?;
+        load_time_spent(&conn, &mut task)?;
+        
+        Ok(task)
     }
 
     fn create_task(&self, task: &Task) -> Result<()> {
@@ -235,19 +469,25 @@ impl Repository for SqliteRepository {
         
         conn.execute(
             "INSERT INTO tasks (id, title, description, status, priority, customer_id, executor_id, 
-                              created_at, due_date, completed_at) 
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                              created_at, due_date, completed_at, version, project_id, estimate_minutes, rank,
+                              cancelled_at) 
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
             params![
                 task.id,
                 task.title,
                 task.description,
-                format!("{:?}", task.status).to_lowercase(),
-                format!("{:?}", task.priority).to_lowercase(),
+                task.status.code(),
+                task.priority.code(),
                 task.customer_id,
                 task.executor_id,
                 task.created_at.to_rfc3339(),
                 task.due_date.map(|d| d.to_rfc3339()),
                 task.completed_at.map(|d| d.to_rfc3339()),
+                task.version,
+                task.project_id,
+                task.estimate_minutes,
+                task.rank,
+                task.cancelled_at.map(|d| d.to_rfc3339()),
             ],
         )?;
 
@@ -260,172 +500,1387 @@ impl Repository for SqliteRepository {
     }
 
     fn update_task(&self, task: &Task) -> Result<()> {
+        let mut conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        let tx = conn.transaction()?;
+        write_task_update(&tx, task)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    // Задача перемещается в корзину вместе со связями с тегами;
+    // окончательно удаляется в purge_deleted
+    fn delete_task(&self, id: &str, now: DateTime<Utc>) -> Result<()> {
         let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
         
         let rows_affected = conn.execute(
-            "UPDATE tasks 
-             SET title = ?, description = ?, status = ?, priority = ?, 
-                 customer_id = ?, executor_id = ?, due_date = ?, completed_at = ? 
-             WHERE id = ?",
+            "UPDATE tasks SET deleted_at = ?, version = version + 1 WHERE id = ? AND deleted_at IS NULL",
+            params![now.to_rfc3339(), id],
+        )?;
+
+        if rows_affected == 0 {
+            return Err(RepositoryError::not_found(Entity::Task, id));
+        }
+
+        Ok(())
+    }
+
+    fn add_task_participant(&self, task_id: &str, user_id: &str, role: ParticipantRole) -> Result<()> {
+        let mut conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        let tx = conn.transaction()?;
+        
+        tx.execute(
+            "INSERT OR REPLACE INTO task_participants (task_id, user_id, role) VALUES (?, ?, ?)",
+            params![task_id, user_id, role.code()],
+        )?;
+        tx.execute("UPDATE tasks SET version = version + 1 WHERE id = ?", params![task_id])?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn remove_task_participant(&self, task_id: &str, user_id: &str, role: ParticipantRole) -> Result<()> {
+        let mut conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        let tx = conn.transaction()?;
+        
+        let rows_affected = tx.execute(
+            "DELETE FROM task_participants WHERE task_id = ? AND user_id = ? AND role = ?",
+            params![task_id, user_id, role.code()],
+        )?;
+
+        if rows_affected == 0 {
+            return Err(RepositoryError::not_found(Entity::Participant, user_id));
+        }
+        tx.execute("UPDATE tasks SET version = version + 1 WHERE id = ?", params![task_id])?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn move_task(&self, task: &Task, before_id: Option<&str>) -> Result<i64> {
+        let mut conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        let tx = conn.transaction()?;
+        
+        let rows_affected = tx.execute(
+            "UPDATE tasks SET status = ?, completed_at = ?, cancelled_at = ?, version = version + 1
+             WHERE id = ? AND version = ? AND deleted_at IS NULL",
             params![
-                task.title,
-                task.description,
-                format!("{:?}", task.status).to_lowercase(),
-                format!("{:?}", task.priority).to_lowercase(),
-                task.customer_id,
-                task.executor_id,
-                task.due_date.map(|d| d.to_rfc3339()),
+                task.status.code(),
                 task.completed_at.map(|d| d.to_rfc3339()),
+                task.cancelled_at.map(|d| d.to_rfc3339()),
                 task.id,
+                task.version,
             ],
         )?;
 
         if rows_affected == 0 {
-            return Err(RepositoryError::NotFound(format!("Task with id {} not found", task.id)));
+            return Err(version_conflict_or_not_found(&tx, "tasks", Entity::Task, &task.id, task.version));
+        }
+
+        // Колонка перенумеровывается целиком: ранги идут с 1 без пропусков
+        let mut ids = {
+            let mut stmt = tx.prepare(
+                "SELECT id FROM tasks WHERE status = ? AND id <> ? AND deleted_at IS NULL
+                 ORDER BY rank, created_at",
+            )?;
+            let rows = stmt.query_map(params![task.status.code(), task.id], |row| row.get::<_, String>(0))?;
+            rows.collect::<rusqlite::Result<Vec<String>>>()?
+        };
+        let position = before_id
+            .and_then(|before_id| ids.iter().position(|id| id == before_id))
+            .unwrap_or(ids.len());
+        ids.insert(position, task.id.clone());
+
+        for (index, id) in ids.iter().enumerate() {
+            tx.execute("UPDATE tasks SET rank = ? WHERE id = ?", params![index as i64 + 1, id])?;
         }
 
-        // Обновляем теги: сначала удаляем все, потом добавляем заново
-        conn.execute("DELETE FROM task_tags WHERE task_id = ?", params![task.id])?;
+        tx.commit()?;
+        Ok(position as i64 + 1)
+    }
+
+    fn reassign_task(&self, task: &Task, delegation: &Delegation) -> Result<()> {
+        let mut conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        let tx = conn.transaction()?;
+        write_task_reassign(&tx, task, delegation)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn apply_task_changes(&self, changes: &[TaskChange], now: DateTime<Utc>) -> Result<()> {
+        let mut conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        let tx = conn.transaction()?;
         
-        for tag in &task.tags {
-            self.add_tag_to_task(&task.id, &tag.id)?;
+        for change in changes {
+            write_task_change(&tx, change, now)?;
         }
 
+        tx.commit()?;
         Ok(())
     }
 
-    fn delete_task(&self, id: &str) -> Result<()> {
+    fn get_task_delegations(&self, task_id: &str) -> Result<Vec<Delegation>> {
         let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
         
-        // Сначала удаляем связи с тегами
-        conn.execute("DELETE FROM task_tags WHERE task_id = ?", params![id])?;
-        
-        // Затем удаляем саму задачу
-        let rows_affected = conn.execute("DELETE FROM tasks WHERE id = ?", params![id])?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM task_delegations WHERE task_id = ? ORDER BY created_at, rowid",
+            DELEGATION_COLUMNS
+        ))?;
+        let rows = stmt.query_map(params![task_id], delegation_from_row)?;
 
-        if rows_affected == 0 {
-            return Err(RepositoryError::NotFound(format!("Task with id {} not found", id)));
+        let mut delegations = Vec::new();
+        for row in rows {
+            delegations.push(row?);
         }
 
-        Ok(())
+        Ok(delegations)
     }
-    
-    // Tag methods
-    fn get_tags(&self) -> Result<Vec<Tag>> {
+
+    fn get_delegations(&self) -> Result<Vec<Delegation>> {
         let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
         
-        let mut stmt = conn.prepare("SELECT id, name FROM tags")?;
-        let rows = stmt.query_map([], |row| {
-            Ok(Tag {
-                id: row.get(0)?,
-                name: row.get(1)?,
-            })
-        })?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM task_delegations ORDER BY created_at, rowid",
+            DELEGATION_COLUMNS
+        ))?;
+        let rows = stmt.query_map([], delegation_from_row)?;
 
-        let mut tags = Vec::new();
+        let mut delegations = Vec::new();
         for row in rows {
-            tags.push(row?);
+            delegations.push(row?);
         }
 
-        Ok(tags)
+        Ok(delegations)
     }
 
-    fn get_tag_by_id(&self, id: &str) -> Result<Tag> {
+    // Board methods
+    fn get_wip_limits(&self) -> Result<HashMap<String, u32>> {
         let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
         
-        let mut stmt = conn.prepare("SELECT id, name FROM tags WHERE id = ?")?;
-        let tag = stmt.query_row(params![id], |row| {
-            Ok(Tag {
-                id: row.get(0)?,
-                name: row.get(1)?,
-            })
-        }).map_err(|_| RepositoryError::NotFound(format!("Tag with id {} not found", id)))?;
+        let mut stmt = conn.prepare("SELECT status, wip_limit FROM wip_limits")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?)))?;
 
-        Ok(tag)
+        let mut limits = HashMap::new();
+        for row in rows {
+            let (status, limit) = row?;
+            limits.insert(status, limit);
+        }
+
+        Ok(limits)
     }
 
-    fn create_tag(&self, tag: &Tag) -> Result<()> {
+    fn set_wip_limit(&self, status: &str, limit: Option<u32>) -> Result<()> {
         let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
         
-        conn.execute(
-            "INSERT INTO tags (id, name) VALUES (?, ?)",
-            params![tag.id, tag.name],
-        )?;
+        match limit {
+            Some(limit) => conn.execute(
+                "INSERT OR REPLACE INTO wip_limits (status, wip_limit) VALUES (?, ?)",
+                params![status, limit],
+            )?,
+            None => conn.execute("DELETE FROM wip_limits WHERE status = ?", params![status])?,
+        };
 
         Ok(())
     }
 
-    fn get_tags_for_task(&self, task_id: &str) -> Result<Vec<Tag>> {
-        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+    // Time tracking methods
+    // Учтенное время входит в задачу, поэтому изменения записей увеличивают ее версию
+    fn create_time_entry(&self, entry: &TimeEntry) -> Result<()> {
+        let mut conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        let tx = conn.transaction()?;
         
-        let mut stmt = conn.prepare(
-            "SELECT t.id, t.name 
-             FROM tags t
-             JOIN task_tags tt ON t.id = tt.tag_id
-             WHERE tt.task_id = ?"
+        tx.execute(
+            &format!("INSERT INTO time_entries ({}) VALUES (?, ?, ?, ?, ?, ?, ?)", TIME_ENTRY_COLUMNS),
+            params![
+                entry.id,
+                entry.task_id,
+                entry.user_id,
+                entry.started_at.to_rfc3339(),
+                entry.ended_at.map(|d| d.to_rfc3339()),
+                entry.minutes,
+                entry.note,
+            ],
         )?;
+        tx.execute("UPDATE tasks SET version = version + 1 WHERE id = ?", params![entry.task_id])?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn stop_time_entry(&self, entry: &TimeEntry) -> Result<()> {
+        let mut conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        let tx = conn.transaction()?;
         
-        let rows = stmt.query_map(params![task_id], |row| {
-            Ok(Tag {
-                id: row.get(0)?,
-                name: row.get(1)?,
-            })
-        })?;
+        let rows_affected = tx.execute(
+            "UPDATE time_entries SET ended_at = ?, minutes = ? WHERE id = ? AND ended_at IS NULL",
+            params![entry.ended_at.map(|d| d.to_rfc3339()), entry.minutes, entry.id],
+        )?;
 
-        let mut tags = Vec::new();
-        for row in rows {
-            tags.push(row?);
+        if rows_affected == 0 {
+            return Err(RepositoryError::not_found(Entity::RunningTimeEntry, &entry.id));
         }
+        tx.execute("UPDATE tasks SET version = version + 1 WHERE id = ?", params![entry.task_id])?;
 
-        Ok(tags)
+        tx.commit()?;
+        Ok(())
     }
 
-    fn add_tag_to_task(&self, task_id: &str, tag_id: &str) -> Result<()> {
-        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+    fn delete_time_entry(&self, id: &str) -> Result<()> {
+        let mut conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        let tx = conn.transaction()?;
         
-        // Проверяем, существует ли задача
-        if conn.query_row(
-            "SELECT 1 FROM tasks WHERE id = ?",
-            params![task_id],
-            |_| Ok(()),
-        ).is_err() {
-            return Err(RepositoryError::NotFound(format!("Task with id {} not found", task_id)));
-        }
+        tx.execute(
+            "UPDATE tasks SET version = version + 1 WHERE id = (SELECT task_id FROM time_entries WHERE id = ?)",
+            params![id],
+        )?;
+        let rows_affected = tx.execute("DELETE FROM time_entries WHERE id = ?", params![id])?;
 
-        // Проверяем, существует ли тег
-        if conn.query_row(
-            "SELECT 1 FROM tags WHERE id = ?",
-            params![tag_id],
-            |_| Ok(()),
-        ).is_err() {
-            return Err(RepositoryError::NotFound(format!("Tag with id {} not found", tag_id)));
+        if rows_affected == 0 {
+            return Err(RepositoryError::not_found(Entity::TimeEntry, id));
         }
 
-        // Добавляем связь (игнорируем, если уже существует)
-        conn.execute(
-            "INSERT OR IGNORE INTO task_tags (task_id, tag_id) VALUES (?, ?)",
-            params![task_id, tag_id],
-        )?;
-
+        tx.commit()?;
         Ok(())
     }
 
-    fn remove_tag_from_task(&self, task_id: &str, tag_id: &str) -> Result<()> {
+    fn get_running_time_entry(&self, user_id: &str) -> Result<Option<TimeEntry>> {
         let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
         
-        let rows_affected = conn.execute(
-            "DELETE FROM task_tags WHERE task_id = ? AND tag_id = ?",
-            params![task_id, tag_id],
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM time_entries WHERE user_id = ? AND ended_at IS NULL",
+            TIME_ENTRY_COLUMNS
+        ))?;
+        let mut rows = stmt.query_map(params![user_id], time_entry_from_row)?;
 
-        if rows_affected == 0 {
-            return Err(RepositoryError::NotFound(
-                format!("Relationship between task {} and tag {} not found", task_id, tag_id)
-            ));
+        Ok(rows.next().transpose()?)
+    }
+
+    fn get_time_entries(&self, filter: &TimeEntryFilter) -> Result<Vec<TimeEntry>> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        let mut conditions = Vec::new();
+        let mut values: Vec<String> = Vec::new();
+        
+        if let Some(task_id) = &filter.task_id {
+            conditions.push("task_id = ?");
+            values.push(task_id.clone());
+        }
+        if let Some(user_id) = &filter.user_id {
+            conditions.push("user_id = ?");
+            values.push(user_id.clone());
+        }
+        if let Some(from) = filter.started_from {
+            conditions.push("started_at >= ?");
+            values.push(from.to_rfc3339());
+        }
+        if let Some(to) = filter.started_to {
+            conditions.push("started_at < ?");
+            values.push(to.to_rfc3339());
         }
+        
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM time_entries {} ORDER BY started_at",
+            TIME_ENTRY_COLUMNS, where_clause
+        ))?;
+        let rows = stmt.query_map(params_from_iter(values.iter()), time_entry_from_row)?;
 
-        Ok(())
+        let mut entries = Vec::new();
+        for row in rows {
+            entries.push(row?);
+        }
+
+        Ok(entries)
     }
+
+    // Project methods
+    fn get_projects(&self) -> Result<Vec<Project>> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM projects ORDER BY name", PROJECT_COLUMNS))?;
+        let rows = stmt.query_map([], project_from_row)?;
+
+        let mut projects = Vec::new();
+        for row in rows {
+            projects.push(row?);
+        }
+
+        Ok(projects)
+    }
+
+    fn get_project_by_id(&self, id: &str) -> Result<Project> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM projects WHERE id = ?", PROJECT_COLUMNS))?;
+        stmt.query_row(params![id], project_from_row)
+            .map_err(|_| RepositoryError::not_found(Entity::Project, id))
+    }
+
+    fn create_project(&self, project: &Project) -> Result<()> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        conn.execute(
+            "INSERT INTO projects (id, name, description, owner_id, customer_id, status, created_at, version)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                project.id,
+                project.name,
+                project.description,
+                project.owner_id,
+                project.customer_id,
+                project.status.code(),
+                project.created_at.to_rfc3339(),
+                project.version,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn update_project(&self, project: &Project) -> Result<()> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        let rows_affected = conn.execute(
+            "UPDATE projects
+             SET name = ?, description = ?, owner_id = ?, customer_id = ?, status = ?,
+                 version = version + 1
+             WHERE id = ? AND version = ?",
+            params![
+                project.name,
+                project.description,
+                project.owner_id,
+                project.customer_id,
+                project.status.code(),
+                project.id,
+                project.version,
+            ],
+        )?;
+
+        if rows_affected == 0 {
+            return Err(version_conflict_or_not_found(&conn, "projects", Entity::Project, &project.id, project.version));
+        }
+
+        Ok(())
+    }
+
+    fn delete_project(&self, id: &str) -> Result<()> {
+        let mut conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        let tx = conn.transaction()?;
+        
+        tx.execute("UPDATE tasks SET project_id = NULL, version = version + 1 WHERE project_id = ?", params![id])?;
+        tx.execute("UPDATE archived_tasks SET project_id = NULL WHERE project_id = ?", params![id])?;
+        let rows_affected = tx.execute("DELETE FROM projects WHERE id = ?", params![id])?;
+
+        if rows_affected == 0 {
+            return Err(RepositoryError::not_found(Entity::Project, id));
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get_tasks_by_project(&self, project_id: &str) -> Result<Vec<Task>> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tasks WHERE project_id = ? AND deleted_at IS NULL",
+            TASK_COLUMNS
+        ))?;
+        let rows = stmt.query_map(params![project_id], task_from_row)?;
+
+        let mut tasks = Vec::new();
+        for row in rows {
+            let mut task = row?;
+            task.tags = tags_for_task(&conn, "task_tags", &task.id)?;
+            load_participants(&conn, &mut task)?;
+            load_time_spent(&conn, &mut task)?;
+            tasks.push(task);
+        }
+
+        Ok(tasks)
+    }
+
+    // Saved view methods
+    fn get_saved_views(&self, user_id: &str) -> Result<Vec<SavedView>> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM saved_views WHERE owner_id = ? OR shared = 1 ORDER BY name",
+            SAVED_VIEW_COLUMNS
+        ))?;
+        let rows = stmt.query_map(params![user_id], saved_view_from_row)?;
+
+        let mut views = Vec::new();
+        for row in rows {
+            views.push(row?);
+        }
+
+        Ok(views)
+    }
+
+    fn get_saved_view_by_id(&self, id: &str) -> Result<SavedView> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM saved_views WHERE id = ?", SAVED_VIEW_COLUMNS))?;
+        stmt.query_row(params![id], saved_view_from_row)
+            .map_err(|_| RepositoryError::not_found(Entity::SavedView, id))
+    }
+
+    fn create_saved_view(&self, view: &SavedView) -> Result<()> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        conn.execute(
+            "INSERT INTO saved_views (id, owner_id, name, filters, sort, columns, shared, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                view.id,
+                view.owner_id,
+                view.name,
+                to_json(&view.filters)?,
+                view.sort,
+                to_json(&view.columns)?,
+                view.shared,
+                view.created_at.to_rfc3339(),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn update_saved_view(&self, view: &SavedView) -> Result<()> {
+        let mut conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        let tx = conn.transaction()?;
+        
+        let rows_affected = tx.execute(
+            "UPDATE saved_views SET name = ?, filters = ?, sort = ?, columns = ?, shared = ? WHERE id = ?",
+            params![
+                view.name,
+                to_json(&view.filters)?,
+                view.sort,
+                to_json(&view.columns)?,
+                view.shared,
+                view.id,
+            ],
+        )?;
+
+        if rows_affected == 0 {
+            return Err(RepositoryError::not_found(Entity::SavedView, &view.id));
+        }
+
+        if !view.shared {
+            tx.execute(
+                "DELETE FROM default_views WHERE view_id = ? AND user_id <> ?",
+                params![view.id, view.owner_id],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn delete_saved_view(&self, id: &str) -> Result<()> {
+        let mut conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        let tx = conn.transaction()?;
+        
+        tx.execute("DELETE FROM default_views WHERE view_id = ?", params![id])?;
+        let rows_affected = tx.execute("DELETE FROM saved_views WHERE id = ?", params![id])?;
+
+        if rows_affected == 0 {
+            return Err(RepositoryError::not_found(Entity::SavedView, id));
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get_default_view_id(&self, user_id: &str) -> Result<Option<String>> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        let view_id = conn.query_row(
+            "SELECT view_id FROM default_views WHERE user_id = ?",
+            params![user_id],
+            |row| row.get(0),
+        );
+
+        match view_id {
+            Ok(view_id) => Ok(Some(view_id)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn set_default_view(&self, user_id: &str, view_id: Option<&str>) -> Result<()> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        match view_id {
+            Some(view_id) => conn.execute(
+                "INSERT OR REPLACE INTO default_views (user_id, view_id) VALUES (?, ?)",
+                params![user_id, view_id],
+            )?,
+            None => conn.execute("DELETE FROM default_views WHERE user_id = ?", params![user_id])?,
+        };
+
+        Ok(())
+    }
+
+    // Trash methods
+    fn get_deleted_tasks(&self) -> Result<Vec<Task>> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tasks WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC",
+            TASK_COLUMNS
+        ))?;
+        let rows = stmt.query_map([], task_from_row)?;
+
+        let mut tasks = Vec::new();
+        for row in rows {
+            let mut task = row?;
+            task.tags = self.get_tags_for_task(&task.id)?;
+            load_participants(&conn, &mut task)?;
+            load_time_spent(&conn, &mut task)?;
+            tasks.push(task);
+        }
+
+        Ok(tasks)
+    }
+
+    fn get_deleted_users(&self) -> Result<Vec<User>> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM users WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC",
+            USER_COLUMNS
+        ))?;
+        let rows = stmt.query_map([], user_from_row)?;
+
+        let mut users = Vec::new();
+        for row in rows {
+            users.push(row?);
+        }
+
+        Ok(users)
+    }
+
+    fn restore_task(&self, id: &str) -> Result<()> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        let rows_affected = conn.execute(
+            "UPDATE tasks SET deleted_at = NULL, version = version + 1 WHERE id = ? AND deleted_at IS NOT NULL",
+            params![id],
+        )?;
+
+        if rows_affected == 0 {
+            return Err(RepositoryError::not_found(Entity::DeletedTask, id));
+        }
+
+        Ok(())
+    }
+
+    fn restore_user(&self, id: &str) -> Result<()> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        let rows_affected = conn.execute(
+            "UPDATE users SET deleted_at = NULL, version = version + 1 WHERE id = ? AND deleted_at IS NOT NULL",
+            params![id],
+        )?;
+
+        if rows_affected == 0 {
+            return Err(RepositoryError::not_found(Entity::DeletedUser, id));
+        }
+
+        Ok(())
+    }
+
+    fn purge_deleted(&self, deleted_before: DateTime<Utc>) -> Result<usize> {
+        let mut conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        let tx = conn.transaction()?;
+        let cutoff = deleted_before.to_rfc3339();
+        
+        tx.execute(
+            "DELETE FROM task_tags WHERE task_id IN
+                (SELECT id FROM tasks WHERE deleted_at IS NOT NULL AND deleted_at < ?)",
+            params![cutoff],
+        )?;
+        tx.execute(
+            "DELETE FROM task_participants WHERE task_id IN
+                (SELECT id FROM tasks WHERE deleted_at IS NOT NULL AND deleted_at < ?)",
+            params![cutoff],
+        )?;
+        tx.execute(
+            "DELETE FROM task_delegations WHERE task_id IN
+                (SELECT id FROM tasks WHERE deleted_at IS NOT NULL AND deleted_at < ?)",
+            params![cutoff],
+        )?;
+        tx.execute(
+            "DELETE FROM time_entries WHERE task_id IN
+                (SELECT id FROM tasks WHERE deleted_at IS NOT NULL AND deleted_at < ?)",
+            params![cutoff],
+        )?;
+        let tasks = tx.execute(
+            "DELETE FROM tasks WHERE deleted_at IS NOT NULL AND deleted_at < ?",
+            params![cutoff],
+        )?;
+        
+        // Пользователи, на которых еще ссылаются задачи, проекты, история передач
+        // или учет времени, остаются в корзине
+        let users = tx.execute(
+            "DELETE FROM users WHERE deleted_at IS NOT NULL AND deleted_at < ?
+                AND id NOT IN (SELECT customer_id FROM tasks)
+                AND id NOT IN (SELECT executor_id FROM tasks)
+                AND id NOT IN (SELECT customer_id FROM archived_tasks)
+                AND id NOT IN (SELECT executor_id FROM archived_tasks)
+                AND id NOT IN (SELECT owner_id FROM projects)
+                AND id NOT IN (SELECT customer_id FROM projects WHERE customer_id IS NOT NULL)
+                AND id NOT IN (SELECT from_user_id FROM task_delegations)
+                AND id NOT IN (SELECT to_user_id FROM task_delegations)
+                AND id NOT IN (SELECT by_user_id FROM task_delegations)
+                AND id NOT IN (SELECT user_id FROM time_entries)",
+            params![cutoff],
+        )?;
+        // Окончательно удаленные пользователи перестают быть участниками задач
+        tx.execute("DELETE FROM task_participants WHERE user_id NOT IN (SELECT id FROM users)", [])?;
+        // Их виды списка задач удаляются вместе с ними
+        tx.execute("DELETE FROM default_views WHERE user_id NOT IN (SELECT id FROM users)", [])?;
+        tx.execute(
+            "DELETE FROM default_views WHERE view_id IN
+                (SELECT id FROM saved_views WHERE owner_id NOT IN (SELECT id FROM users))",
+            [],
+        )?;
+        tx.execute("DELETE FROM saved_views WHERE owner_id NOT IN (SELECT id FROM users)", [])?;
+        
+        tx.commit()?;
+        Ok(tasks + users)
+    }
+
+    // Archive methods
+    fn archive_tasks(&self, finished_before: DateTime<Utc>, now: DateTime<Utc>) -> Result<usize> {
+        let mut conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        let tx = conn.transaction()?;
+        let cutoff = finished_before.to_rfc3339();
+        
+        let selection = format!(
+            "SELECT id FROM tasks
+             WHERE deleted_at IS NULL
+               AND status IN ('completed', 'cancelled')
+               AND COALESCE(unarchived_at, {}) < ?",
+            FINISHED_AT
+        );
+        
+        tx.execute(
+            &format!(
+                "INSERT INTO archived_tasks ({columns}, archived_at)
+                 SELECT {columns}, ? FROM tasks WHERE id IN ({selection})",
+                columns = TASK_COLUMNS,
+                selection = selection,
+            ),
+            params![now.to_rfc3339(), cutoff],
+        )?;
+        tx.execute(
+            &format!(
+                "INSERT INTO archived_task_tags (task_id, tag_id)
+                 SELECT task_id, tag_id FROM task_tags WHERE task_id IN ({})",
+                selection
+            ),
+            params![cutoff],
+        )?;
+        tx.execute(
+            &format!("DELETE FROM task_tags WHERE task_id IN ({})", selection),
+            params![cutoff],
+        )?;
+        let archived = tx.execute(
+            &format!("DELETE FROM tasks WHERE id IN ({})", selection),
+            params![cutoff],
+        )?;
+        
+        tx.commit()?;
+        Ok(archived)
+    }
+
+    fn get_archived_tasks(&self, filter: &ArchiveFilter) -> Result<Vec<Task>> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        let mut conditions: Vec<String> = Vec::new();
+        let mut values: Vec<String> = Vec::new();
+        
+        if let Some(status) = &filter.status {
+            conditions.push("status = ?".to_string());
+            values.push(status.clone());
+        }
+        if let Some(customer_id) = &filter.customer_id {
+            conditions.push("customer_id = ?".to_string());
+            values.push(customer_id.clone());
+        }
+        if let Some(executor_id) = &filter.executor_id {
+            conditions.push("executor_id = ?".to_string());
+            values.push(executor_id.clone());
+        }
+        if let Some(tag) = &filter.tag {
+            conditions.push(
+                "id IN (SELECT att.task_id FROM archived_task_tags att
+                        JOIN tags t ON t.id = att.tag_id WHERE t.name_key = ?)"
+                    .to_string(),
+            );
+            values.push(normalize_tag_name(tag));
+        }
+        if let Some(from) = filter.finished_from {
+            conditions.push(format!("{} >= ?", FINISHED_AT));
+            values.push(from.to_rfc3339());
+        }
+        if let Some(to) = filter.finished_to {
+            conditions.push(format!("{} < ?", FINISHED_AT));
+            values.push(to.to_rfc3339());
+        }
+        
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}, archived_at FROM archived_tasks {} ORDER BY {} DESC",
+            TASK_COLUMNS, where_clause, FINISHED_AT
+        ))?;
+        let rows = stmt.query_map(params_from_iter(values.iter()), archived_task_from_row)?;
+
+        // LIKE в SQLite не учитывает регистр только для латиницы,
+        // поэтому поиск по тексту выполняется здесь
+        let needle = filter.search.as_ref().map(|s| s.to_lowercase());
+
+        let mut tasks = Vec::new();
+        for row in rows {
+            let mut task = row?;
+            if let Some(needle) = &needle {
+                if !task.title.to_lowercase().contains(needle)
+                    && !task.description.to_lowercase().contains(needle)
+                {
+                    continue;
+                }
+            }
+            task.tags = tags_for_task(&conn, "archived_task_tags", &task.id)?;
+            load_participants(&conn, &mut task)?;
+            load_time_spent(&conn, &mut task)?;
+            tasks.push(task);
+        }
+
+        Ok(tasks)
+    }
+
+    fn unarchive_task(&self, id: &str, now: DateTime<Utc>) -> Result<()> {
+        let mut conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        let tx = conn.transaction()?;
+        
+        let restored = tx.execute(
+            &format!(
+                "INSERT INTO tasks ({columns}, unarchived_at)
+                 SELECT {columns}, ? FROM archived_tasks WHERE id = ?",
+                columns = TASK_COLUMNS
+            ),
+            params![now.to_rfc3339(), id],
+        )?;
+
+        if restored == 0 {
+            return Err(RepositoryError::not_found(Entity::ArchivedTask, id));
+        }
+
+        tx.execute(
+            "INSERT INTO task_tags (task_id, tag_id)
+             SELECT task_id, tag_id FROM archived_task_tags WHERE task_id = ?",
+            params![id],
+        )?;
+        tx.execute("DELETE FROM archived_task_tags WHERE task_id = ?", params![id])?;
+        tx.execute("DELETE FROM archived_tasks WHERE id = ?", params![id])?;
+        
+        tx.commit()?;
+        Ok(())
+    }
+    
+    // Tag methods
+    fn get_tags(&self) -> Result<Vec<Tag>> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM tags ORDER BY name", TAG_COLUMNS))?;
+        let rows = stmt.query_map([], tag_from_row)?;
+
+        let mut tags = Vec::new();
+        for row in rows {
+            tags.push(row?);
+        }
+
+        Ok(tags)
+    }
+
+    fn get_tag_by_id(&self, id: &str) -> Result<Tag> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM tags WHERE id = ?", TAG_COLUMNS))?;
+        let tag = stmt.query_row(params![id], tag_from_row)
+            .map_err(|_| RepositoryError::not_found(Entity::Tag, id))?;
+
+        Ok(tag)
+    }
+
+    fn create_tag(&self, tag: &Tag) -> Result<()> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        conn.execute(
+            "INSERT INTO tags (id, name, name_key, color, description) VALUES (?, ?, ?, ?, ?)",
+            params![tag.id, tag.name, normalize_tag_name(&tag.name), tag.color, tag.description],
+        )?;
+
+        Ok(())
+    }
+
+    fn get_or_create_tags_by_names(&self, names: &[String]) -> Result<Vec<Tag>> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        // Имена, различающиеся только регистром, пробелами или ё/е, дают один тег
+        let mut wanted: Vec<(String, String)> = Vec::new();
+        for name in names {
+            let key = normalize_tag_name(name);
+            if !key.is_empty() && !wanted.iter().any(|(k, _)| *k == key) {
+                wanted.push((key, clean_tag_name(name)));
+            }
+        }
+        if wanted.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        // Одним запросом создаем недостающие теги и получаем все нужные:
+        // при конфликте по ключу строка не меняется, но попадает в RETURNING
+        let values = vec!["(?, ?, ?)"; wanted.len()].join(", ");
+        let mut stmt = conn.prepare(&format!(
+            "INSERT INTO tags (id, name, name_key) VALUES {}
+             ON CONFLICT (name_key) DO UPDATE SET name_key = excluded.name_key
+             RETURNING {}, name_key",
+            values, TAG_COLUMNS
+        ))?;
+        let ids: Vec<String> = wanted.iter().map(|_| uuid::Uuid::new_v4().to_string()).collect();
+        let args = wanted.iter().zip(&ids).flat_map(|((key, name), id)| [id, name, key]);
+        let mut found: HashMap<String, Tag> = stmt
+            .query_map(params_from_iter(args), |row| Ok((row.get::<_, String>(4)?, tag_from_row(row)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        
+        // Порядок тегов совпадает с порядком имен в запросе
+        Ok(wanted.iter().filter_map(|(key, _)| found.remove(key)).collect())
+    }
+
+    fn update_tag(&self, tag: &Tag) -> Result<()> {
+        let mut conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        let tx = conn.transaction()?;
+        
+        let rows_affected = tx.execute(
+            "UPDATE tags SET name = ?, name_key = ?, color = ?, description = ? WHERE id = ?",
+            params![tag.name, normalize_tag_name(&tag.name), tag.color, tag.description, tag.id],
+        )?;
+
+        if rows_affected == 0 {
+            return Err(RepositoryError::not_found(Entity::Tag, &tag.id));
+        }
+        bump_tagged_tasks(&tx, &tag.id)?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    // Тег удаляется вместе со связями с задачами, в том числе архивными
+    fn delete_tag(&self, id: &str) -> Result<()> {
+        let mut conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        let tx = conn.transaction()?;
+        
+        bump_tagged_tasks(&tx, id)?;
+        tx.execute("DELETE FROM task_tags WHERE tag_id = ?", params![id])?;
+        tx.execute("DELETE FROM archived_task_tags WHERE tag_id = ?", params![id])?;
+        let rows_affected = tx.execute("DELETE FROM tags WHERE id = ?", params![id])?;
+
+        if rows_affected == 0 {
+            return Err(RepositoryError::not_found(Entity::Tag, id));
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn merge_tags(&self, source_id: &str, target_id: &str) -> Result<()> {
+        let mut conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        let tx = conn.transaction()?;
+        
+        for id in [source_id, target_id] {
+            if tx.query_row("SELECT 1 FROM tags WHERE id = ?", params![id], |_| Ok(())).is_err() {
+                return Err(RepositoryError::not_found(Entity::Tag, id));
+            }
+        }
+
+        repoint_tag(&tx, source_id, target_id)?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get_tag_usage_counts(&self) -> Result<HashMap<String, usize>> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        // Учитываются только задачи основного списка: без корзины и архива
+        let mut stmt = conn.prepare(
+            "SELECT tt.tag_id, COUNT(*)
+             FROM task_tags tt
+             JOIN tasks t ON t.id = tt.task_id
+             WHERE t.deleted_at IS NULL
+             GROUP BY tt.tag_id"
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize)))?;
+
+        let mut counts = HashMap::new();
+        for row in rows {
+            let (tag_id, count) = row?;
+            counts.insert(tag_id, count);
+        }
+
+        Ok(counts)
+    }
+
+    fn get_tags_for_task(&self, task_id: &str) -> Result<Vec<Tag>> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        Ok(tags_for_task(&conn, "task_tags", task_id)?)
+    }
+
+    fn add_tag_to_task(&self, task_id: &str, tag_id: &str) -> Result<()> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        // Проверяем, существует ли задача
+        if conn.query_row(
+            "SELECT 1 FROM tasks WHERE id = ? AND deleted_at IS NULL",
+            params![task_id],
+            |_| Ok(()),
+        ).is_err() {
+            return Err(RepositoryError::not_found(Entity::Task, task_id));
+        }
+
+        // Проверяем, существует ли тег
+        if conn.query_row(
+            "SELECT 1 FROM tags WHERE id = ?",
+            params![tag_id],
+            |_| Ok(()),
+        ).is_err() {
+            return Err(RepositoryError::not_found(Entity::Tag, tag_id));
+        }
+
+        // Добавляем связь (игнорируем, если уже существует)
+        conn.execute(
+            "INSERT OR IGNORE INTO task_tags (task_id, tag_id) VALUES (?, ?)",
+            params![task_id, tag_id],
+        )?;
+
+        Ok(())
+    }
+
+    fn remove_tag_from_task(&self, task_id: &str, tag_id: &str) -> Result<()> {
+        let conn = self.pool.get().map_err(|e| RepositoryError::Internal(e.to_string()))?;
+        
+        let rows_affected = conn.execute(
+            "DELETE FROM task_tags WHERE task_id = ? AND tag_id = ?",
+            params![task_id, tag_id],
+        )?;
+
+        if rows_affected == 0 {
+            return Err(RepositoryError::not_found(Entity::TaskTag, tag_id));
+        }
+
+        Ok(())
+    }
+}
+
+// Соисполнители и наблюдатели задачи
+fn load_participants(conn: &Connection, task: &mut Task) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(
+        "SELECT user_id, role FROM task_participants WHERE task_id = ? ORDER BY rowid",
+    )?;
+    let rows = stmt.query_map(params![task.id], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+
+    task.co_executor_ids.clear();
+    task.watcher_ids.clear();
+    for row in rows {
+        let (user_id, role) = row?;
+        if role == ParticipantRole::CoExecutor.code() {
+            task.co_executor_ids.push(user_id);
+        } else {
+            task.watcher_ids.push(user_id);
+        }
+    }
+
+    Ok(())
+}
+
+// Время по завершенным записям учета; запущенный таймер не учитывается
+fn load_time_spent(conn: &Connection, task: &mut Task) -> rusqlite::Result<()> {
+    task.spent_minutes = conn.query_row(
+        "SELECT COALESCE(SUM(minutes), 0) FROM time_entries WHERE task_id = ? AND ended_at IS NOT NULL",
+        params![task.id],
+        |row| row.get(0),
+    )?;
+    Ok(())
+}
+
+// Теги задачи из таблицы связей task_tags или archived_task_tags
+fn tags_for_task(conn: &Connection, links_table: &str, task_id: &str) -> rusqlite::Result<Vec<Tag>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT t.id, t.name, t.color, t.description
+         FROM tags t JOIN {} l ON t.id = l.tag_id
+         WHERE l.task_id = ?
+         ORDER BY t.name",
+        links_table
+    ))?;
+    let rows = stmt.query_map(params![task_id], tag_from_row)?;
+    rows.collect()
+}
+
+// Теги входят в представление задачи: при их изменении растет версия задач
+// с этим тегом, чтобы клиент со старым ETag не перезаписал изменение
+fn bump_tagged_tasks(conn: &Connection, tag_id: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE tasks SET version = version + 1 WHERE id IN (SELECT task_id FROM task_tags WHERE tag_id = ?)",
+        params![tag_id],
+    )?;
+    Ok(())
+}
+
+// Переносит связи задач с тега source_id на target_id и удаляет source_id.
+// Задачи, у которых были оба тега, получают одну связь с целевым
+fn repoint_tag(conn: &Connection, source_id: &str, target_id: &str) -> rusqlite::Result<()> {
+    bump_tagged_tasks(conn, source_id)?;
+    for links in ["task_tags", "archived_task_tags"] {
+        conn.execute(
+            &format!(
+                "INSERT OR IGNORE INTO {links} (task_id, tag_id)
+                 SELECT task_id, ? FROM {links} WHERE tag_id = ?",
+                links = links
+            ),
+            params![target_id, source_id],
+        )?;
+        conn.execute(&format!("DELETE FROM {} WHERE tag_id = ?", links), params![source_id])?;
+    }
+    conn.execute("DELETE FROM tags WHERE id = ?", params![source_id])?;
+    Ok(())
+}
+
+// Одноразовая миграция: заполняет name_key у тегов, созданных до его
+// появления, и объединяет теги с одинаковым ключом в самый ранний из них
+fn merge_duplicate_tags(conn: &Connection) -> rusqlite::Result<()> {
+    let tags: Vec<(String, String, Option<String>)> = {
+        let mut stmt = conn.prepare("SELECT id, name, name_key FROM tags ORDER BY rowid")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+    // Ключи также пересчитываются, если изменились правила нормализации
+    if tags.iter().all(|(_, name, key)| key.as_deref() == Some(normalize_tag_name(name).as_str())) {
+        return Ok(());
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    // Уникальный индекс мог быть создан раньше: временно его снимаем
+    tx.execute("DROP INDEX IF EXISTS idx_tags_name_key", [])?;
+
+    // Сначала удаляем дубли и только потом переименовываем оставшиеся теги:
+    // пока дубль не удален, его имя может совпадать с очищенным именем
+    // оставляемого тега, а tags.name уникально
+    let mut kept: HashMap<String, String> = HashMap::new();
+    let mut renamed: Vec<(String, String)> = Vec::new();
+    for (id, name, _) in tags {
+        let key = normalize_tag_name(&name);
+        match kept.get(&key) {
+            Some(target_id) => repoint_tag(&tx, &id, target_id)?,
+            None => {
+                tx.execute("UPDATE tags SET name_key = ? WHERE id = ?", params![key, id])?;
+                renamed.push((id.clone(), clean_tag_name(&name)));
+                kept.insert(key, id);
+            }
+        }
+    }
+    for (id, name) in renamed {
+        tx.execute("UPDATE tags SET name = ? WHERE id = ?", params![name, id])?;
+    }
+
+    tx.commit()
+}
+
+const TAG_COLUMNS: &str = "id, name, color, description";
+
+fn tag_from_row(row: &Row) -> rusqlite::Result<Tag> {
+    Ok(Tag {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        color: row.get(2)?,
+        description: row.get(3)?,
+    })
+}
+
+// Сохраняет поля и теги задачи; task.version - версия, которую видел клиент
+fn write_task_update(conn: &Connection, task: &Task) -> Result<()> {
+    let rows_affected = conn.execute(
+        "UPDATE tasks 
+         SET title = ?, description = ?, status = ?, priority = ?, 
+             customer_id = ?, executor_id = ?, due_date = ?, completed_at = ?, cancelled_at = ?,
+             project_id = ?, estimate_minutes = ?, rank = ?, version = version + 1
+         WHERE id = ? AND version = ? AND deleted_at IS NULL",
+        params![
+            task.title,
+            task.description,
+            task.status.code(),
+            task.priority.code(),
+            task.customer_id,
+            task.executor_id,
+            task.due_date.map(|d| d.to_rfc3339()),
+            task.completed_at.map(|d| d.to_rfc3339()),
+            task.cancelled_at.map(|d| d.to_rfc3339()),
+            task.project_id,
+            task.estimate_minutes,
+            task.rank,
+            task.id,
+            task.version,
+        ],
+    )?;
+
+    if rows_affected == 0 {
+        // Либо задачи нет, либо ее уже изменили
+        return Err(version_conflict_or_not_found(conn, "tasks", Entity::Task, &task.id, task.version));
+    }
+
+    // Обновляем теги: сначала удаляем все, потом добавляем заново
+    conn.execute("DELETE FROM task_tags WHERE task_id = ?", params![task.id])?;
+    
+    for tag in &task.tags {
+        conn.execute(
+            "INSERT OR IGNORE INTO task_tags (task_id, tag_id) VALUES (?, ?)",
+            params![task.id, tag.id],
+        )?;
+    }
+
+    Ok(())
+}
+
+// Меняет исполнителя и записывает передачу в цепочку делегирования
+fn write_task_change(conn: &Connection, change: &TaskChange, now: DateTime<Utc>) -> Result<()> {
+    match change {
+        TaskChange::Update(task) => write_task_update(conn, task),
+        TaskChange::Reassign(task, delegation) => write_task_reassign(conn, task, delegation),
+        TaskChange::Delete(task) => {
+            let rows_affected = conn.execute(
+                "UPDATE tasks SET deleted_at = ?, version = version + 1
+                 WHERE id = ? AND version = ? AND deleted_at IS NULL",
+                params![now.to_rfc3339(), task.id, task.version],
+            )?;
+            if rows_affected == 0 {
+                return Err(version_conflict_or_not_found(conn, "tasks", Entity::Task, &task.id, task.version));
+            }
+            Ok(())
+        }
+    }
+}
+
+fn write_task_reassign(conn: &Connection, task: &Task, delegation: &Delegation) -> Result<()> {
+    let rows_affected = conn.execute(
+        "UPDATE tasks SET executor_id = ?, version = version + 1
+         WHERE id = ? AND version = ? AND deleted_at IS NULL",
+        params![delegation.to_user_id, task.id, task.version],
+    )?;
+
+    if rows_affected == 0 {
+        return Err(version_conflict_or_not_found(conn, "tasks", Entity::Task, &task.id, task.version));
+    }
+
+    conn.execute(
+        &format!("INSERT INTO task_delegations ({}) VALUES (?, ?, ?, ?, ?, ?, ?)", DELEGATION_COLUMNS),
+        params![
+            delegation.id,
+            delegation.task_id,
+            delegation.from_user_id,
+            delegation.to_user_id,
+            delegation.by_user_id,
+            delegation.reason,
+            delegation.created_at.to_rfc3339(),
+        ],
+    )?;
+    // Новый исполнитель перестает быть соисполнителем
+    conn.execute(
+        "DELETE FROM task_participants WHERE task_id = ? AND user_id = ? AND role = ?",
+        params![task.id, delegation.to_user_id, ParticipantRole::CoExecutor.code()],
+    )?;
+
+    Ok(())
+}
+
+// Добавляет колонку в существующую таблицу, если ее еще нет
+fn ensure_column(conn: &Connection, table: &str, column: &str, definition: &str) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<rusqlite::Result<Vec<_>>>()?
+        .iter()
+        .any(|name| name == column);
+
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+
+    Ok(())
+}
+
+// Таблицы, записи которых перемещаются в корзину, а не удаляются сразу
+const SOFT_DELETE_TABLES: [&str; 2] = ["users", "tasks"];
+
+// Ошибка для UPDATE, не затронувшего ни одной строки: если запись
+// существует, значит ее версия уже изменилась
+fn version_conflict_or_not_found(
+    conn: &Connection,
+    table: &str,
+    entity: Entity,
+    id: &str,
+    expected: i64,
+) -> RepositoryError {
+    let not_deleted = if SOFT_DELETE_TABLES.contains(&table) { " AND deleted_at IS NULL" } else { "" };
+    let current = conn.query_row(
+        &format!("SELECT version FROM {} WHERE id = ?{}", table, not_deleted),
+        params![id],
+        |row| row.get::<_, i64>(0),
+    );
+
+    match current {
+        Ok(current) => RepositoryError::VersionConflict { id: id.to_string(), expected, current },
+        Err(_) => RepositoryError::not_found(entity, id),
+    }
+}
+
+const DELEGATION_COLUMNS: &str = "id, task_id, from_user_id, to_user_id, by_user_id, reason, created_at";
+
+fn delegation_from_row(row: &Row) -> rusqlite::Result<Delegation> {
+    Ok(Delegation {
+        id: row.get(0)?,
+        task_id: row.get(1)?,
+        from_user_id: row.get(2)?,
+        to_user_id: row.get(3)?,
+        by_user_id: row.get(4)?,
+        reason: row.get(5)?,
+        created_at: parse_datetime(row.get::<_, String>(6)?)?,
+    })
+}
+
+const TIME_ENTRY_COLUMNS: &str = "id, task_id, user_id, started_at, ended_at, minutes, note";
+
+fn time_entry_from_row(row: &Row) -> rusqlite::Result<TimeEntry> {
+    Ok(TimeEntry {
+        id: row.get(0)?,
+        task_id: row.get(1)?,
+        user_id: row.get(2)?,
+        started_at: parse_datetime(row.get::<_, String>(3)?)?,
+        ended_at: row.get::<_, Option<String>>(4)?.map(parse_datetime).transpose()?,
+        minutes: row.get(5)?,
+        note: row.get(6)?,
+    })
+}
+
+const USER_COLUMNS: &str = "id, name, email, role, time_zone, version, deleted_at, active, deactivated_at, locale";
+
+fn user_from_row(row: &Row) -> rusqlite::Result<User> {
+    Ok(User {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        email: row.get(2)?,
+        role: UserRole::from(row.get::<_, String>(3)?.as_str()),
+        time_zone: row.get(4)?,
+        version: row.get(5)?,
+        deleted_at: row.get::<_, Option<String>>(6)?.map(parse_datetime).transpose()?,
+        active: row.get(7)?,
+        deactivated_at: row.get::<_, Option<String>>(8)?.map(parse_datetime).transpose()?,
+        locale: row.get::<_, Option<String>>(9)?.as_deref().and_then(Locale::parse),
+    })
+}
+
+const TASK_COLUMNS: &str = "id, title, description, status, priority, customer_id, executor_id,
+    created_at, due_date, completed_at, version, deleted_at, project_id, estimate_minutes, rank, cancelled_at";
+
+// Время завершения или отмены задачи, как Task::finished_at
+const FINISHED_AT: &str =
+    "COALESCE(CASE WHEN status = 'cancelled' THEN cancelled_at ELSE completed_at END, created_at)";
+
+fn task_from_row(row: &Row) -> rusqlite::Result<Task> {
+    Ok(Task {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        description: row.get(2)?,
+        status: TaskStatus::from(row.get::<_, String>(3)?.as_str()),
+        priority: TaskPriority::from(row.get::<_, String>(4)?.as_str()),
+        customer_id: row.get(5)?,
+        executor_id: row.get(6)?,
+        created_at: parse_datetime(row.get::<_, String>(7)?)?,
+        due_date: row.get::<_, Option<String>>(8)?.map(parse_datetime).transpose()?,
+        completed_at: row.get::<_, Option<String>>(9)?.map(parse_datetime).transpose()?,
+        version: row.get(10)?,
+        deleted_at: row.get::<_, Option<String>>(11)?.map(parse_datetime).transpose()?,
+        project_id: row.get(12)?,
+        estimate_minutes: row.get(13)?,
+        rank: row.get(14)?,
+        cancelled_at: row.get::<_, Option<String>>(15)?.map(parse_datetime).transpose()?,
+        spent_minutes: 0, // Заполняется отдельным запросом
+        co_executor_ids: Vec::new(), // Заполняются отдельным запросом
+        watcher_ids: Vec::new(),
+        archived_at: None,
+        tags: Vec::new(), // Заполняется отдельным запросом
+    })
+}
+
+// Строка archived_tasks: колонки TASK_COLUMNS и archived_at
+fn archived_task_from_row(row: &Row) -> rusqlite::Result<Task> {
+    Ok(Task {
+        archived_at: Some(parse_datetime(row.get::<_, String>(16)?)?),
+        ..task_from_row(row)?
+    })
+}
+
+const PROJECT_COLUMNS: &str = "id, name, description, owner_id, customer_id, status, created_at, version";
+
+fn project_from_row(row: &Row) -> rusqlite::Result<Project> {
+    Ok(Project {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        owner_id: row.get(3)?,
+        customer_id: row.get(4)?,
+        status: ProjectStatus::from(row.get::<_, String>(5)?.as_str()),
+        created_at: parse_datetime(row.get::<_, String>(6)?)?,
+        version: row.get(7)?,
+    })
+}
+
+const SAVED_VIEW_COLUMNS: &str = "id, owner_id, name, filters, sort, columns, shared, created_at";
+
+fn saved_view_from_row(row: &Row) -> rusqlite::Result<SavedView> {
+    Ok(SavedView {
+        id: row.get(0)?,
+        owner_id: row.get(1)?,
+        name: row.get(2)?,
+        filters: parse_json(row.get::<_, String>(3)?)?,
+        sort: row.get(4)?,
+        columns: parse_json(row.get::<_, String>(5)?)?,
+        shared: row.get(6)?,
+        created_at: parse_datetime(row.get::<_, String>(7)?)?,
+    })
+}
+
+// Значения, хранящиеся в колонках как JSON
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string(value).map_err(|e| RepositoryError::Internal(e.to_string()))
+}
+
+fn parse_json<T: serde::de::DeserializeOwned>(s: String) -> rusqlite::Result<T> {
+    serde_json::from_str(&s).map_err(|e| rusqlite::Error::InvalidParameterName(e.to_string()))
 }
 
 // Вспомогательная функция для парсинга DateTime из строки
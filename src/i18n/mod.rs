@@ -0,0 +1,198 @@
+use actix_web::dev::Payload;
+use actix_web::http::header::ACCEPT_LANGUAGE;
+use actix_web::{web, FromRequest, HttpRequest};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::future::{ready, Ready};
+use std::sync::{Arc, OnceLock};
+use utoipa::ToSchema;
+
+use crate::db::Repository;
+
+// Каталоги сообщений встраиваются в бинарный файл
+const RU_CATALOG: &str = include_str!("../../locales/ru.json");
+const EN_CATALOG: &str = include_str!("../../locales/en.json");
+
+// Имя cookie и параметра запроса для явного выбора языка
+pub const LANG_PARAM: &str = "lang";
+
+// Заголовок с идентификатором пользователя, от имени которого выполняется запрос:
+// если язык не выбран явно, используется язык из его настроек
+pub const USER_HEADER: &str = "x-user-id";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    Ru,
+    En,
+}
+
+struct Catalogs {
+    ru: Value,
+    en: Value,
+}
+
+fn catalogs() -> &'static Catalogs {
+    static CATALOGS: OnceLock<Catalogs> = OnceLock::new();
+    CATALOGS.get_or_init(|| Catalogs {
+        ru: serde_json::from_str(RU_CATALOG).expect("locales/ru.json is not valid JSON"),
+        en: serde_json::from_str(EN_CATALOG).expect("locales/en.json is not valid JSON"),
+    })
+}
+
+impl Locale {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::Ru => "ru",
+            Locale::En => "en",
+        }
+    }
+
+    pub fn parse(code: &str) -> Option<Self> {
+        // "en-US" и "en_GB" сводятся к основному языку
+        let primary = code.trim().split(['-', '_']).next()?.to_lowercase();
+        match primary.as_str() {
+            "ru" => Some(Locale::Ru),
+            "en" => Some(Locale::En),
+            _ => None,
+        }
+    }
+
+    // Весь каталог сообщений; используется в шаблонах как {{ tr.section.key }}
+    pub fn catalog(&self) -> &'static Value {
+        match self {
+            Locale::Ru => &catalogs().ru,
+            Locale::En => &catalogs().en,
+        }
+    }
+
+    // Сообщение по ключу вида "section.key". Если перевода нет,
+    // используется русский каталог, а затем сам ключ
+    pub fn t(&self, key: &str) -> String {
+        lookup(self.catalog(), key)
+            .or_else(|| lookup(Locale::Ru.catalog(), key))
+            .unwrap_or_else(|| key.to_string())
+    }
+
+    // Перевод, если строка является ключом каталога, иначе строка без изменений
+    pub fn translate(&self, text: &str) -> String {
+        lookup(self.catalog(), text).unwrap_or_else(|| text.to_string())
+    }
+
+    // Перевод с подстановкой аргументов: {name} в сообщении заменяется значением
+    pub fn translate_with(&self, text: &str, args: &[(&str, &str)]) -> String {
+        args.iter().fold(self.translate(text), |message, (name, value)| {
+            message.replace(&format!("{{{}}}", name), value)
+        })
+    }
+
+    // Выбор языка: параметр ?lang=, затем cookie lang, затем язык пользователя
+    // из заголовка X-User-Id, затем Accept-Language
+    pub fn negotiate(req: &HttpRequest) -> Self {
+        let from_query = req
+            .query_string()
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(name, _)| *name == LANG_PARAM)
+            .and_then(|(_, value)| Locale::parse(value));
+
+        if let Some(locale) = from_query {
+            return locale;
+        }
+
+        if let Some(locale) = req.cookie(LANG_PARAM).and_then(|c| Locale::parse(c.value())) {
+            return locale;
+        }
+
+        if let Some(locale) = user_locale(req) {
+            return locale;
+        }
+
+        req.headers()
+            .get(ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(from_accept_language)
+            .unwrap_or_default()
+    }
+}
+
+impl FromRequest for Locale {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Ok(Locale::negotiate(req)))
+    }
+}
+
+// Язык из настроек пользователя, указанного в заголовке X-User-Id
+fn user_locale(req: &HttpRequest) -> Option<Locale> {
+    let user_id = req.headers().get(USER_HEADER)?.to_str().ok()?;
+    let repo = req.app_data::<web::Data<Arc<dyn Repository>>>()?;
+    repo.get_user_by_id(user_id).ok()?.locale
+}
+
+fn lookup(catalog: &Value, key: &str) -> Option<String> {
+    key.split('.')
+        .try_fold(catalog, |node, part| node.get(part))
+        .and_then(|value| value.as_str())
+        .map(str::to_string)
+}
+
+// Разбор заголовка вида "en-US,en;q=0.9,ru;q=0.8": выбирается
+// поддерживаемый язык с наибольшим весом. Вес q=0 означает, что язык
+// клиенту не подходит
+fn from_accept_language(header: &str) -> Option<Locale> {
+    let mut best: Option<(Locale, f32)> = None;
+
+    for item in header.split(',') {
+        let mut parts = item.split(';');
+        let code = parts.next().unwrap_or("");
+        let weight = parts
+            .find_map(|p| p.trim().strip_prefix("q="))
+            .and_then(|q| q.parse::<f32>().ok())
+            .unwrap_or(1.0);
+
+        if weight <= 0.0 {
+            continue;
+        }
+
+        if let Some(locale) = Locale::parse(code) {
+            let better = match best {
+                Some((_, w)) => weight > w,
+                None => true,
+            };
+            if better {
+                best = Some((locale, weight));
+            }
+        }
+    }
+
+    best.map(|(locale, _)| locale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accept_language_prefers_highest_weight() {
+        assert_eq!(from_accept_language("en-US,en;q=0.9,ru;q=0.8"), Some(Locale::En));
+        assert_eq!(from_accept_language("en;q=0.5, ru-RU;q=0.7"), Some(Locale::Ru));
+        assert_eq!(from_accept_language("de, en_GB;q=0.3"), Some(Locale::En));
+    }
+
+    #[test]
+    fn accept_language_ignores_unsupported_languages() {
+        assert_eq!(from_accept_language("de-DE,fr;q=0.9"), None);
+        assert_eq!(from_accept_language(""), None);
+    }
+
+    #[test]
+    fn zero_weight_means_not_acceptable() {
+        assert_eq!(from_accept_language("en;q=0"), None);
+        assert_eq!(from_accept_language("en;q=0.0, ru;q=0.1"), Some(Locale::Ru));
+        assert_eq!(from_accept_language("ru;q=0,en;q=0.2"), Some(Locale::En));
+    }
+}
@@ -0,0 +1,44 @@
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Arc;
+use std::thread;
+
+use crate::clock::Clock;
+use crate::db;
+
+// Как часто запускаются фоновые задачи
+const JOB_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+// Срок в днях из переменной среды var, по умолчанию default. 0 отключает задачу (None)
+pub fn days_from_env(var: &str, default: u32) -> Result<Option<u32>, String> {
+    let days = match std::env::var(var) {
+        Ok(value) => value
+            .trim()
+            .parse::<u32>()
+            .map_err(|_| format!("{}: invalid number of days '{}'", var, value))?,
+        Err(_) => default,
+    };
+
+    Ok((days > 0).then_some(days))
+}
+
+// Фоновая задача над записями старше days дней: раз в JOB_INTERVAL вызывает
+// run(cutoff, now) и выводит число обработанных записей (done) или ошибку (failed).
+// При days = None задача не запускается
+pub fn spawn_periodic<F>(days: Option<u32>, clock: Arc<dyn Clock>, done: &'static str, failed: &'static str, run: F)
+where
+    F: Fn(DateTime<Utc>, DateTime<Utc>) -> db::Result<usize> + Send + 'static,
+{
+    let Some(days) = days else {
+        return;
+    };
+
+    thread::spawn(move || loop {
+        let now = clock.now();
+        match run(now - Duration::days(days as i64), now) {
+            Ok(0) => {}
+            Ok(count) => println!("{}: {}", done, count),
+            Err(e) => eprintln!("{}: {}", failed, e),
+        }
+        thread::sleep(JOB_INTERVAL);
+    });
+}
@@ -0,0 +1,106 @@
+pub mod api;
+pub mod archive;
+pub mod calendar;
+pub mod clock;
+pub mod db;
+pub mod i18n;
+pub mod jobs;
+pub mod models;
+pub mod pages;
+pub mod trash;
+pub mod validation;
+
+use actix_files as fs;
+use actix_web::body::MessageBody;
+use actix_web::dev::{Service, ServiceFactory, ServiceRequest, ServiceResponse};
+use actix_web::{web, App, HttpResponse};
+use std::sync::Arc;
+use tera::Tera;
+
+use crate::archive::ArchiveSettings;
+use crate::calendar::BusinessCalendar;
+use crate::clock::Clock;
+use crate::db::Repository;
+use crate::i18n::Locale;
+use crate::trash::TrashSettings;
+
+// Общие данные приложения, которые получают обработчики через web::Data
+#[derive(Clone)]
+pub struct AppState {
+    pub tera: Tera,
+    pub repo: Arc<dyn Repository>,
+    pub calendar: BusinessCalendar,
+    pub clock: Arc<dyn Clock>,
+    pub trash_settings: TrashSettings,
+    pub archive_settings: ArchiveSettings,
+}
+
+// Шаблоны страниц из каталога templates с фильтрами приложения
+pub fn templates() -> Result<Tera, tera::Error> {
+    let mut tera = Tera::new("templates/**/*")?;
+    tera.register_filter("local_datetime", calendar::timezone::tera_local_datetime);
+    Ok(tera)
+}
+
+// Собирает приложение: страницы, API и статические файлы. Используется
+// сервером в main и интеграционными тестами
+pub fn build_app(
+    state: &AppState,
+) -> App<
+    impl ServiceFactory<
+        ServiceRequest,
+        Config = (),
+        Response = ServiceResponse<impl MessageBody>,
+        Error = actix_web::Error,
+        InitError = (),
+    >,
+> {
+    App::new()
+        .app_data(web::Data::new(state.tera.clone()))
+        .app_data(web::Data::new(state.repo.clone()))
+        .app_data(web::Data::new(state.calendar.clone()))
+        .app_data(web::Data::new(state.clock.clone()))
+        .app_data(web::Data::new(state.trash_settings))
+        .app_data(web::Data::new(state.archive_settings))
+        .service(pages::index)
+        .service(pages::users_page)
+        .service(pages::projects_page)
+        .service(pages::project_page)
+        .service(pages::tags_page)
+        .service(pages::trash_page)
+        .service(pages::board_page)
+        .service(pages::timesheet_page)
+        .service(pages::archive_page)
+        .service(
+            web::scope("/api")
+                // Ошибки API возвращаются JSON-ответом на языке запроса
+                // с идентификатором запроса
+                .wrap_fn(|req, srv| {
+                    let locale = Locale::negotiate(req.request());
+                    let request_id = api::request_id(req.request());
+                    let fut = srv.call(req);
+                    async move { fut.await.map(|res| api::localize_error(res, locale, &request_id)) }
+                })
+                .app_data(api::json_config())
+                .app_data(api::path_config())
+                .app_data(api::query_config())
+                .configure(api::openapi::config)
+                .configure(api::enums::config)
+                .configure(api::users::config)
+                .configure(api::views::config)
+                .configure(api::projects::config)
+                .configure(api::bulk::config)
+                .configure(api::tasks::config)
+                .configure(api::board::config)
+                .configure(api::time_entries::config)
+                .configure(api::tags::config)
+                .configure(api::trash::config)
+                .configure(api::archive::config)
+                .configure(api::reports::config)
+                .default_service(web::route().to(api::route_not_found))
+        )
+        .service(fs::Files::new("/static", "static").show_files_listing())
+        .default_service(web::route().to(|locale: Locale| async move {
+            HttpResponse::NotFound().body(locale.t("errors.page_not_found"))
+        }))
+}
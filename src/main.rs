@@ -1,84 +1,13 @@
-mod api;
-mod db;
-mod models;
-
-use actix_files as fs;
-use actix_web::{web, App, HttpResponse, HttpServer, Responder, get};
+use actix_web::HttpServer;
 use dotenv::dotenv;
-use r2d2_sqlite::SqliteConnectionManager;
 use std::env;
 use std::sync::Arc;
-use tera::Tera;
-
-use crate::db::{Repository, SqliteRepository};
-use crate::models::{User, UserRole};
 
-#[get("/")]
-async fn index(
-    tmpl: web::Data<Tera>,
-    repo: web::Data<Arc<dyn Repository>>,
-) -> impl Responder {
-    let mut ctx = tera::Context::new();
-    
-    // Получаем списки пользователей для выпадающих списков
-    match repo.get_users() {
-        Ok(users) => {
-            let customers: Vec<&User> = users.iter()
-                .filter(|u| u.role == UserRole::Customer)
-                .collect();
-            
-            let executors: Vec<&User> = users.iter()
-                .filter(|u| u.role == UserRole::Executor)
-                .collect();
-            
-            ctx.insert("customers", &customers);
-            ctx.insert("executors", &executors);
-        },
-        Err(e) => {
-            eprintln!("Ошибка при получении пользователей: {}", e);
-            ctx.insert("customers", &Vec::<User>::new());
-            ctx.insert("executors", &Vec::<User>::new());
-        }
-    }
-    
-    let rendered = match tmpl.render("index.html", &ctx) {
-        Ok(t) => t,
-        Err(e) => {
-            eprintln!("Ошибка рендеринга шаблона: {}", e);
-            return HttpResponse::InternalServerError().body("Ошибка рендеринга шаблона");
-        }
-    };
-    
-    HttpResponse::Ok().content_type("text/html").body(rendered)
-}
-
-#[get("/users")]
-async fn users_page(
-    tmpl: web::Data<Tera>,
-    repo: web::Data<Arc<dyn Repository>>,
-) -> impl Responder {
-    let mut ctx = tera::Context::new();
-    
-    match repo.get_users() {
-        Ok(users) => {
-            ctx.insert("users", &users);
-        },
-        Err(e) => {
-            eprintln!("Ошибка при получении пользователей: {}", e);
-            ctx.insert("users", &Vec::<User>::new());
-        }
-    }
-    
-    let rendered = match tmpl.render("users.html", &ctx) {
-        Ok(t) => t,
-        Err(e) => {
-            eprintln!("Ошибка рендеринга шаблона: {}", e);
-            return HttpResponse::InternalServerError().body("Ошибка рендеринга шаблона");
-        }
-    };
-    
-    HttpResponse::Ok().content_type("text/html").body(rendered)
-}
+use business_tasks::archive::{self, ArchiveSettings};
+use business_tasks::calendar::BusinessCalendar;
+use business_tasks::clock::{Clock, SystemClock};
+use business_tasks::trash::{self, TrashSettings};
+use business_tasks::{build_app, db, templates, AppState};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -92,24 +21,28 @@ async fn main() -> std::io::Result<()> {
     let port = env::var("PORT").unwrap_or_else(|_| "8080".to_string());
     let port = port.parse::<u16>().expect("PORT должен быть числом");
     
-    // Получаем путь к базе данных из переменных среды или используем значение по умолчанию
-    let db_path = env::var("DATABASE_URL").unwrap_or_else(|_| "business_tasks.db".to_string());
+    // Адрес базы данных: путь к файлу SQLite или postgres://... для PostgreSQL
+    let database_url = env::var("DATABASE_URL").unwrap_or_else(|_| "business_tasks.db".to_string());
+    
+    // Подключаемся к базе данных и применяем схему
+    let repo = db::connect(&database_url).expect("Не удалось подключиться к базе данных");
     
-    // Настраиваем соединение с базой данных
-    let manager = SqliteConnectionManager::file(&db_path);
-    let pool = r2d2::Pool::new(manager).expect("Не удалось создать пул соединений");
+    // Часы приложения: общие для обработчиков и фоновых задач
+    let clock: Arc<dyn Clock> = Arc::new(SystemClock);
     
-    // Инициализируем базу данных
-    {
-        let conn = pool.get().expect("Не удалось получить соединение с БД");
-        SqliteRepository::init_db(&conn).expect("Не удалось инициализировать БД");
-    }
+    // Запускаем автоматическую очистку корзины
+    let trash_settings = TrashSettings::from_env().expect("Некорректный срок хранения корзины");
+    trash::spawn_purge(repo.clone(), clock.clone(), trash_settings);
     
-    // Создаем репозиторий
-    let repo = Arc::new(SqliteRepository::new(Arc::new(pool)));
+    // Запускаем автоматическую архивацию завершенных задач
+    let archive_settings = ArchiveSettings::from_env().expect("Некорректный срок архивации");
+    archive::spawn_archiver(repo.clone(), clock.clone(), archive_settings);
+    
+    // Загружаем производственный календарь
+    let calendar = BusinessCalendar::from_env().expect("Не удалось загрузить производственный календарь");
     
     // Настраиваем шаблонизатор Tera
-    let tera = match Tera::new("templates/**/*") {
+    let tera = match templates() {
         Ok(t) => t,
         Err(e) => {
             eprintln!("Ошибка компиляции шаблонов: {}", e);
@@ -117,26 +50,20 @@ async fn main() -> std::io::Result<()> {
         }
     };
     
+    let state = AppState {
+        tera,
+        repo,
+        calendar,
+        clock,
+        trash_settings,
+        archive_settings,
+    };
+    
     println!("Сервер запущен на http://localhost:{}", port);
     
     // Запускаем HTTP-сервер
-    HttpServer::new(move || {
-        App::new()
-            .app_data(web::Data::new(tera.clone()))
-            .app_data(web::Data::new(repo.clone()))
-            .service(index)
-            .service(users_page)
-            .service(
-                web::scope("/api")
-                    .configure(api::users::config)
-                    .configure(api::tasks::config)
-            )
-            .service(fs::Files::new("/static", "static").show_files_listing())
-            .default_service(web::route().to(|| async {
-                HttpResponse::NotFound().body("Страница не найдена")
-            }))
-    })
-    .bind(("0.0.0.0", port))?
-    .run()
-    .await
+    HttpServer::new(move || build_app(&state))
+        .bind(("0.0.0.0", port))?
+        .run()
+        .await
 }
@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+// Передача задачи другому исполнителю; записи по задаче образуют цепочку делегирования
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Delegation {
+    pub id: String,
+    pub task_id: String,
+    pub from_user_id: String, // Прежний исполнитель
+    pub to_user_id: String,   // Новый исполнитель
+    pub by_user_id: String,   // Кто передал: прежний исполнитель или заказчик
+    pub reason: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Delegation {
+    pub fn new(task_id: String, from_user_id: String, to_user_id: String, by_user_id: String, reason: String, now: DateTime<Utc>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            task_id,
+            from_user_id,
+            to_user_id,
+            by_user_id,
+            reason,
+            created_at: now,
+        }
+    }
+}
@@ -1,7 +1,15 @@
 pub mod task;
 pub mod user;
 pub mod tag;
+pub mod project;
+pub mod delegation;
+pub mod time_entry;
+pub mod saved_view;
 
-pub use task::Task;
-pub use user::User;
+pub use task::{ParticipantRole, Task, TaskPriority, TaskStatus};
+pub use user::{User, UserRole};
 pub use tag::Tag;
+pub use project::{Project, ProjectStatus};
+pub use delegation::Delegation;
+pub use time_entry::TimeEntry;
+pub use saved_view::{SavedView, ViewFilters};
@@ -0,0 +1,83 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::i18n::Locale;
+
+// Проект объединяет связанные поручения
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Project {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub owner_id: String,            // Руководитель проекта
+    pub customer_id: Option<String>, // Заказчик, если проект ведется для него
+    pub status: ProjectStatus,
+    pub created_at: DateTime<Utc>,
+    pub version: i64, // Номер версии для оптимистичной блокировки
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub enum ProjectStatus {
+    Active,
+    OnHold,
+    Completed,
+    Cancelled,
+}
+
+impl Project {
+    pub fn new(name: String, description: String, owner_id: String, now: DateTime<Utc>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            description,
+            owner_id,
+            customer_id: None,
+            status: ProjectStatus::Active,
+            created_at: now,
+            version: 1,
+        }
+    }
+}
+
+impl ProjectStatus {
+    pub const ALL: [ProjectStatus; 4] = [
+        ProjectStatus::Active,
+        ProjectStatus::OnHold,
+        ProjectStatus::Completed,
+        ProjectStatus::Cancelled,
+    ];
+
+    // Строковое значение, используемое в API и базе данных
+    pub fn code(&self) -> &'static str {
+        match self {
+            ProjectStatus::Active => "active",
+            ProjectStatus::OnHold => "on_hold",
+            ProjectStatus::Completed => "completed",
+            ProjectStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn label(&self, locale: Locale) -> String {
+        locale.t(&format!("project_status.{}", self.code()))
+    }
+}
+
+impl std::fmt::Display for ProjectStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label(Locale::default()))
+    }
+}
+
+impl From<&str> for ProjectStatus {
+    fn from(s: &str) -> Self {
+        match s {
+            "active" => ProjectStatus::Active,
+            "on_hold" => ProjectStatus::OnHold,
+            "completed" => ProjectStatus::Completed,
+            "cancelled" => ProjectStatus::Cancelled,
+            _ => ProjectStatus::Active,
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::tag::normalize_tag_name;
+use super::Task;
+
+// Сохраненный вид списка поручений: условия фильтрации, сортировка и видимые колонки.
+// Общий вид (shared) доступен всем пользователям, изменяет его только владелец
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct SavedView {
+    pub id: String,
+    pub owner_id: String,
+    pub name: String,
+    pub filters: ViewFilters,
+    pub sort: Option<String>, // Поле сортировки; "-" в начале - по убыванию
+    pub columns: Vec<String>, // Видимые колонки; пустой список - все колонки
+    pub shared: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+// Условия фильтрации вида; те же, что у фильтров на странице поручений
+#[derive(Debug, Default, Serialize, Deserialize, Clone, ToSchema)]
+#[serde(default)]
+pub struct ViewFilters {
+    pub status: Option<String>,   // Код статуса
+    pub priority: Option<String>, // Код приоритета
+    pub customer_id: Option<String>,
+    pub executor_id: Option<String>,
+    pub participant_id: Option<String>,
+    pub project_id: Option<String>, // "none" - поручения вне проектов
+    pub tags: Vec<String>,          // Имена тегов; подходит поручение с любым из них
+    pub search: Option<String>,
+}
+
+impl SavedView {
+    // Поля, по которым можно сортировать список
+    pub const SORT_FIELDS: [&'static str; 6] = ["title", "status", "priority", "due_date", "created_at", "overdue"];
+    // Колонки таблицы поручений, которые можно скрыть
+    pub const COLUMNS: [&'static str; 8] = ["title", "status", "priority", "customer", "executor", "due", "overdue", "tags"];
+
+    pub fn new(owner_id: String, name: String, now: DateTime<Utc>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            owner_id,
+            name,
+            filters: ViewFilters::default(),
+            sort: None,
+            columns: Vec::new(),
+            shared: false,
+            created_at: now,
+        }
+    }
+
+    // Вид доступен владельцу и, если он общий, всем остальным
+    pub fn is_available_to(&self, user_id: &str) -> bool {
+        self.shared || self.owner_id == user_id
+    }
+}
+
+impl ViewFilters {
+    // Проверяет задачу так же, как фильтры на странице поручений
+    pub fn matches(&self, task: &Task) -> bool {
+        let equals = |filter: &Option<String>, value: &str| filter.as_ref().is_none_or(|f| f == value);
+
+        if !equals(&self.status, task.status.code()) || !equals(&self.priority, task.priority.code()) {
+            return false;
+        }
+        if !equals(&self.customer_id, &task.customer_id) || !equals(&self.executor_id, &task.executor_id) {
+            return false;
+        }
+        if let Some(participant_id) = &self.participant_id {
+            if task.roles_of(participant_id).is_empty() {
+                return false;
+            }
+        }
+        match self.project_id.as_deref() {
+            Some("none") if task.project_id.is_some() => return false,
+            Some(id) if id != "none" && task.project_id.as_deref() != Some(id) => return false,
+            _ => {}
+        }
+        if !self.tags.is_empty() {
+            let keys: Vec<String> = self.tags.iter().map(|tag| normalize_tag_name(tag)).collect();
+            if !task.tags.iter().any(|tag| keys.contains(&normalize_tag_name(&tag.name))) {
+                return false;
+            }
+        }
+        if let Some(search) = &self.search {
+            let search = search.to_lowercase();
+            if !task.title.to_lowercase().contains(&search) && !task.description.to_lowercase().contains(&search) {
+                return false;
+            }
+        }
+        true
+    }
+}
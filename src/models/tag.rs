@@ -1,10 +1,13 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
 pub struct Tag {
     pub id: String,
     pub name: String,
+    pub color: Option<String>,       // Цвет в формате #RRGGBB
+    pub description: Option<String>,
 }
 
 impl Tag {
@@ -12,6 +15,54 @@ impl Tag {
         Self {
             id: Uuid::new_v4().to_string(),
             name,
+            color: None,
+            description: None,
         }
     }
 }
+
+// Ключ для сравнения имен тегов: без пробелов по краям и повторных пробелов,
+// со свернутым регистром, "ё" приравнивается к "е". "Отчёт", " отчет " и "ОТЧЕТ"
+// считаются одним тегом
+pub fn normalize_tag_name(name: &str) -> String {
+    fold_case(&name.split_whitespace().collect::<Vec<_>>().join(" ")).replace('ё', "е")
+}
+
+// Свертка регистра Unicode: в отличие от to_lowercase сводит к одному виду
+// и символы, у которых строчных форм несколько ("ß" и "SS", "ς" и "σ")
+fn fold_case(text: &str) -> String {
+    text.to_uppercase().to_lowercase()
+}
+
+// Отображаемое имя тега: без пробелов по краям и повторных пробелов
+pub fn clean_tag_name(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_ignores_spaces_case_and_yo() {
+        let key = normalize_tag_name("Отчет");
+        for name in ["отчет", " Отчет ", "ОТЧЕТ", "Отчёт", "ОТЧЁТ"] {
+            assert_eq!(normalize_tag_name(name), key, "{}", name);
+        }
+        assert_eq!(normalize_tag_name("  годовой \t  Отчет "), "годовой отчет");
+        assert_ne!(normalize_tag_name("отчеты"), key);
+    }
+
+    #[test]
+    fn normalize_folds_case_beyond_lowercase() {
+        assert_eq!(normalize_tag_name("Straße"), normalize_tag_name("STRASSE"));
+        assert_eq!(normalize_tag_name("ΟΔΟΣ"), normalize_tag_name("οδος"));
+        assert_eq!(normalize_tag_name("ﬁnance"), "finance");
+    }
+
+    #[test]
+    fn clean_keeps_case_and_yo() {
+        assert_eq!(clean_tag_name("  Годовой   Отчёт "), "Годовой Отчёт");
+        assert_eq!(normalize_tag_name("   "), "");
+    }
+}
@@ -1,10 +1,14 @@
-use chrono::{DateTime, Local, NaiveDateTime, Utc};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 use super::tag::Tag;
+use crate::calendar::BusinessCalendar;
+use crate::i18n::Locale;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Task {
     pub id: String,
     pub title: String,
@@ -13,13 +17,23 @@ pub struct Task {
     pub priority: TaskPriority,
     pub customer_id: String,
     pub executor_id: String,
+    pub co_executor_ids: Vec<String>, // Соисполнители
+    pub watcher_ids: Vec<String>,     // Наблюдатели, следящие за ходом выполнения
+    pub project_id: Option<String>, // Проект, к которому относится задача
+    pub estimate_minutes: Option<i64>, // Оценка трудозатрат
+    pub spent_minutes: i64, // Учтенное время по завершенным записям
+    pub rank: i64, // Порядок карточки в колонке доски; 0 - в начале колонки
     pub created_at: DateTime<Utc>,
     pub due_date: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
+    pub cancelled_at: Option<DateTime<Utc>>, // Время отмены
     pub tags: Vec<Tag>,
+    pub version: i64, // Номер версии для оптимистичной блокировки
+    pub deleted_at: Option<DateTime<Utc>>, // Время перемещения в корзину
+    pub archived_at: Option<DateTime<Utc>>, // Время перемещения в архив
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
 pub enum TaskStatus {
     New,
     InProgress,
@@ -27,7 +41,14 @@ pub enum TaskStatus {
     Cancelled,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+// Роль дополнительного участника задачи
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, ToSchema)]
+pub enum ParticipantRole {
+    CoExecutor,
+    Watcher,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
 pub enum TaskPriority {
     Low,
     Medium,
@@ -36,6 +57,8 @@ pub enum TaskPriority {
 }
 
 impl Task {
+    // now - время создания по часам приложения
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         title: String,
         description: String,
@@ -44,6 +67,7 @@ impl Task {
         priority: TaskPriority,
         due_date: Option<DateTime<Utc>>,
         tags: Vec<Tag>,
+        now: DateTime<Utc>,
     ) -> Self {
         Self {
             id: Uuid::new_v4().to_string(),
@@ -53,53 +77,127 @@ impl Task {
             priority,
             customer_id,
             executor_id,
-            created_at: Utc::now(),
+            co_executor_ids: Vec::new(),
+            watcher_ids: Vec::new(),
+            project_id: None,
+            estimate_minutes: None,
+            spent_minutes: 0,
+            rank: 0,
+            created_at: now,
             due_date,
             completed_at: None,
+            cancelled_at: None,
             tags,
+            version: 1,
+            deleted_at: None,
+            archived_at: None,
+        }
+    }
+
+    // Время, с которого отсчитывается срок архивации: завершение или отмена;
+    // для записей, где оно не сохранено, - время создания
+    pub fn finished_at(&self) -> DateTime<Utc> {
+        let finished_at = match self.status {
+            TaskStatus::Cancelled => self.cancelled_at,
+            _ => self.completed_at,
+        };
+        finished_at.unwrap_or(self.created_at)
+    }
+
+    // Отклонение учтенного времени от оценки: положительное - перерасход
+    pub fn estimate_variance_minutes(&self) -> Option<i64> {
+        self.estimate_minutes.map(|estimate| self.spent_minutes - estimate)
+    }
+
+    // Роли пользователя в задаче: заказчик, исполнитель, соисполнитель, наблюдатель
+    pub fn roles_of(&self, user_id: &str) -> Vec<&'static str> {
+        let mut roles = Vec::new();
+        if self.customer_id == user_id {
+            roles.push("customer");
+        }
+        if self.executor_id == user_id {
+            roles.push("executor");
+        }
+        if self.co_executor_ids.iter().any(|id| id == user_id) {
+            roles.push(ParticipantRole::CoExecutor.code());
+        }
+        if self.watcher_ids.iter().any(|id| id == user_id) {
+            roles.push(ParticipantRole::Watcher.code());
         }
+        roles
     }
 
-    pub fn is_overdue(&self) -> bool {
+    // Просрочка определяется на момент now: текущее время или время,
+    // на которое строится прогноз
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
         if self.status == TaskStatus::Completed || self.status == TaskStatus::Cancelled {
             return false;
         }
 
         if let Some(due) = self.due_date {
-            return Utc::now() > due;
+            return now > due;
         }
 
         false
     }
 
-    pub fn overdue_days(&self) -> Option<i64> {
-        if !self.is_overdue() {
+    pub fn overdue_days(&self, now: DateTime<Utc>) -> Option<i64> {
+        if !self.is_overdue(now) {
             return None;
         }
 
         self.due_date.map(|due| {
-            let duration = Utc::now().signed_duration_since(due);
+            let duration = now.signed_duration_since(due);
             duration.num_days()
         })
     }
+
+    // Количество просроченных рабочих дней по производственному календарю
+    // с учетом часового пояса исполнителя
+    pub fn overdue_working_days(&self, calendar: &BusinessCalendar, tz: Tz, now: DateTime<Utc>) -> Option<i64> {
+        if !self.is_overdue(now) {
+            return None;
+        }
+
+        self.due_date.map(|due| calendar.overdue_working_days(due, now, tz))
+    }
 }
 
-impl std::fmt::Display for TaskStatus {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl TaskStatus {
+    pub const ALL: [TaskStatus; 4] = [
+        TaskStatus::New,
+        TaskStatus::InProgress,
+        TaskStatus::Completed,
+        TaskStatus::Cancelled,
+    ];
+
+    // Строковое значение, используемое в API и базе данных
+    pub fn code(&self) -> &'static str {
         match self {
-            TaskStatus::New => write!(f, "Новая"),
-            TaskStatus::InProgress => write!(f, "В работе"),
-            TaskStatus::Completed => write!(f, "Завершена"),
-            TaskStatus::Cancelled => write!(f, "Отменена"),
+            TaskStatus::New => "new",
+            TaskStatus::InProgress => "in_progress",
+            TaskStatus::Completed => "completed",
+            TaskStatus::Cancelled => "cancelled",
         }
     }
+
+    pub fn label(&self, locale: Locale) -> String {
+        locale.t(&format!("status.{}", self.code()))
+    }
+}
+
+impl std::fmt::Display for TaskStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label(Locale::default()))
+    }
 }
 
 impl From<&str> for TaskStatus {
     fn from(s: &str) -> Self {
         match s {
             "new" => TaskStatus::New,
-            "in_progress" => TaskStatus::InProgress,
+            // "inprogress" записывался ранними версиями приложения
+            "in_progress" | "inprogress" => TaskStatus::InProgress,
             "completed" => TaskStatus::Completed,
             "cancelled" => TaskStatus::Cancelled,
             _ => TaskStatus::New,
@@ -107,17 +205,45 @@ impl From<&str> for TaskStatus {
     }
 }
 
-impl std::fmt::Display for TaskPriority {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl ParticipantRole {
+    // Строковое значение, используемое в API и базе данных
+    pub fn code(&self) -> &'static str {
         match self {
-            TaskPriority::Low => write!(f, "Низкий"),
-            TaskPriority::Medium => write!(f, "Средний"),
-            TaskPriority::High => write!(f, "Высокий"),
-            TaskPriority::Critical => write!(f, "Критический"),
+            ParticipantRole::CoExecutor => "co_executor",
+            ParticipantRole::Watcher => "watcher",
         }
     }
 }
 
+impl TaskPriority {
+    pub const ALL: [TaskPriority; 4] = [
+        TaskPriority::Low,
+        TaskPriority::Medium,
+        TaskPriority::High,
+        TaskPriority::Critical,
+    ];
+
+    // Строковое значение, используемое в API и базе данных
+    pub fn code(&self) -> &'static str {
+        match self {
+            TaskPriority::Low => "low",
+            TaskPriority::Medium => "medium",
+            TaskPriority::High => "high",
+            TaskPriority::Critical => "critical",
+        }
+    }
+
+    pub fn label(&self, locale: Locale) -> String {
+        locale.t(&format!("priority.{}", self.code()))
+    }
+}
+
+impl std::fmt::Display for TaskPriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label(Locale::default()))
+    }
+}
+
 impl From<&str> for TaskPriority {
     fn from(s: &str) -> Self {
         match s {
@@ -129,3 +255,15 @@ impl From<&str> for TaskPriority {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Исходная версия записывала статус как format!("{:?}").to_lowercase()
+    #[test]
+    fn legacy_in_progress_status_is_read() {
+        assert_eq!(TaskStatus::from("inprogress"), TaskStatus::InProgress);
+        assert_eq!(TaskStatus::from("in_progress"), TaskStatus::InProgress);
+    }
+}
@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+// Запись учета времени по задаче. Запись без ended_at - запущенный таймер
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct TimeEntry {
+    pub id: String,
+    pub task_id: String,
+    pub user_id: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub minutes: i64, // Для запущенного таймера 0
+    pub note: String,
+}
+
+impl TimeEntry {
+    // Запущенный таймер
+    pub fn start(task_id: String, user_id: String, started_at: DateTime<Utc>, note: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            task_id,
+            user_id,
+            started_at,
+            ended_at: None,
+            minutes: 0,
+            note,
+        }
+    }
+
+    // Завершенная запись за период
+    pub fn finished(
+        task_id: String,
+        user_id: String,
+        started_at: DateTime<Utc>,
+        ended_at: DateTime<Utc>,
+        note: String,
+    ) -> Self {
+        let mut entry = Self::start(task_id, user_id, started_at, note);
+        entry.stop(ended_at);
+        entry
+    }
+
+    // Длительность округляется до целых минут
+    pub fn stop(&mut self, ended_at: DateTime<Utc>) {
+        let seconds = (ended_at - self.started_at).num_seconds().max(0);
+        self.ended_at = Some(ended_at);
+        self.minutes = (seconds + 30) / 60;
+    }
+}
@@ -1,15 +1,34 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+use crate::calendar::timezone::{self, parse_time_zone};
+use crate::i18n::Locale;
+
+// Часовой пояс по умолчанию для новых и существующих пользователей
+pub const DEFAULT_TIME_ZONE: &str = "Europe/Moscow";
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct User {
     pub id: String,
     pub name: String,
     pub email: String,
     pub role: UserRole,
+    #[serde(default = "default_time_zone")]
+    pub time_zone: String, // Название пояса из базы IANA, например "Asia/Novosibirsk"
+    pub version: i64, // Номер версии для оптимистичной блокировки
+    pub deleted_at: Option<DateTime<Utc>>, // Время перемещения в корзину
+    #[serde(default = "default_active")]
+    pub active: bool, // Деактивированный пользователь не назначается на новые задачи
+    #[serde(default)]
+    pub deactivated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub locale: Option<Locale>, // Язык интерфейса и сообщений; None - по настройкам браузера
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
 pub enum UserRole {
     Executor,    // Исполнитель
     Customer,    // Заказчик
@@ -22,17 +41,49 @@ impl User {
             name,
             email,
             role,
+            time_zone: DEFAULT_TIME_ZONE.to_string(),
+            version: 1,
+            deleted_at: None,
+            active: true,
+            deactivated_at: None,
+            locale: None,
         }
     }
+
+    // Часовой пояс пользователя; некорректное значение заменяется поясом по умолчанию
+    pub fn tz(&self) -> Tz {
+        parse_time_zone(&self.time_zone).unwrap_or_else(timezone::default_time_zone)
+    }
 }
 
-impl std::fmt::Display for UserRole {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+fn default_time_zone() -> String {
+    DEFAULT_TIME_ZONE.to_string()
+}
+
+fn default_active() -> bool {
+    true
+}
+
+impl UserRole {
+    pub const ALL: [UserRole; 2] = [UserRole::Executor, UserRole::Customer];
+
+    // Строковое значение, используемое в API и базе данных
+    pub fn code(&self) -> &'static str {
         match self {
-            UserRole::Executor => write!(f, "Исполнитель"),
-            UserRole::Customer => write!(f, "Заказчик"),
+            UserRole::Executor => "executor",
+            UserRole::Customer => "customer",
         }
     }
+
+    pub fn label(&self, locale: Locale) -> String {
+        locale.t(&format!("role.{}", self.code()))
+    }
+}
+
+impl std::fmt::Display for UserRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label(Locale::default()))
+    }
 }
 
 impl From<&str> for UserRole {
@@ -0,0 +1,306 @@
+use actix_web::{web, HttpResponse, Responder, get};
+use std::sync::Arc;
+use tera::Tera;
+
+use crate::archive::ArchiveSettings;
+use crate::db::Repository;
+use crate::i18n::Locale;
+use crate::models::{Project, User, UserRole};
+use crate::validation;
+
+// Контекст шаблона с каталогом сообщений выбранного языка
+fn page_context(locale: Locale) -> tera::Context {
+    let mut ctx = tera::Context::new();
+    ctx.insert("lang", locale.code());
+    ctx.insert("tr", locale.catalog());
+    ctx.insert("rules", &validation::RULES);
+    ctx
+}
+
+#[get("/")]
+pub async fn index(
+    tmpl: web::Data<Tera>,
+    repo: web::Data<Arc<dyn Repository>>,
+    locale: Locale,
+) -> impl Responder {
+    let mut ctx = page_context(locale);
+    
+    // Получаем списки пользователей для выпадающих списков. Деактивированные
+    // пользователи не предлагаются, но нужны форме задачи для старых поручений
+    match repo.get_users() {
+        Ok(users) => {
+            let customers: Vec<&User> = users.iter()
+                .filter(|u| u.active && u.role == UserRole::Customer)
+                .collect();
+            
+            let executors: Vec<&User> = users.iter()
+                .filter(|u| u.active && u.role == UserRole::Executor)
+                .collect();
+            
+            let inactive_users: Vec<&User> = users.iter()
+                .filter(|u| !u.active)
+                .collect();
+            
+            ctx.insert("customers", &customers);
+            ctx.insert("executors", &executors);
+            ctx.insert("inactive_users", &inactive_users);
+        },
+        Err(e) => {
+            eprintln!("Ошибка при получении пользователей: {}", e);
+            ctx.insert("customers", &Vec::<User>::new());
+            ctx.insert("executors", &Vec::<User>::new());
+            ctx.insert("inactive_users", &Vec::<User>::new());
+        }
+    }
+    
+    // Проекты для фильтра и формы поручения
+    match repo.get_projects() {
+        Ok(projects) => {
+            ctx.insert("projects", &projects);
+        },
+        Err(e) => {
+            eprintln!("Ошибка при получении проектов: {}", e);
+            ctx.insert("projects", &Vec::<Project>::new());
+        }
+    }
+    
+    let rendered = match tmpl.render("index.html", &ctx) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("Ошибка рендеринга шаблона: {}", e);
+            return HttpResponse::InternalServerError().body(locale.t("errors.template"));
+        }
+    };
+    
+    HttpResponse::Ok().content_type("text/html").body(rendered)
+}
+
+#[get("/users")]
+pub async fn users_page(
+    tmpl: web::Data<Tera>,
+    repo: web::Data<Arc<dyn Repository>>,
+    locale: Locale,
+) -> impl Responder {
+    let mut ctx = page_context(locale);
+    
+    match repo.get_users() {
+        Ok(users) => {
+            ctx.insert("users", &users);
+        },
+        Err(e) => {
+            eprintln!("Ошибка при получении пользователей: {}", e);
+            ctx.insert("users", &Vec::<User>::new());
+        }
+    }
+    
+    let rendered = match tmpl.render("users.html", &ctx) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("Ошибка рендеринга шаблона: {}", e);
+            return HttpResponse::InternalServerError().body(locale.t("errors.template"));
+        }
+    };
+    
+    HttpResponse::Ok().content_type("text/html").body(rendered)
+}
+
+#[get("/projects")]
+pub async fn projects_page(
+    tmpl: web::Data<Tera>,
+    repo: web::Data<Arc<dyn Repository>>,
+    locale: Locale,
+) -> impl Responder {
+    let mut ctx = page_context(locale);
+    
+    // Пользователи для выбора руководителя и заказчика; проекты загружаются через /api/projects
+    match repo.get_users() {
+        Ok(users) => {
+            ctx.insert("users", &users);
+        },
+        Err(e) => {
+            eprintln!("Ошибка при получении пользователей: {}", e);
+            ctx.insert("users", &Vec::<User>::new());
+        }
+    }
+    
+    let rendered = match tmpl.render("projects.html", &ctx) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("Ошибка рендеринга шаблона: {}", e);
+            return HttpResponse::InternalServerError().body(locale.t("errors.template"));
+        }
+    };
+    
+    HttpResponse::Ok().content_type("text/html").body(rendered)
+}
+
+#[get("/projects/{id}")]
+pub async fn project_page(
+    tmpl: web::Data<Tera>,
+    repo: web::Data<Arc<dyn Repository>>,
+    path: web::Path<String>,
+    locale: Locale,
+) -> impl Responder {
+    let mut ctx = page_context(locale);
+    
+    // Ход выполнения и задачи проекта загружаются на странице через API
+    let project = match repo.get_project_by_id(&path.into_inner()) {
+        Ok(project) => project,
+        Err(_) => return HttpResponse::NotFound().body(locale.t("errors.page_not_found")),
+    };
+    ctx.insert("project", &project);
+    
+    match repo.get_users() {
+        Ok(users) => {
+            ctx.insert("users", &users);
+        },
+        Err(e) => {
+            eprintln!("Ошибка при получении пользователей: {}", e);
+            ctx.insert("users", &Vec::<User>::new());
+        }
+    }
+    
+    let rendered = match tmpl.render("project.html", &ctx) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("Ошибка рендеринга шаблона: {}", e);
+            return HttpResponse::InternalServerError().body(locale.t("errors.template"));
+        }
+    };
+    
+    HttpResponse::Ok().content_type("text/html").body(rendered)
+}
+
+#[get("/tags")]
+pub async fn tags_page(tmpl: web::Data<Tera>, locale: Locale) -> impl Responder {
+    let ctx = page_context(locale);
+    
+    // Список тегов загружается на странице через /api/tags
+    let rendered = match tmpl.render("tags.html", &ctx) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("Ошибка рендеринга шаблона: {}", e);
+            return HttpResponse::InternalServerError().body(locale.t("errors.template"));
+        }
+    };
+    
+    HttpResponse::Ok().content_type("text/html").body(rendered)
+}
+
+#[get("/trash")]
+pub async fn trash_page(tmpl: web::Data<Tera>, locale: Locale) -> impl Responder {
+    let ctx = page_context(locale);
+    
+    // Содержимое корзины загружается на странице через /api/trash
+    let rendered = match tmpl.render("trash.html", &ctx) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("Ошибка рендеринга шаблона: {}", e);
+            return HttpResponse::InternalServerError().body(locale.t("errors.template"));
+        }
+    };
+    
+    HttpResponse::Ok().content_type("text/html").body(rendered)
+}
+
+#[get("/board")]
+pub async fn board_page(
+    tmpl: web::Data<Tera>,
+    repo: web::Data<Arc<dyn Repository>>,
+    locale: Locale,
+) -> impl Responder {
+    let mut ctx = page_context(locale);
+    
+    // Пользователи и проекты для фильтров; доска загружается через /api/board
+    match repo.get_users() {
+        Ok(users) => {
+            ctx.insert("users", &users);
+        },
+        Err(e) => {
+            eprintln!("Ошибка при получении пользователей: {}", e);
+            ctx.insert("users", &Vec::<User>::new());
+        }
+    }
+    
+    match repo.get_projects() {
+        Ok(projects) => {
+            ctx.insert("projects", &projects);
+        },
+        Err(e) => {
+            eprintln!("Ошибка при получении проектов: {}", e);
+            ctx.insert("projects", &Vec::<Project>::new());
+        }
+    }
+    
+    let rendered = match tmpl.render("board.html", &ctx) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("Ошибка рендеринга шаблона: {}", e);
+            return HttpResponse::InternalServerError().body(locale.t("errors.template"));
+        }
+    };
+    
+    HttpResponse::Ok().content_type("text/html").body(rendered)
+}
+
+#[get("/timesheet")]
+pub async fn timesheet_page(
+    tmpl: web::Data<Tera>,
+    repo: web::Data<Arc<dyn Repository>>,
+    locale: Locale,
+) -> impl Responder {
+    let mut ctx = page_context(locale);
+    
+    // Пользователи для фильтра; табель загружается через /api/reports/timesheet
+    match repo.get_users() {
+        Ok(users) => {
+            ctx.insert("users", &users);
+        },
+        Err(e) => {
+            eprintln!("Ошибка при получении пользователей: {}", e);
+            ctx.insert("users", &Vec::<User>::new());
+        }
+    }
+    
+    let rendered = match tmpl.render("timesheet.html", &ctx) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("Ошибка рендеринга шаблона: {}", e);
+            return HttpResponse::InternalServerError().body(locale.t("errors.template"));
+        }
+    };
+    
+    HttpResponse::Ok().content_type("text/html").body(rendered)
+}
+
+#[get("/archive")]
+pub async fn archive_page(
+    tmpl: web::Data<Tera>,
+    repo: web::Data<Arc<dyn Repository>>,
+    settings: web::Data<ArchiveSettings>,
+    locale: Locale,
+) -> impl Responder {
+    let mut ctx = page_context(locale);
+    ctx.insert("archive_after_days", &settings.after_days);
+    
+    // Пользователи для фильтров; архивные задачи загружаются через /api/archive
+    match repo.get_users() {
+        Ok(users) => {
+            ctx.insert("users", &users);
+        },
+        Err(e) => {
+            eprintln!("Ошибка при получении пользователей: {}", e);
+            ctx.insert("users", &Vec::<User>::new());
+        }
+    }
+    
+    let rendered = match tmpl.render("archive.html", &ctx) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("Ошибка рендеринга шаблона: {}", e);
+            return HttpResponse::InternalServerError().body(locale.t("errors.template"));
+        }
+    };
+    
+    HttpResponse::Ok().content_type("text/html").body(rendered)
+}
@@ -0,0 +1,32 @@
+use std::sync::Arc;
+
+use crate::clock::Clock;
+use crate::db::Repository;
+use crate::jobs;
+
+// Срок хранения записей в корзине (TRASH_RETENTION_DAYS, по умолчанию 30 дней).
+// 0 отключает автоматическую очистку
+#[derive(Debug, Clone, Copy)]
+pub struct TrashSettings {
+    pub retention_days: Option<u32>,
+}
+
+impl TrashSettings {
+    pub const DEFAULT_RETENTION_DAYS: u32 = 30;
+
+    pub fn from_env() -> Result<Self, String> {
+        let retention_days = jobs::days_from_env("TRASH_RETENTION_DAYS", Self::DEFAULT_RETENTION_DAYS)?;
+        Ok(Self { retention_days })
+    }
+}
+
+// Фоновая очистка корзины: записи старше срока хранения удаляются окончательно
+pub fn spawn_purge(repo: Arc<dyn Repository>, clock: Arc<dyn Clock>, settings: TrashSettings) {
+    jobs::spawn_periodic(
+        settings.retention_days,
+        clock,
+        "Корзина очищена, удалено записей",
+        "Ошибка очистки корзины",
+        move |cutoff, _| repo.purge_deleted(cutoff),
+    );
+}
@@ -0,0 +1,223 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::i18n::Locale;
+
+// Ограничения полей; те же значения передаются в шаблоны форм (maxlength),
+// чтобы браузер и API проверяли данные по одним правилам
+pub const TITLE_MAX_LEN: usize = 200;
+pub const DESCRIPTION_MAX_LEN: usize = 10_000;
+pub const USER_NAME_MAX_LEN: usize = 100;
+pub const EMAIL_MAX_LEN: usize = 254;
+pub const TAG_NAME_MAX_LEN: usize = 50;
+pub const TAGS_MAX: usize = 20;
+pub const SLA_DAYS_MAX: u32 = 365; // Срок по SLA считается по дням, поэтому ограничен
+pub const TIME_ENTRY_NOTE_MAX_LEN: usize = 1000;
+pub const TIME_ENTRY_MAX_MINUTES: i64 = 24 * 60; // Одна запись - не больше суток
+
+// Ограничения для шаблонов: {{ rules.title_max_len }}
+#[derive(Serialize)]
+pub struct Rules {
+    pub title_max_len: usize,
+    pub description_max_len: usize,
+    pub user_name_max_len: usize,
+    pub email_max_len: usize,
+    pub tag_name_max_len: usize,
+    pub tags_max: usize,
+    pub time_entry_note_max_len: usize,
+    pub time_entry_max_minutes: i64,
+}
+
+pub const RULES: Rules = Rules {
+    title_max_len: TITLE_MAX_LEN,
+    description_max_len: DESCRIPTION_MAX_LEN,
+    user_name_max_len: USER_NAME_MAX_LEN,
+    email_max_len: EMAIL_MAX_LEN,
+    tag_name_max_len: TAG_NAME_MAX_LEN,
+    tags_max: TAGS_MAX,
+    time_entry_note_max_len: TIME_ENTRY_NOTE_MAX_LEN,
+    time_entry_max_minutes: TIME_ENTRY_MAX_MINUTES,
+};
+
+// Ошибка одного поля. code - стабильный машиночитаемый код (required, too_long,
+// invalid_email, in_past, too_many, too_large), message - ключ каталога "validation.<code>",
+// переводится при формировании ответа
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub code: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>, // Допустимый предел для too_long, too_many и too_large
+}
+
+impl FieldError {
+    fn new(field: &str, code: &str, limit: Option<usize>) -> Self {
+        Self {
+            field: field.to_string(),
+            code: code.to_string(),
+            message: format!("validation.{}", code),
+            limit,
+        }
+    }
+
+    pub fn localized(&self, locale: Locale) -> Self {
+        let mut message = locale.t(&self.message);
+        if let Some(limit) = self.limit {
+            message = message.replace("{limit}", &limit.to_string());
+        }
+        Self { message, ..self.clone() }
+    }
+}
+
+// Собирает ошибки всех полей запроса, чтобы вернуть их одним ответом
+#[derive(Debug, Default)]
+pub struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn required(&mut self, field: &str, value: &str) -> &mut Self {
+        if value.trim().is_empty() {
+            self.errors.push(FieldError::new(field, "required", None));
+        }
+        self
+    }
+
+    // Длина считается в символах, а не в байтах
+    pub fn max_length(&mut self, field: &str, value: &str, max: usize) -> &mut Self {
+        if value.chars().count() > max {
+            self.errors.push(FieldError::new(field, "too_long", Some(max)));
+        }
+        self
+    }
+
+    pub fn email(&mut self, field: &str, value: &str) -> &mut Self {
+        if !value.trim().is_empty() && !is_valid_email(value) {
+            self.errors.push(FieldError::new(field, "invalid_email", None));
+        }
+        self
+    }
+
+    pub fn not_in_past(&mut self, field: &str, value: Option<DateTime<Utc>>, now: DateTime<Utc>) -> &mut Self {
+        if value.is_some_and(|value| value < now) {
+            self.errors.push(FieldError::new(field, "in_past", None));
+        }
+        self
+    }
+
+    pub fn max_items(&mut self, field: &str, count: usize, max: usize) -> &mut Self {
+        if count > max {
+            self.errors.push(FieldError::new(field, "too_many", Some(max)));
+        }
+        self
+    }
+
+    pub fn max_value(&mut self, field: &str, value: i64, max: i64) -> &mut Self {
+        if value > max {
+            self.errors.push(FieldError::new(field, "too_large", usize::try_from(max).ok()));
+        }
+        self
+    }
+
+    pub fn finish(self) -> Result<(), Vec<FieldError>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+// Правила полей, общие для всех запросов с этими полями
+
+pub fn task_title(v: &mut Validator, title: &str) {
+    v.required("title", title).max_length("title", title, TITLE_MAX_LEN);
+}
+
+pub fn task_description(v: &mut Validator, description: &str) {
+    v.max_length("description", description, DESCRIPTION_MAX_LEN);
+}
+
+pub fn task_tags(v: &mut Validator, tags: &[String]) {
+    v.max_items("tags", tags.len(), TAGS_MAX);
+    if let Some(tag) = tags.iter().find(|tag| tag.trim().chars().count() > TAG_NAME_MAX_LEN) {
+        tag_name(v, "tags", tag);
+    }
+}
+
+// Имя тега - и в задаче, и в справочнике тегов
+pub fn tag_name(v: &mut Validator, field: &str, name: &str) {
+    v.max_length(field, name.trim(), TAG_NAME_MAX_LEN);
+}
+
+pub fn task_sla_days(v: &mut Validator, sla_days: Option<u32>) {
+    if let Some(days) = sla_days {
+        v.max_value("sla_days", days.into(), SLA_DAYS_MAX.into());
+    }
+}
+
+pub fn user_name(v: &mut Validator, name: &str) {
+    v.required("name", name).max_length("name", name, USER_NAME_MAX_LEN);
+}
+
+pub fn user_email(v: &mut Validator, email: &str) {
+    v.required("email", email).max_length("email", email, EMAIL_MAX_LEN).email("email", email);
+}
+
+pub fn time_entry_note(v: &mut Validator, note: &str) {
+    v.max_length("note", note, TIME_ENTRY_NOTE_MAX_LEN);
+}
+
+// Длительность записи учета времени в минутах
+pub fn time_entry_minutes(v: &mut Validator, field: &str, minutes: i64) {
+    v.max_value(field, minutes, TIME_ENTRY_MAX_MINUTES);
+}
+
+// Простая проверка вида local@domain.tld без пробелов; полную проверку
+// адреса выполняет только отправка письма
+fn is_valid_email(value: &str) -> bool {
+    let Some((local, domain)) = value.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !domain.contains('@')
+        && !value.chars().any(char::is_whitespace)
+        && domain.split('.').count() >= 2
+        && domain.split('.').all(|part| !part.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_plain_addresses() {
+        assert!(is_valid_email("ivanov@example.com"));
+        assert!(is_valid_email("i.petrov+tasks@mail.example.ru"));
+    }
+
+    #[test]
+    fn rejects_malformed_addresses() {
+        for value in [
+            "",
+            "ivanov",
+            "@example.com",
+            "ivanov@",
+            "ivanov@example",
+            "ivanov@example.",
+            "ivanov@.example.com",
+            "ivanov@example..com",
+            "ivanov@@example.com",
+            "iva nov@example.com",
+            "ivanov@example.com ",
+        ] {
+            assert!(!is_valid_email(value), "{:?}", value);
+        }
+    }
+}
@@ -0,0 +1,125 @@
+// Документация API (templates/api_docs.html): страница строится по
+// спецификации /api/openapi.json без сторонних библиотек
+const METHODS = ['get', 'post', 'put', 'patch', 'delete'];
+
+function el(tag, className, text) {
+    const node = document.createElement(tag);
+    if (className) node.className = className;
+    if (text !== undefined) node.textContent = text;
+    return node;
+}
+
+// Краткая запись схемы: имя компонента, массив или тип
+function schemaName(schema) {
+    if (!schema) return '';
+    if (schema.$ref) return schema.$ref.split('/').pop();
+    if (schema.type === 'array') return '[' + schemaName(schema.items) + ']';
+    if (schema.oneOf) return schema.oneOf.map(schemaName).join(' | ');
+    if (schema.allOf) return schema.allOf.map(schemaName).join(' & ');
+    const type = Array.isArray(schema.type) ? schema.type.join(' | ') : (schema.type || 'object');
+    return schema.format ? type + ' (' + schema.format + ')' : type;
+}
+
+function bodySchema(content) {
+    const json = content && content['application/json'];
+    return json ? schemaName(json.schema) : '';
+}
+
+function renderOperation(path, method, op) {
+    const block = el('details', 'operation operation-' + method);
+    const summary = el('summary');
+    summary.appendChild(el('span', 'method', method.toUpperCase()));
+    summary.appendChild(el('code', 'path', path));
+    if (op.summary) summary.appendChild(el('span', 'summary', op.summary));
+    block.appendChild(summary);
+
+    if (op.description) block.appendChild(el('p', 'description', op.description));
+
+    if (op.parameters && op.parameters.length) {
+        block.appendChild(el('h4', null, 'Параметры'));
+        const list = el('ul');
+        op.parameters.forEach(param => {
+            const item = el('li');
+            item.appendChild(el('code', null, param.name));
+            item.appendChild(document.createTextNode(
+                ' (' + param.in + ', ' + schemaName(param.schema) + (param.required ? ', обязательный' : '') + ')'
+                + (param.description ? ' - ' + param.description : '')));
+            list.appendChild(item);
+        });
+        block.appendChild(list);
+    }
+
+    if (op.requestBody) {
+        block.appendChild(el('h4', null, 'Тело запроса'));
+        block.appendChild(el('code', null, bodySchema(op.requestBody.content)));
+    }
+
+    block.appendChild(el('h4', null, 'Ответы'));
+    const responses = el('ul');
+    Object.entries(op.responses || {}).forEach(([status, response]) => {
+        const item = el('li');
+        item.appendChild(el('strong', null, status));
+        item.appendChild(document.createTextNode(' ' + (response.description || '')));
+        const body = bodySchema(response.content);
+        if (body) {
+            item.appendChild(document.createTextNode(' '));
+            item.appendChild(el('code', null, body));
+        }
+        responses.appendChild(item);
+    });
+    block.appendChild(responses);
+    return block;
+}
+
+function renderSchema(name, schema) {
+    const block = el('details', 'schema');
+    block.id = 'schema-' + name;
+    block.appendChild(el('summary', null, name));
+    const required = schema.required || [];
+    const list = el('ul');
+    Object.entries(schema.properties || {}).forEach(([field, prop]) => {
+        const item = el('li');
+        item.appendChild(el('code', null, field));
+        item.appendChild(document.createTextNode(
+            ': ' + schemaName(prop) + (required.includes(field) ? '' : ' (необязательное)')));
+        list.appendChild(item);
+    });
+    if (schema.enum) list.appendChild(el('li', null, schema.enum.join(', ')));
+    block.appendChild(list);
+    return block;
+}
+
+function renderSpec(spec) {
+    const root = document.getElementById('api-docs');
+    root.textContent = '';
+    root.appendChild(el('h1', null, spec.info.title + ' ' + spec.info.version));
+
+    // Операции группируются по тегам в порядке появления
+    const groups = new Map();
+    Object.entries(spec.paths || {}).forEach(([path, item]) => {
+        METHODS.filter(method => item[method]).forEach(method => {
+            const tag = (item[method].tags || ['default'])[0];
+            if (!groups.has(tag)) groups.set(tag, []);
+            groups.get(tag).push(renderOperation(path, method, item[method]));
+        });
+    });
+    groups.forEach((operations, tag) => {
+        const section = el('section', 'tag');
+        section.appendChild(el('h2', null, tag));
+        operations.forEach(op => section.appendChild(op));
+        root.appendChild(section);
+    });
+
+    const schemas = (spec.components && spec.components.schemas) || {};
+    const section = el('section', 'tag');
+    section.appendChild(el('h2', null, 'Схемы'));
+    Object.keys(schemas).sort().forEach(name => section.appendChild(renderSchema(name, schemas[name])));
+    root.appendChild(section);
+}
+
+fetch('/api/openapi.json')
+    .then(response => response.json())
+    .then(renderSpec)
+    .catch(error => {
+        document.getElementById('api-docs').textContent = 'Не удалось загрузить спецификацию: ' + error;
+    });
@@ -21,13 +21,46 @@ const filterStatus = document.getElementById('filterStatus');
 const filterPriority = document.getElementById('filterPriority');
 const filterCustomer = document.getElementById('filterCustomer');
 const filterExecutor = document.getElementById('filterExecutor');
+const filterProject = document.getElementById('filterProject');
+const filterParticipant = document.getElementById('filterParticipant');
 const filterTags = document.getElementById('filterTags');
 const filterSearch = document.getElementById('filterSearch');
+const filterSort = document.getElementById('filterSort');
+const columnToggles = document.querySelectorAll('#columnToggles input');
 const applyFiltersBtn = document.getElementById('applyFilters');
 const resetFiltersBtn = document.getElementById('resetFilters');
 
+// Сохраненные виды
+const viewUserSelect = document.getElementById('viewUser');
+const viewSelect = document.getElementById('viewSelect');
+const viewSharedCheckbox = document.getElementById('viewShared');
+let views = [];
+let viewSnapshot = null; // Состояние списка в момент выбора вида
+
+// Отмеченные для группового изменения поручения
+const selectedTaskIds = new Set();
+const bulkBar = document.getElementById('bulkBar');
+const bulkOperation = document.getElementById('bulkOperation');
+const selectAllTasks = document.getElementById('selectAllTasks');
+
+// Параметры адреса страницы и соответствующие им поля фильтров
+const FILTER_PARAMS = {
+    status: filterStatus,
+    priority: filterPriority,
+    customer_id: filterCustomer,
+    executor_id: filterExecutor,
+    participant_id: filterParticipant,
+    project_id: filterProject,
+    tags: filterTags,
+    search: filterSearch,
+    sort: filterSort,
+};
+
 // Инициализация при загрузке страницы
 document.addEventListener('DOMContentLoaded', () => {
+    // Скрипт подключается на всех страницах, но работает только со списком задач
+    if (!taskTableBody) return;
+    
     // Загрузка данных
     loadTasks();
     loadTags();
@@ -35,6 +68,9 @@ document.addEventListener('DOMContentLoaded', () => {
     // Обработчики событий для модальных окон
     setupEventListeners();
     
+    // Вид из адреса страницы или вид пользователя по умолчанию
+    initViews();
+    
     // Запускаем таймер для обновления информации о просроченных днях
     setInterval(updateOverdueDays, 60000); // Обновляем каждую минуту
 });
@@ -44,15 +80,19 @@ function loadTasks() {
     fetch('/api/tasks')
         .then(response => {
             if (!response.ok) {
-                throw new Error('Не удалось загрузить задачи');
+                throw new Error(TR.js.load_tasks_failed);
             }
             return response.json();
         })
         .then(data => {
             tasks = data;
+            // Отметки снимаются с поручений, которых больше нет в списке
+            selectedTaskIds.forEach(id => {
+                if (!tasks.some(task => task.id === id)) selectedTaskIds.delete(id);
+            });
             renderTasksTable();
         })
-        .catch(error => showNotification('Ошибка: ' + error.message, 'danger'));
+        .catch(error => showNotification(`${TR.js.error}: ${error.message}`, 'danger'));
 }
 
 // Загрузка тегов с сервера
@@ -60,7 +100,7 @@ function loadTags() {
     fetch('/api/tags')
         .then(response => {
             if (!response.ok) {
-                throw new Error('Не удалось загрузить теги');
+                throw new Error(TR.js.load_tags_failed);
             }
             return response.json();
         })
@@ -68,13 +108,13 @@ function loadTags() {
             tags = data;
             updateAvailableTags();
         })
-        .catch(error => showNotification('Ошибка: ' + error.message, 'danger'));
+        .catch(error => showNotification(`${TR.js.error}: ${error.message}`, 'danger'));
 }
 
 // Обновление отображения доступных тегов
 function updateAvailableTags() {
     if (tags.length === 0) {
-        availableTagsSpan.textContent = 'нет доступных тегов';
+        availableTagsSpan.textContent = TR.js.no_tags;
         return;
     }
     
@@ -86,9 +126,13 @@ function setupEventListeners() {
     // Обработчик для кнопки "Новое поручение"
     document.querySelector('[data-bs-target="#taskModal"]').addEventListener('click', () => {
         resetTaskForm();
-        document.getElementById('taskModalLabel').textContent = 'Новое поручение';
+        document.getElementById('taskModalLabel').textContent = TR.tasks.new;
         document.getElementById('taskStatus').disabled = true;
         document.getElementById('taskStatus').value = 'new';
+        document.getElementById('participantsSection').hidden = true;
+        document.getElementById('delegationSection').hidden = true;
+        document.getElementById('timeSection').hidden = true;
+        document.getElementById('taskExecutor').disabled = false;
     });
     
     // Обработчик для кнопки "Сохранить"
@@ -97,6 +141,14 @@ function setupEventListeners() {
     // Обработчик для подтверждения удаления
     confirmDeleteBtn.addEventListener('click', deleteTask);
     
+    // Обработчик для передачи поручения другому исполнителю
+    document.getElementById('reassignBtn').addEventListener('click', reassignTask);
+    
+    // Обработчики учета времени
+    document.getElementById('addTimeBtn').addEventListener('click', addTimeEntry);
+    document.getElementById('startTimerBtn').addEventListener('click', () => switchTimer('start'));
+    document.getElementById('stopTimerBtn').addEventListener('click', () => switchTimer('stop'));
+    
     // Обработчик для добавления тега
     addTagBtn.addEventListener('click', addTag);
     
@@ -108,20 +160,59 @@ function setupEventListeners() {
         }
     });
     
+    // Обработчики для участников задачи
+    document.getElementById('addCoExecutorBtn').addEventListener('click', () =>
+        addParticipant('co-executors', document.getElementById('coExecutorSelect').value));
+    document.getElementById('addWatcherBtn').addEventListener('click', () =>
+        addParticipant('watchers', document.getElementById('watcherSelect').value));
+    
     // Обработчики для фильтров
     applyFiltersBtn.addEventListener('click', applyFilters);
     resetFiltersBtn.addEventListener('click', resetFilters);
+    
+    // Обработчики сохраненных видов
+    viewUserSelect.addEventListener('change', () => {
+        localStorage.setItem('viewUserId', viewUserSelect.value);
+        loadViews();
+    });
+    viewSelect.addEventListener('change', () => selectView(viewSelect.value));
+    document.getElementById('saveViewBtn').addEventListener('click', saveViewAs);
+    document.getElementById('updateViewBtn').addEventListener('click', updateView);
+    document.getElementById('defaultViewBtn').addEventListener('click', makeDefaultView);
+    document.getElementById('copyViewLinkBtn').addEventListener('click', copyViewLink);
+    document.getElementById('deleteViewBtn').addEventListener('click', deleteView);
+    
+    // Обработчики групповых действий
+    selectAllTasks.addEventListener('change', () => {
+        filterTasks().forEach(task => {
+            if (selectAllTasks.checked) {
+                selectedTaskIds.add(task.id);
+            } else {
+                selectedTaskIds.delete(task.id);
+            }
+        });
+        renderTasksTable();
+    });
+    bulkOperation.addEventListener('change', updateBulkBar);
+    document.getElementById('bulkApplyBtn').addEventListener('click', applyBulkOperation);
+    document.getElementById('bulkClearBtn').addEventListener('click', () => {
+        selectedTaskIds.clear();
+        renderTasksTable();
+    });
 }
 
 // Отображение задач в таблице
 function renderTasksTable() {
     taskTableBody.innerHTML = '';
     
-    const filteredTasks = filterTasks();
+    const filteredTasks = sortTasks(filterTasks());
+    applyColumnVisibility();
+    updateBulkBar();
+    selectAllTasks.checked = filteredTasks.length > 0 && filteredTasks.every(task => selectedTaskIds.has(task.id));
     
     if (filteredTasks.length === 0) {
         const emptyRow = document.createElement('tr');
-        emptyRow.innerHTML = '<td colspan="9" class="text-center py-3">Нет доступных поручений</td>';
+        emptyRow.innerHTML = `<td colspan="10" class="text-center py-3">${TR.js.no_tasks}</td>`;
         taskTableBody.appendChild(emptyRow);
         return;
     }
@@ -129,20 +220,15 @@ function renderTasksTable() {
     filteredTasks.forEach(task => {
         const row = document.createElement('tr');
         
-        // Вычисляем количество просроченных дней
+        // Просрочка в календарных и рабочих днях вычисляется на сервере
         let overdueDays = '';
         let overdueClass = '';
         
-        if (task.status !== 'completed' && task.status !== 'cancelled' && task.due_date) {
-            const dueDate = new Date(task.due_date);
-            const now = new Date();
-            
-            if (now > dueDate) {
-                const diffTime = Math.abs(now - dueDate);
-                const diffDays = Math.ceil(diffTime / (1000 * 60 * 60 * 24));
-                overdueDays = `${diffDays} ${getDayWordForm(diffDays)}`;
-                overdueClass = 'overdue';
-            }
+        if (task.overdue_days !== null && task.overdue_days !== undefined) {
+            const diffDays = task.overdue_days;
+            const workingDays = task.overdue_working_days || 0;
+            overdueDays = `${diffDays} ${getDayWordForm(diffDays)} (${TR.js.working_days}: ${workingDays})`;
+            overdueClass = 'overdue';
         }
         
         // Находим имена заказчика и исполнителя
@@ -151,29 +237,36 @@ function renderTasksTable() {
         
         // Формируем HTML для тегов
         const tagsHtml = task.tags.map(tag => 
-            `<span class="tag-badge">${tag.name}</span>`
+            `<span class="tag-badge"${tagStyle(tag)}>${tag.name}</span>`
         ).join('');
         
         // Формируем статус и приоритет с соответствующими стилями
         const statusText = getStatusText(task.status);
         const priorityText = getPriorityText(task.priority);
-        const statusClass = `status-${task.status}`;
-        const priorityClass = `priority-${task.priority}`;
+        const statusClass = `status-${toCode(task.status)}`;
+        const priorityClass = `priority-${toCode(task.priority)}`;
         
         // Формируем дату выполнения
-        const dueDateText = task.due_date 
-            ? new Date(task.due_date).toLocaleString('ru-RU')
-            : 'Не указан';
+        // Дата уже переведена сервером в часовой пояс исполнителя
+        const dueDateText = task.due_date_local 
+            ? `${task.due_date_local} (${task.time_zone})`
+            : TR.js.not_specified;
+        
+        // Соисполнители показываются числом рядом с исполнителем
+        const coExecutorsHtml = task.co_executor_ids.length
+            ? ` <span class="badge bg-secondary" title="${TR.tasks.co_executors}">+${task.co_executor_ids.length}</span>`
+            : '';
         
         row.innerHTML = `
-            <td>${task.title}</td>
-            <td><span class="badge ${statusClass}">${statusText}</span></td>
-            <td><span class="badge ${priorityClass}">${priorityText}</span></td>
-            <td>${customerName}</td>
-            <td>${executorName}</td>
-            <td>${dueDateText}</td>
-            <td class="${overdueClass}">${overdueDays}</td>
-            <td>${tagsHtml}</td>
+            <td><input type="checkbox" class="form-check-input task-select" data-id="${task.id}" ${selectedTaskIds.has(task.id) ? 'checked' : ''}></td>
+            <td data-column="title">${task.title}</td>
+            <td data-column="status"><span class="badge ${statusClass}">${statusText}</span></td>
+            <td data-column="priority"><span class="badge ${priorityClass}">${priorityText}</span></td>
+            <td data-column="customer">${customerName}</td>
+            <td data-column="executor">${executorName}${coExecutorsHtml}</td>
+            <td data-column="due">${dueDateText}</td>
+            <td data-column="overdue" class="${overdueClass}">${overdueDays}</td>
+            <td data-column="tags">${tagsHtml}</td>
             <td>
                 <button class="btn btn-sm btn-outline-primary action-btn edit-task-btn" data-id="${task.id}">
                     <i class="bi bi-pencil"></i>
@@ -184,8 +277,41 @@ function renderTasksTable() {
             </td>
         `;
         
+        // Описание тега - подсказка к нему; задается через DOM, чтобы кавычки
+        // в описании не выходили за пределы атрибута
+        row.querySelectorAll('[data-column="tags"] .tag-badge').forEach((badge, i) => {
+            badge.title = task.tags[i].description || '';
+        });
+        
+        // Название проекта берется из фильтра, заполненного сервером;
+        // оно задается текстом, чтобы разметка в названии не выполнялась
+        const projectOption = task.project_id
+            ? filterProject.querySelector(`option[value="${task.project_id}"]`)
+            : null;
+        if (projectOption) {
+            const link = document.createElement('a');
+            link.href = `/projects/${task.project_id}`;
+            link.className = 'small text-muted';
+            link.textContent = projectOption.textContent;
+            const wrapper = document.createElement('div');
+            wrapper.appendChild(link);
+            row.querySelector('[data-column="title"]').appendChild(wrapper);
+        }
+        
         taskTableBody.appendChild(row);
     });
+    applyColumnVisibility();
+    
+    document.querySelectorAll('.task-select').forEach(checkbox => {
+        checkbox.addEventListener('change', () => {
+            if (checkbox.checked) {
+                selectedTaskIds.add(checkbox.dataset.id);
+            } else {
+                selectedTaskIds.delete(checkbox.dataset.id);
+            }
+            updateBulkBar();
+        });
+    });
     
     // Добавляем обработчики для кнопок редактирования и удаления
     document.querySelectorAll('.edit-task-btn').forEach(btn => {
@@ -201,12 +327,12 @@ function renderTasksTable() {
 function filterTasks() {
     return tasks.filter(task => {
         // Фильтр по статусу
-        if (filterStatus.value && task.status !== filterStatus.value) {
+        if (filterStatus.value && toCode(task.status) !== filterStatus.value) {
             return false;
         }
         
         // Фильтр по приоритету
-        if (filterPriority.value && task.priority !== filterPriority.value) {
+        if (filterPriority.value && toCode(task.priority) !== filterPriority.value) {
             return false;
         }
         
@@ -220,6 +346,19 @@ function filterTasks() {
             return false;
         }
         
+        // Фильтр по участнику: заказчик, исполнитель, соисполнитель или наблюдатель
+        if (filterParticipant.value && !isParticipant(task, filterParticipant.value)) {
+            return false;
+        }
+        
+        // Фильтр по проекту; "none" - задачи вне проектов
+        if (filterProject.value === 'none' && task.project_id) {
+            return false;
+        }
+        if (filterProject.value && filterProject.value !== 'none' && task.project_id !== filterProject.value) {
+            return false;
+        }
+        
         // Фильтр по тегам
         if (filterTags.value.trim()) {
             const filterTagsList = filterTags.value.split(',').map(tag => tag.trim().toLowerCase());
@@ -243,9 +382,124 @@ function filterTasks() {
     });
 }
 
+// Панель групповых действий видна, пока отмечено хотя бы одно поручение;
+// показываются только поля выбранного действия
+function updateBulkBar() {
+    bulkBar.hidden = selectedTaskIds.size === 0;
+    document.getElementById('bulkCount').textContent = TR.bulk.selected.replace('{count}', selectedTaskIds.size);
+    bulkBar.querySelectorAll('[data-bulk]').forEach(field => {
+        field.hidden = !field.dataset.bulk.split(' ').includes(bulkOperation.value);
+    });
+}
+
+// Изменение применяется ко всем отмеченным поручениям или ни к одному
+function applyBulkOperation() {
+    const type = bulkOperation.value;
+    const operation = { type };
+    const tagNames = () => document.getElementById('bulkTags').value.split(',').map(tag => tag.trim()).filter(Boolean);
+    
+    switch (type) {
+        case 'set_status':
+            operation.status = document.getElementById('bulkStatus').value;
+            break;
+        case 'set_priority':
+            operation.priority = document.getElementById('bulkPriority').value;
+            break;
+        case 'reassign':
+            operation.to_user_id = document.getElementById('bulkTo').value;
+            operation.by_user_id = document.getElementById('bulkBy').value;
+            operation.reason = document.getElementById('bulkReason').value;
+            break;
+        case 'add_tags':
+        case 'remove_tags':
+            operation.tags = tagNames();
+            break;
+        case 'set_due_date':
+            // Пустая дата снимает срок
+            operation.due_date = document.getElementById('bulkDueDate').value || null;
+            break;
+        case 'delete':
+            if (!confirm(TR.bulk.delete_confirm.replace('{count}', selectedTaskIds.size))) return;
+            break;
+    }
+    
+    fetch('/api/tasks/bulk', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ ids: [...selectedTaskIds], operation }),
+    })
+    .then(response => response.json()
+        .catch(() => ({}))
+        .then(body => ({ response, body })))
+    .then(({ response, body }) => {
+        // 409: часть поручений изменить нельзя, не изменено ни одно
+        if (response.status === 409 && body.results) {
+            const reasons = body.results
+                .filter(result => !result.ok)
+                .map(result => {
+                    const task = tasks.find(t => t.id === result.id);
+                    return `${task ? task.title : result.id}: ${result.error}`;
+                });
+            showNotification(`${TR.js.bulk_rejected}<br>${reasons.join('<br>')}`, 'danger');
+            return;
+        }
+        if (!response.ok) {
+            throw new Error(body.error || TR.js.bulk_failed);
+        }
+        
+        selectedTaskIds.clear();
+        loadTasks();
+        loadTags();
+        showNotification(TR.js.bulk_applied.replace('{count}', body.results.length), 'success');
+    })
+    .catch(error => showNotification(`${TR.js.error}: ${error.message}`, 'danger'));
+}
+
+// Сортировка списка; "-" в начале поля - по убыванию
+const PRIORITY_ORDER = ['low', 'medium', 'high', 'critical'];
+const STATUS_ORDER = ['new', 'in_progress', 'completed', 'cancelled'];
+
+function sortTasks(list) {
+    const sort = filterSort.value;
+    if (!sort) return list;
+    
+    const descending = sort.startsWith('-');
+    const field = descending ? sort.slice(1) : sort;
+    const key = task => {
+        switch (field) {
+            case 'priority': return PRIORITY_ORDER.indexOf(toCode(task.priority));
+            case 'status': return STATUS_ORDER.indexOf(toCode(task.status));
+            case 'overdue': return task.overdue_days ?? -1;
+            case 'title': return task.title.toLowerCase();
+            // Поручения без срока - в конце списка
+            case 'due_date': return task.due_date || '9999';
+            default: return task[field];
+        }
+    };
+    
+    return [...list].sort((a, b) => {
+        const order = key(a) < key(b) ? -1 : key(a) > key(b) ? 1 : 0;
+        return descending ? -order : order;
+    });
+}
+
+// Видимые колонки; пустой список означает все колонки
+function visibleColumns() {
+    const checked = [...columnToggles].filter(input => input.checked).map(input => input.value);
+    return checked.length === columnToggles.length ? [] : checked;
+}
+
+function applyColumnVisibility() {
+    const columns = visibleColumns();
+    document.querySelectorAll('th[data-column], td[data-column]').forEach(cell => {
+        cell.hidden = columns.length > 0 && !columns.includes(cell.dataset.column);
+    });
+}
+
 // Применение фильтров
 function applyFilters() {
     renderTasksTable();
+    updateUrl();
 }
 
 // Сброс фильтров
@@ -254,9 +508,268 @@ function resetFilters() {
     filterPriority.value = '';
     filterCustomer.value = '';
     filterExecutor.value = '';
+    filterProject.value = '';
+    filterParticipant.value = '';
     filterTags.value = '';
     filterSearch.value = '';
+    filterSort.value = '';
+    columnToggles.forEach(input => { input.checked = true; });
+    viewSelect.value = '';
+    viewSnapshot = null;
     renderTasksTable();
+    updateUrl();
+}
+
+// Состояние списка в том виде, в каком оно сохраняется в виде
+function currentViewState() {
+    const value = input => input.value.trim() || null;
+    return {
+        filters: {
+            status: value(filterStatus),
+            priority: value(filterPriority),
+            customer_id: value(filterCustomer),
+            executor_id: value(filterExecutor),
+            participant_id: value(filterParticipant),
+            project_id: value(filterProject),
+            tags: filterTags.value.split(',').map(tag => tag.trim()).filter(Boolean),
+            search: value(filterSearch),
+        },
+        sort: value(filterSort),
+        columns: visibleColumns(),
+    };
+}
+
+function applyViewState(state) {
+    Object.entries(FILTER_PARAMS).forEach(([name, input]) => {
+        if (name === 'sort') return;
+        const value = state.filters[name];
+        input.value = Array.isArray(value) ? value.join(', ') : (value || '');
+    });
+    filterSort.value = state.sort || '';
+    columnToggles.forEach(input => {
+        input.checked = state.columns.length === 0 || state.columns.includes(input.value);
+    });
+}
+
+// Адрес страницы повторяет состояние списка, чтобы его можно было сохранить в закладках:
+// /?view=<id> для выбранного вида без изменений, иначе параметры фильтров
+function updateUrl() {
+    const params = new URLSearchParams();
+    if (viewSelect.value) {
+        params.set('view', viewSelect.value);
+    }
+    if (JSON.stringify(currentViewState()) !== viewSnapshot) {
+        Object.entries(FILTER_PARAMS).forEach(([name, input]) => {
+            if (input.value.trim()) params.set(name, input.value.trim());
+        });
+        const columns = visibleColumns();
+        if (columns.length) params.set('columns', columns.join(','));
+    }
+    
+    const query = params.toString();
+    history.replaceState(null, '', query ? `?${query}` : location.pathname);
+}
+
+// Параметры фильтров из адреса страницы дополняют или заменяют условия вида
+function applyUrlParams(params) {
+    Object.entries(FILTER_PARAMS).forEach(([name, input]) => {
+        if (params.has(name)) input.value = params.get(name);
+    });
+    if (params.has('columns')) {
+        const columns = params.get('columns').split(',');
+        columnToggles.forEach(input => { input.checked = columns.includes(input.value); });
+    }
+}
+
+function initViews() {
+    const params = new URLSearchParams(location.search);
+    viewUserSelect.value = localStorage.getItem('viewUserId') || '';
+    
+    loadViews().then(() => {
+        const viewId = params.get('view');
+        const opened = viewId
+            ? openView(viewId)
+            : params.toString() ? Promise.resolve() : openDefaultView();
+        
+        opened.then(() => {
+            applyUrlParams(params);
+            renderTasksTable();
+            updateUrl();
+        });
+    });
+}
+
+// Виды текущего пользователя: собственные и общие виды коллег
+function loadViews() {
+    if (!viewUserSelect.value) {
+        views = [];
+        renderViewOptions();
+        return Promise.resolve();
+    }
+    
+    return fetch(`/api/views?user_id=${viewUserSelect.value}`)
+        .then(response => {
+            if (!response.ok) {
+                throw new Error(TR.js.load_views_failed);
+            }
+            return response.json();
+        })
+        .then(data => {
+            views = data;
+            renderViewOptions();
+        })
+        .catch(error => showNotification(`${TR.js.error}: ${error.message}`, 'danger'));
+}
+
+function renderViewOptions() {
+    const selected = viewSelect.value;
+    viewSelect.innerHTML = `<option value="">${TR.views.no_view}</option>`;
+    
+    views.forEach(view => {
+        const option = document.createElement('option');
+        option.value = view.id;
+        const marks = [];
+        if (view.is_default) marks.push(TR.views.default_mark);
+        if (view.owner_id !== viewUserSelect.value) marks.push(TR.views.shared_mark);
+        option.textContent = marks.length ? `${view.name} (${marks.join(', ')})` : view.name;
+        viewSelect.appendChild(option);
+    });
+    
+    if (views.some(view => view.id === selected)) {
+        viewSelect.value = selected;
+    }
+}
+
+// Открывает вид по ссылке; чужой общий вид доступен и без выбора пользователя
+function openView(viewId) {
+    const known = views.find(view => view.id === viewId);
+    const loaded = known
+        ? Promise.resolve(known)
+        : fetch(`/api/views/${viewId}?user_id=${viewUserSelect.value}`).then(response => {
+            if (!response.ok) {
+                throw new Error(TR.js.view_not_available);
+            }
+            return response.json();
+        }).then(view => {
+            views.push(view);
+            renderViewOptions();
+            return view;
+        });
+    
+    return loaded
+        .then(view => selectView(view.id))
+        .catch(error => showNotification(`${TR.js.error}: ${error.message}`, 'danger'));
+}
+
+function openDefaultView() {
+    const view = views.find(v => v.is_default);
+    if (view) {
+        selectView(view.id);
+    }
+    return Promise.resolve();
+}
+
+function selectView(viewId) {
+    const view = views.find(v => v.id === viewId);
+    viewSelect.value = view ? view.id : '';
+    viewSnapshot = null;
+    
+    if (view) {
+        applyViewState(view);
+        viewSharedCheckbox.checked = view.shared;
+        viewSnapshot = JSON.stringify(currentViewState());
+    }
+    renderTasksTable();
+    updateUrl();
+}
+
+// Изменения видов выполняются от имени выбранного пользователя
+function requireViewUser() {
+    if (!viewUserSelect.value) {
+        showNotification(TR.js.view_user_required, 'warning');
+        return false;
+    }
+    return true;
+}
+
+function sendView(url, method, body) {
+    return fetch(url, {
+        method,
+        headers: { 'Content-Type': 'application/json' },
+        body: body ? JSON.stringify(body) : undefined,
+    }).then(response => {
+        if (!response.ok) {
+            return response.json()
+                .catch(() => ({ error: TR.js.view_save_failed }))
+                .then(data => { throw new Error(data.error || TR.js.view_save_failed); });
+        }
+        return response.status === 204 ? null : response.json();
+    });
+}
+
+function viewRequestBody(name) {
+    return {
+        owner_id: viewUserSelect.value,
+        name,
+        shared: viewSharedCheckbox.checked,
+        ...currentViewState(),
+    };
+}
+
+function saveViewAs() {
+    if (!requireViewUser()) return;
+    const name = prompt(TR.views.name_prompt);
+    if (!name) return;
+    
+    sendView('/api/views', 'POST', viewRequestBody(name))
+        .then(saved => loadViews().then(() => selectView(saved.id)))
+        .then(() => showNotification(TR.js.view_saved, 'success'))
+        .catch(error => showNotification(`${TR.js.error}: ${error.message}`, 'danger'));
+}
+
+function updateView() {
+    const view = views.find(v => v.id === viewSelect.value);
+    if (!view || !requireViewUser()) return;
+    
+    sendView(`/api/views/${view.id}`, 'PUT', viewRequestBody(view.name))
+        .then(() => loadViews().then(() => selectView(view.id)))
+        .then(() => showNotification(TR.js.view_saved, 'success'))
+        .catch(error => showNotification(`${TR.js.error}: ${error.message}`, 'danger'));
+}
+
+// Выбранный вид становится видом по умолчанию; без выбранного вида сбрасывается
+function makeDefaultView() {
+    if (!requireViewUser()) return;
+    
+    sendView(`/api/users/${viewUserSelect.value}/default-view`, 'PUT', { view_id: viewSelect.value || null })
+        .then(loadViews)
+        .then(() => showNotification(TR.js.default_view_saved, 'success'))
+        .catch(error => showNotification(`${TR.js.error}: ${error.message}`, 'danger'));
+}
+
+function copyViewLink() {
+    updateUrl();
+    navigator.clipboard.writeText(location.href)
+        .then(() => showNotification(TR.js.view_link_copied, 'success'))
+        .catch(() => prompt(TR.views.copy_link, location.href));
+}
+
+function deleteView() {
+    const view = views.find(v => v.id === viewSelect.value);
+    if (!view || !requireViewUser()) return;
+    if (!confirm(TR.views.delete_confirm)) return;
+    
+    sendView(`/api/views/${view.id}?user_id=${viewUserSelect.value}`, 'DELETE')
+        .then(() => {
+            viewSelect.value = '';
+            viewSnapshot = null;
+            return loadViews();
+        })
+        .then(() => {
+            updateUrl();
+            showNotification(TR.js.view_deleted, 'success');
+        })
+        .catch(error => showNotification(`${TR.js.error}: ${error.message}`, 'danger'));
 }
 
 // Обновление количества просроченных дней
@@ -270,21 +783,24 @@ function editTask(taskId) {
     if (!task) return;
     
     // Заполняем форму данными задачи
+    clearFieldErrors(taskForm);
     document.getElementById('taskId').value = task.id;
     document.getElementById('taskTitle').value = task.title;
     document.getElementById('taskDescription').value = task.description;
     document.getElementById('taskCustomer').value = task.customer_id;
     document.getElementById('taskExecutor').value = task.executor_id;
-    document.getElementById('taskPriority').value = task.priority;
-    document.getElementById('taskStatus').value = task.status;
+    document.getElementById('taskExecutor').disabled = true;
+    document.getElementById('taskPriority').value = toCode(task.priority);
+    document.getElementById('taskStatus').value = toCode(task.status);
     document.getElementById('taskStatus').disabled = false;
+    document.getElementById('taskProject').value = task.project_id || '';
+    document.getElementById('taskEstimate').value = task.estimate_minutes ? task.estimate_minutes / 60 : '';
     
     // Устанавливаем срок выполнения
-    if (task.due_date) {
-        const dueDate = new Date(task.due_date);
-        const offset = dueDate.getTimezoneOffset() * 60000;
-        const localDueDate = new Date(dueDate.getTime() - offset);
-        document.getElementById('taskDueDate').value = localDueDate.toISOString().slice(0, 16);
+    if (task.due_date_local) {
+        // Формат сервера: ДД.ММ.ГГГГ ЧЧ:ММ в поясе исполнителя
+        const [day, month, year] = task.due_date_local.split(' ')[0].split('.');
+        document.getElementById('taskDueDate').value = `${year}-${month}-${day}`;
     } else {
         document.getElementById('taskDueDate').value = '';
     }
@@ -293,8 +809,23 @@ function editTask(taskId) {
     selectedTaskTags = [...task.tags];
     renderSelectedTags();
     
+    // Показываем соисполнителей и наблюдателей
+    document.getElementById('participantsSection').hidden = false;
+    renderParticipants(task);
+    
+    // Исполнитель меняется передачей поручения; показываем цепочку передач
+    document.getElementById('delegationSection').hidden = false;
+    document.getElementById('reassignReason').value = '';
+    renderReassignBy(task);
+    loadDelegations(task.id);
+    
+    // Учет времени по поручению
+    document.getElementById('timeSection').hidden = false;
+    document.getElementById('timeSummary').textContent = timeSummary(task);
+    loadTimeEntries(task.id);
+    
     // Обновляем заголовок модального окна
-    document.getElementById('taskModalLabel').textContent = 'Редактирование поручения';
+    document.getElementById('taskModalLabel').textContent = TR.tasks.edit;
     
     // Открываем модальное окно
     taskModal.show();
@@ -307,7 +838,7 @@ function addTag() {
     
     // Проверяем, не добавлен ли уже такой тег
     if (selectedTaskTags.some(tag => tag.name.toLowerCase() === tagName.toLowerCase())) {
-        showNotification('Этот тег уже добавлен', 'warning');
+        showNotification(TR.js.tag_exists, 'warning');
         tagInput.value = '';
         return;
     }
@@ -350,6 +881,268 @@ function renderSelectedTags() {
     });
 }
 
+// Участник задачи в какой-либо роли
+function isParticipant(task, userId) {
+    return task.customer_id === userId
+        || task.executor_id === userId
+        || task.co_executor_ids.includes(userId)
+        || task.watcher_ids.includes(userId);
+}
+
+// Имя участника берется из списка наблюдателей, заполненного сервером
+function participantName(userId) {
+    const option = document.querySelector(`#watcherSelect option[value="${userId}"]`);
+    return option ? option.textContent : userId.substring(0, 6);
+}
+
+// Отображение соисполнителей и наблюдателей в форме задачи
+function renderParticipants(task) {
+    const groups = [
+        ['co-executors', task.co_executor_ids, document.getElementById('coExecutorsContainer')],
+        ['watchers', task.watcher_ids, document.getElementById('watchersContainer')],
+    ];
+    
+    groups.forEach(([kind, ids, container]) => {
+        container.innerHTML = '';
+        ids.forEach(userId => {
+            const element = document.createElement('span');
+            element.className = 'tag-badge';
+            element.textContent = participantName(userId);
+            
+            const removeBtn = document.createElement('span');
+            removeBtn.className = 'tag-remove';
+            removeBtn.textContent = '×';
+            removeBtn.addEventListener('click', () => removeParticipant(kind, userId));
+            
+            element.appendChild(removeBtn);
+            container.appendChild(element);
+        });
+    });
+}
+
+// Добавление и удаление участников сразу сохраняются на сервере
+function addParticipant(kind, userId) {
+    const taskId = document.getElementById('taskId').value;
+    if (!taskId || !userId) return;
+    
+    updateParticipants(`/api/tasks/${taskId}/${kind}`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json', 'If-Match': taskVersionTag(taskId) },
+        body: JSON.stringify({ user_id: userId }),
+    });
+}
+
+function removeParticipant(kind, userId) {
+    const taskId = document.getElementById('taskId').value;
+    updateParticipants(`/api/tasks/${taskId}/${kind}/${userId}`, {
+        method: 'DELETE',
+        headers: { 'If-Match': taskVersionTag(taskId) },
+    });
+}
+
+// Версия задачи для If-Match: после каждого изменения участников
+// список задач обновляется ответом сервера
+function taskVersionTag(taskId) {
+    const task = tasks.find(t => t.id === taskId);
+    return task ? `"${task.version}"` : '*';
+}
+
+function updateParticipants(url, options) {
+    fetch(url, options)
+        .then(response => {
+            if (!response.ok) {
+                return response.json()
+                    .catch(() => ({ error: TR.js.participant_failed }))
+                    .then(body => { throw new Error(body.error || TR.js.participant_failed); });
+            }
+            return response.json();
+        })
+        .then(updated => {
+            const index = tasks.findIndex(t => t.id === updated.id);
+            if (index >= 0) {
+                tasks[index] = updated;
+            }
+            renderParticipants(updated);
+            renderTasksTable();
+        })
+        .catch(error => showNotification(`${TR.js.error}: ${error.message}`, 'danger'));
+}
+
+// Передать поручение может текущий исполнитель или заказчик
+function renderReassignBy(task) {
+    const options = [task.executor_id, task.customer_id].map(userId => new Option(participantName(userId), userId));
+    document.getElementById('reassignBy').replaceChildren(...options);
+}
+
+// Цепочка передач поручения, от первой к последней
+function loadDelegations(taskId) {
+    const history = document.getElementById('delegationHistory');
+    history.innerHTML = '';
+    
+    fetch(`/api/tasks/${taskId}/delegations`)
+        .then(response => {
+            if (!response.ok) {
+                throw new Error(TR.js.load_delegations_failed);
+            }
+            return response.json();
+        })
+        .then(delegations => {
+            if (delegations.length === 0) {
+                history.innerHTML = `<li class="text-muted">${TR.tasks.no_delegations}</li>`;
+                return;
+            }
+            delegations.forEach(delegation => {
+                const item = document.createElement('li');
+                const date = new Date(delegation.created_at).toLocaleString();
+                item.textContent = `${date}: ${participantName(delegation.from_user_id)} → ${participantName(delegation.to_user_id)}`
+                    + ` (${TR.tasks.reassign_by}: ${participantName(delegation.by_user_id)}) - ${delegation.reason}`;
+                history.appendChild(item);
+            });
+        })
+        .catch(error => showNotification(`${TR.js.error}: ${error.message}`, 'danger'));
+}
+
+function reassignTask() {
+    const taskId = document.getElementById('taskId').value;
+    const task = tasks.find(t => t.id === taskId);
+    if (!task) return;
+    
+    const reason = document.getElementById('reassignReason').value.trim();
+    if (!reason) {
+        showNotification(TR.errors.reassign_reason_required, 'warning');
+        return;
+    }
+    
+    fetch(`/api/tasks/${taskId}/reassign`, {
+        method: 'POST',
+        headers: {
+            'Content-Type': 'application/json',
+            'If-Match': `"${task.version}"`,
+        },
+        body: JSON.stringify({
+            to_user_id: document.getElementById('reassignTo').value,
+            by_user_id: document.getElementById('reassignBy').value,
+            reason,
+        }),
+    })
+        .then(response => {
+            if (!response.ok) {
+                return response.json()
+                    .catch(() => ({ error: TR.js.reassign_failed }))
+                    .then(body => { throw new Error(body.error || TR.js.reassign_failed); });
+            }
+            return response.json();
+        })
+        .then(updated => {
+            const index = tasks.findIndex(t => t.id === updated.id);
+            if (index >= 0) {
+                tasks[index] = updated;
+            }
+            document.getElementById('taskExecutor').value = updated.executor_id;
+            document.getElementById('reassignReason').value = '';
+            renderReassignBy(updated);
+            renderParticipants(updated);
+            loadDelegations(updated.id);
+            renderTasksTable();
+            showNotification(TR.js.task_reassigned, 'success');
+        })
+        .catch(error => showNotification(`${TR.js.error}: ${error.message}`, 'danger'));
+}
+
+// Учтенное время и оценка: "3:30 из 4:00 (-0:30)"
+function timeSummary(task) {
+    if (task.estimate_minutes === null) {
+        return formatMinutes(task.spent_minutes);
+    }
+    const variance = task.estimate_variance_minutes;
+    const sign = variance >= 0 ? '+' : '-';
+    return `${formatMinutes(task.spent_minutes)} ${TR.tasks.of_estimate} ${formatMinutes(task.estimate_minutes)}`
+        + ` (${sign}${formatMinutes(Math.abs(variance))})`;
+}
+
+// Записи учета времени; запущенные таймеры отмечены отдельно
+function loadTimeEntries(taskId) {
+    const list = document.getElementById('timeEntries');
+    list.innerHTML = '';
+    
+    fetch(`/api/tasks/${taskId}/time-entries`)
+        .then(response => {
+            if (!response.ok) {
+                throw new Error(TR.js.load_time_failed);
+            }
+            return response.json();
+        })
+        .then(entries => {
+            entries.forEach(entry => {
+                const item = document.createElement('li');
+                const started = new Date(entry.started_at).toLocaleString();
+                const duration = entry.ended_at ? formatMinutes(entry.minutes) : TR.tasks.timer_running;
+                item.textContent = `${started}: ${participantName(entry.user_id)}, ${duration}`
+                    + (entry.note ? ` - ${entry.note}` : '');
+                
+                const removeBtn = document.createElement('span');
+                removeBtn.className = 'tag-remove';
+                removeBtn.textContent = '×';
+                removeBtn.addEventListener('click', () => deleteTimeEntry(entry.id));
+                
+                item.appendChild(removeBtn);
+                list.appendChild(item);
+            });
+        })
+        .catch(error => showNotification(`${TR.js.error}: ${error.message}`, 'danger'));
+}
+
+function addTimeEntry() {
+    const minutes = parseInt(document.getElementById('timeMinutes').value, 10);
+    if (!(minutes > 0)) return;
+    
+    updateTime(`/api/tasks/${document.getElementById('taskId').value}/time-entries`, {
+        user_id: document.getElementById('timeUser').value,
+        minutes,
+        note: document.getElementById('timeNote').value,
+    });
+}
+
+function switchTimer(action) {
+    updateTime(`/api/tasks/${document.getElementById('taskId').value}/timer/${action}`, {
+        user_id: document.getElementById('timeUser').value,
+        note: document.getElementById('timeNote').value,
+    });
+}
+
+function deleteTimeEntry(entryId) {
+    updateTime(`/api/time-entries/${entryId}`, null);
+}
+
+// После изменения записей перечитываем поручение: учтенное время считает сервер
+function updateTime(url, data) {
+    const taskId = document.getElementById('taskId').value;
+    const options = data
+        ? { method: 'POST', headers: { 'Content-Type': 'application/json' }, body: JSON.stringify(data) }
+        : { method: 'DELETE' };
+    
+    fetch(url, options)
+        .then(response => {
+            if (!response.ok) {
+                return response.json()
+                    .catch(() => ({ error: TR.js.time_failed }))
+                    .then(body => { throw new Error(body.error || TR.js.time_failed); });
+            }
+            document.getElementById('timeMinutes').value = '';
+            document.getElementById('timeNote').value = '';
+            return fetch(`/api/tasks/${taskId}`).then(response => response.json());
+        })
+        .then(updated => {
+            const index = tasks.findIndex(t => t.id === updated.id);
+            if (index >= 0) {
+                tasks[index] = updated;
+            }
+            document.getElementById('timeSummary').textContent = timeSummary(updated);
+            loadTimeEntries(taskId);
+        })
+        .catch(error => showNotification(`${TR.js.error}: ${error.message}`, 'danger'));
+}
+
 // Сохранение задачи
 function saveTask() {
     // Проверяем валидность формы
@@ -367,6 +1160,9 @@ function saveTask() {
     const priority = document.getElementById('taskPriority').value;
     const status = document.getElementById('taskStatus').value;
     const dueDateInput = document.getElementById('taskDueDate').value;
+    const project_id = document.getElementById('taskProject').value || null;
+    const estimateHours = parseFloat(document.getElementById('taskEstimate').value);
+    const estimate_minutes = estimateHours > 0 ? Math.round(estimateHours * 60) : null;
     
     // Преобразуем теги в формат для API
     const tagNames = selectedTaskTags.map(tag => tag.name);
@@ -378,6 +1174,8 @@ function saveTask() {
         priority,
         customer_id,
         executor_id,
+        project_id,
+        estimate_minutes,
         tags: tagNames
     };
     
@@ -386,30 +1184,63 @@ function saveTask() {
         taskData.status = status;
     }
     
-    // Добавляем срок выполнения, если указан
+    // Добавляем срок выполнения, если указан. Передаем только дату:
+    // сервер установит конец дня в часовом поясе исполнителя
     if (dueDateInput) {
-        taskData.due_date = new Date(dueDateInput).toISOString();
+        taskData.due_date = dueDateInput;
     }
     
     // Определяем метод и URL в зависимости от того, создаем или редактируем задачу
     const method = taskId ? 'PUT' : 'POST';
     const url = taskId ? `/api/tasks/${taskId}` : '/api/tasks';
     
-    // Отправляем запрос на сервер
+    // При редактировании передаем версию, которую видел пользователь
+    const task = tasks.find(t => t.id === taskId);
+    sendTask(url, method, taskData, task ? task.version : null);
+}
+
+// Отправка задачи на сервер. При конфликте версий (412) пользователь
+// выбирает: загрузить актуальную версию или перезаписать ее своими изменениями
+function sendTask(url, method, taskData, version) {
+    const taskId = document.getElementById('taskId').value;
+    const headers = {
+        'Content-Type': 'application/json',
+    };
+    if (version !== null) {
+        headers['If-Match'] = `"${version}"`;
+    }
+    
     fetch(url, {
         method,
-        headers: {
-            'Content-Type': 'application/json',
-        },
+        headers,
         body: JSON.stringify(taskData),
     })
     .then(response => {
+        if (response.status === 412) {
+            return response.json().then(conflict => {
+                if (confirm(TR.js.conflict_reload)) {
+                    taskModal.hide();
+                    loadTasks();
+                } else {
+                    sendTask(url, method, taskData, conflict.current_version);
+                }
+                return null;
+            });
+        }
+        if (response.status === 422) {
+            return response.json().then(body => {
+                showFieldErrors(taskForm, body.errors);
+                return null;
+            });
+        }
         if (!response.ok) {
-            throw new Error('Не удалось сохранить задачу');
+            throw new Error(TR.js.save_failed);
         }
         return response.json();
     })
-    .then(() => {
+    .then(saved => {
+        if (!saved) return;
+        
         // Закрываем модальное окно
         taskModal.hide();
         
@@ -418,10 +1249,10 @@ function saveTask() {
         loadTags();
         
         // Показываем уведомление об успешном сохранении
-        const message = taskId ? 'Поручение успешно обновлено' : 'Новое поручение создано';
+        const message = taskId ? TR.js.task_updated : TR.js.task_created;
         showNotification(message, 'success');
     })
-    .catch(error => showNotification('Ошибка: ' + error.message, 'danger'));
+    .catch(error => showNotification(`${TR.js.error}: ${error.message}`, 'danger'));
 }
 
 // Показать подтверждение удаления
@@ -434,12 +1265,22 @@ function showDeleteConfirm(taskId) {
 function deleteTask() {
     if (!taskToDeleteId) return;
     
+    const task = tasks.find(t => t.id === taskToDeleteId);
+    
     fetch(`/api/tasks/${taskToDeleteId}`, {
         method: 'DELETE',
+        headers: {
+            'If-Match': task ? `"${task.version}"` : '*',
+        },
     })
     .then(response => {
+        if (response.status === 412) {
+            deleteConfirmModal.hide();
+            loadTasks();
+            throw new Error(TR.errors.version_conflict);
+        }
         if (!response.ok) {
-            throw new Error('Не удалось удалить задачу');
+            throw new Error(TR.js.delete_failed);
         }
         
         // Закрываем модальное окно
@@ -449,19 +1290,44 @@ function deleteTask() {
         loadTasks();
         
         // Показываем уведомление об успешном удалении
-        showNotification('Поручение успешно удалено', 'success');
+        showNotification(TR.js.task_deleted, 'success');
     })
-    .catch(error => showNotification('Ошибка: ' + error.message, 'danger'));
+    .catch(error => showNotification(`${TR.js.error}: ${error.message}`, 'danger'));
 }
 
 // Сброс формы
 function resetTaskForm() {
+    clearFieldErrors(taskForm);
     document.getElementById('taskId').value = '';
     taskForm.reset();
     selectedTaskTags = [];
     renderSelectedTags();
 }
 
+// Ошибки проверки полей (ответ 422): сообщение выводится под полем формы
+// с атрибутом data-field; ошибки полей без места в форме - уведомлением
+function showFieldErrors(form, errors) {
+    clearFieldErrors(form);
+    const unplaced = [];
+    errors.forEach(error => {
+        const input = form.querySelector(`[data-field="${error.field}"]`);
+        const feedback = input ? input.parentElement.querySelector('.invalid-feedback') : null;
+        if (!feedback) {
+            unplaced.push(`${error.field}: ${error.message}`);
+            return;
+        }
+        input.classList.add('is-invalid');
+        feedback.textContent = error.message;
+    });
+    if (unplaced.length) {
+        showNotification(`${TR.js.error}: ${unplaced.join('; ')}`, 'danger');
+    }
+}
+
+function clearFieldErrors(form) {
+    form.querySelectorAll('.is-invalid').forEach(input => input.classList.remove('is-invalid'));
+}
+
 // Показ уведомления
 function showNotification(message, type = 'info') {
     // Создаем элемент уведомления
@@ -473,7 +1339,7 @@ function showNotification(message, type = 'info') {
     `;
     
     // Добавляем уведомление на страницу
-    const container = document.querySelector('main .container');
+    const container = document.querySelector('main');
     container.insertBefore(notification, container.firstChild);
     
     // Автоматически скрываем через 5 секунд
@@ -484,49 +1350,54 @@ function showNotification(message, type = 'info') {
 }
 
 // Вспомогательные функции
+// Значения перечислений приходят из API в виде "InProgress"; подписи
+// и CSS-классы используют вид "in_progress"
+// Цвет тега, заданный на странице управления тегами
+function tagStyle(tag) {
+    return tag.color ? ` style="background-color: ${tag.color}"` : '';
+}
+
+function toCode(value) {
+    return value.replace(/([a-z])([A-Z])/g, '$1_$2').toLowerCase();
+}
+
 function getStatusText(status) {
-    const statuses = {
-        'new': 'Новая',
-        'in_progress': 'В работе',
-        'completed': 'Завершена',
-        'cancelled': 'Отменена'
-    };
-    return statuses[status] || status;
+    return TR.status[toCode(status)] || status;
 }
 
 function getPriorityText(priority) {
-    const priorities = {
-        'low': 'Низкий',
-        'medium': 'Средний',
-        'high': 'Высокий',
-        'critical': 'Критический'
-    };
-    return priorities[priority] || priority;
+    return TR.priority[toCode(priority)] || priority;
 }
 
 function getCustomerName(customerId) {
     // В реальном приложении это будет запрос к API или использование кэшированных данных пользователей
-    return `Заказчик ${customerId.substring(0, 6)}`;
+    return `${TR.tasks.customer} ${customerId.substring(0, 6)}`;
 }
 
 function getExecutorName(executorId) {
     // В реальном приложении это будет запрос к API или использование кэшированных данных пользователей
-    return `Исполнитель ${executorId.substring(0, 6)}`;
+    return `${TR.tasks.executor} ${executorId.substring(0, 6)}`;
+}
+
+// Длительность в минутах в виде "ч:мм"
+function formatMinutes(minutes) {
+    const hours = Math.floor(minutes / 60);
+    return `${hours}:${String(minutes % 60).padStart(2, '0')}`;
 }
 
 function getDayWordForm(days) {
     if (days % 100 >= 11 && days % 100 <= 14) {
-        return 'дней';
+        return TR.js.day_many;
     }
     
     switch (days % 10) {
         case 1:
-            return 'день';
+            return TR.js.day_one;
         case 2:
         case 3:
         case 4:
-            return 'дня';
+            return TR.js.day_few;
         default:
-            return 'дней';
+            return TR.js.day_many;
     }
 }
@@ -0,0 +1,59 @@
+// Форма проекта (templates/project_form.html) для страниц проектов.
+// onProjectSaved вызывается после успешного сохранения
+const projectModal = new bootstrap.Modal(document.getElementById('projectModal'));
+let onProjectSaved = () => {};
+
+function openProjectForm(project) {
+    document.getElementById('projectModalLabel').textContent = project ? TR.projects.edit : TR.projects.new;
+    document.getElementById('projectId').value = project ? project.id : '';
+    document.getElementById('projectName').value = project ? project.name : '';
+    document.getElementById('projectDescription').value = project ? project.description : '';
+    document.getElementById('projectOwner').value = project ? project.owner_id : '';
+    document.getElementById('projectCustomer').value = (project && project.customer_id) || '';
+    document.getElementById('projectStatus').value = project ? toCode(project.status) : 'active';
+    document.getElementById('projectForm').dataset.version = project ? project.version : '';
+    projectModal.show();
+}
+
+function saveProject() {
+    const form = document.getElementById('projectForm');
+    if (!form.checkValidity()) {
+        form.reportValidity();
+        return;
+    }
+
+    const id = document.getElementById('projectId').value;
+    const data = {
+        name: document.getElementById('projectName').value,
+        description: document.getElementById('projectDescription').value,
+        owner_id: document.getElementById('projectOwner').value,
+        customer_id: document.getElementById('projectCustomer').value || null,
+        status: document.getElementById('projectStatus').value,
+    };
+    const headers = { 'Content-Type': 'application/json' };
+    if (id) {
+        headers['If-Match'] = `"${form.dataset.version}"`;
+    }
+
+    fetch(id ? `/api/projects/${id}` : '/api/projects', {
+        method: id ? 'PUT' : 'POST',
+        headers,
+        body: JSON.stringify(data),
+    })
+        .then(response => {
+            if (!response.ok) {
+                return response.json()
+                    .catch(() => ({ error: TR.js.project_save_failed }))
+                    .then(body => { throw new Error(body.error || TR.js.project_save_failed); });
+            }
+            return response.json();
+        })
+        .then(project => {
+            projectModal.hide();
+            showNotification(TR.js.project_saved, 'success');
+            onProjectSaved(project);
+        })
+        .catch(error => showNotification(`${TR.js.error}: ${error.message}`, 'danger'));
+}
+
+document.getElementById('saveProjectBtn').addEventListener('click', saveProject);
@@ -0,0 +1,170 @@
+// Общие средства интеграционных тестов: приложение, собранное так же, как в main,
+// поверх временной базы SQLite, и короткие помощники для запросов к нему
+#![allow(dead_code)]
+
+use actix_web::http::header::{self, HeaderMap};
+use actix_web::http::StatusCode;
+use actix_web::test::{self, TestRequest};
+use actix_web::web::Bytes;
+use chrono::{SubsecRound, Utc};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use business_tasks::archive::ArchiveSettings;
+use business_tasks::calendar::BusinessCalendar;
+use business_tasks::clock::FixedClock;
+use business_tasks::db::{self, Repository};
+use business_tasks::trash::TrashSettings;
+use business_tasks::{build_app, templates, AppState};
+
+pub struct TestApp {
+    pub state: AppState,
+    // Часы приложения; стоят, пока тест их не переставит
+    pub clock: Arc<FixedClock>,
+    dir: PathBuf,
+}
+
+impl TestApp {
+    // Приложение с пустой базой в отдельном временном каталоге. Фоновые
+    // очистка корзины и архивация не запускаются
+    pub fn new() -> Self {
+        let dir = std::env::temp_dir().join(format!("business-tasks-it-{}", uuid::Uuid::new_v4().simple()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let repo = db::connect(dir.join("test.db").to_str().unwrap()).unwrap();
+        let clock = Arc::new(FixedClock::new(Utc::now().trunc_subsecs(0)));
+
+        let state = AppState {
+            tera: templates().unwrap(),
+            repo,
+            calendar: BusinessCalendar::default(),
+            clock: clock.clone(),
+            trash_settings: TrashSettings { retention_days: Some(TrashSettings::DEFAULT_RETENTION_DAYS) },
+            archive_settings: ArchiveSettings { after_days: Some(ArchiveSettings::DEFAULT_AFTER_DAYS) },
+        };
+        Self { state, clock, dir }
+    }
+
+    pub fn repo(&self) -> &Arc<dyn Repository> {
+        &self.state.repo
+    }
+
+    pub async fn call(&self, req: TestRequest) -> Response {
+        let app = test::init_service(build_app(&self.state)).await;
+        let res = test::call_service(&app, req.to_request()).await;
+        let status = res.status();
+        let headers = res.headers().clone();
+        let body = test::read_body(res).await;
+        Response { status, headers, body }
+    }
+
+    pub async fn get(&self, uri: &str) -> Response {
+        self.call(TestRequest::get().uri(uri)).await
+    }
+
+    pub async fn post(&self, uri: &str, body: Value) -> Response {
+        self.call(TestRequest::post().uri(uri).set_json(body)).await
+    }
+
+    // Изменяющие запросы с заголовком If-Match для указанной версии
+    pub async fn post_versioned(&self, uri: &str, version: i64, body: Value) -> Response {
+        self.call(TestRequest::post().uri(uri).insert_header(if_match(version)).set_json(body)).await
+    }
+
+    pub async fn put(&self, uri: &str, version: i64, body: Value) -> Response {
+        self.call(TestRequest::put().uri(uri).insert_header(if_match(version)).set_json(body)).await
+    }
+
+    pub async fn patch(&self, uri: &str, version: i64, body: Value) -> Response {
+        self.call(
+            TestRequest::patch()
+                .uri(uri)
+                .insert_header(if_match(version))
+                .insert_header((header::CONTENT_TYPE, "application/merge-patch+json"))
+                .set_payload(body.to_string()),
+        )
+        .await
+    }
+
+    pub async fn delete(&self, uri: &str, version: i64) -> Response {
+        self.call(TestRequest::delete().uri(uri).insert_header(if_match(version))).await
+    }
+
+    // Пользователь с адресом name@example.com; role - customer или executor
+    pub async fn create_user(&self, name: &str, role: &str) -> Value {
+        let res = self
+            .post("/api/users", json!({ "name": name, "email": format!("{}@example.com", name), "role": role }))
+            .await;
+        assert_eq!(res.status, StatusCode::CREATED, "{}", res.text());
+        res.json()
+    }
+
+    // Задача без срока и тегов; extra дополняет и переопределяет поля запроса
+    pub async fn create_task(&self, customer: &Value, executor: &Value, extra: Value) -> Value {
+        let mut body = json!({
+            "title": "Подготовить отчет",
+            "description": "Квартальный отчет",
+            "priority": "medium",
+            "customer_id": customer["id"],
+            "executor_id": executor["id"],
+            "tags": [],
+        });
+        for (key, value) in extra.as_object().unwrap() {
+            body[key] = value.clone();
+        }
+        let res = self.post("/api/tasks", body).await;
+        assert_eq!(res.status, StatusCode::CREATED, "{}", res.text());
+        res.json()
+    }
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+pub struct Response {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl Response {
+    pub fn json(&self) -> Value {
+        serde_json::from_slice(&self.body).unwrap_or_else(|e| panic!("invalid JSON ({}): {}", e, self.text()))
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    // Код ошибки из ErrorResponse
+    pub fn code(&self) -> String {
+        self.json()["code"].as_str().unwrap_or_default().to_string()
+    }
+
+    pub fn etag(&self) -> Option<String> {
+        self.headers.get(header::ETAG).map(|v| v.to_str().unwrap().to_string())
+    }
+
+    pub fn content_type(&self) -> String {
+        self.headers
+            .get(header::CONTENT_TYPE)
+            .map(|v| v.to_str().unwrap().to_string())
+            .unwrap_or_default()
+    }
+}
+
+fn if_match(version: i64) -> (header::HeaderName, String) {
+    (header::IF_MATCH, format!("\"{}\"", version))
+}
+
+// Версия записи из ответа
+pub fn version(value: &Value) -> i64 {
+    value["version"].as_i64().unwrap()
+}
+
+pub fn id(value: &Value) -> String {
+    value["id"].as_str().unwrap().to_string()
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use actix_web::http::{Method, StatusCode};
+use actix_web::test::TestRequest;
+
+use business_tasks::api::openapi::spec;
+use common::TestApp;
+
+const METHODS: [&str; 5] = ["get", "post", "put", "patch", "delete"];
+
+// Адрес для проверки маршрута: параметры пути заменяются произвольным значением
+fn probe_uri(path: &str) -> String {
+    path.split('/')
+        .map(|segment| if segment.starts_with('{') { "probe" } else { segment })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// Подходит ли адрес под шаблон пути спецификации
+fn matches(template: &str, uri: &str) -> bool {
+    let template: Vec<&str> = template.split('/').collect();
+    let uri: Vec<&str> = uri.split('/').collect();
+    template.len() == uri.len()
+        && template.iter().zip(&uri).all(|(t, u)| t.starts_with('{') || t == u)
+}
+
+// Каждый метод каждого пути спецификации обрабатывается приложением, а методы,
+// не описанные ни для одного подходящего пути, попадают в обработчик
+// несуществующих адресов
+#[actix_web::test]
+async fn spec_matches_app_routes() {
+    let app = TestApp::new();
+    let doc = serde_json::to_value(spec()).unwrap();
+
+    let paths = doc["paths"].as_object().unwrap();
+
+    for path in paths.keys() {
+        let uri = probe_uri(path);
+        for method in METHODS {
+            let req = TestRequest::default()
+                .method(Method::from_bytes(method.to_uppercase().as_bytes()).unwrap())
+                .uri(&uri);
+            let res = app.call(req).await;
+            let routed = !(res.status == StatusCode::NOT_FOUND && res.code() == "route_not_found");
+            let documented = paths
+                .iter()
+                .any(|(template, item)| matches(template, &uri) && item.get(method).is_some());
+            assert_eq!(routed, documented, "{} {}: {}", method, path, res.text());
+        }
+    }
+}
+
+// Страница документации подключает только файлы самого приложения
+#[actix_web::test]
+async fn docs_page_uses_local_assets() {
+    let app = TestApp::new();
+    let page = app.get("/api/docs").await;
+    assert_eq!(page.status, StatusCode::OK);
+    assert!(!page.text().contains("://"), "{}", page.text());
+
+    for asset in ["/static/js/api_docs.js", "/static/css/api_docs.css"] {
+        assert!(page.text().contains(asset));
+        assert_eq!(app.get(asset).await.status, StatusCode::OK, "{}", asset);
+    }
+}
@@ -0,0 +1,82 @@
+// HTML-страницы и общие ответы приложения
+
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use serde_json::json;
+
+use business_tasks::clock::Clock;
+use business_tasks::models::Project;
+use common::{id, TestApp};
+
+#[actix_web::test]
+async fn pages_render() {
+    let app = TestApp::new();
+    let alice = app.create_user("alice", "customer").await;
+    let bob = app.create_user("bob", "executor").await;
+    app.create_task(&alice, &bob, json!({ "tags": ["отчеты"] })).await;
+    let project = Project::new("Запуск".to_string(), String::new(), id(&alice), app.clock.now());
+    app.repo().create_project(&project).unwrap();
+
+    for uri in [
+        "/".to_string(),
+        "/users".to_string(),
+        "/projects".to_string(),
+        format!("/projects/{}", project.id),
+        "/tags".to_string(),
+        "/trash".to_string(),
+        "/board".to_string(),
+        "/timesheet".to_string(),
+        "/archive".to_string(),
+    ] {
+        let res = app.get(&uri).await;
+        assert_eq!(res.status, StatusCode::OK, "{}: {}", uri, res.text());
+        assert!(res.content_type().starts_with("text/html"), "{}", uri);
+        assert!(res.text().contains("<html"), "{}", uri);
+    }
+}
+
+#[actix_web::test]
+async fn index_offers_only_active_users() {
+    let app = TestApp::new();
+    app.create_user("alice", "customer").await;
+    let bob = app.create_user("bob", "executor").await;
+    app.repo().deactivate_user(&id(&bob), app.clock.now()).unwrap();
+
+    let page = app.get("/").await.text();
+    assert!(page.contains(&format!("<option value=\"{}\" hidden>", id(&bob))));
+}
+
+#[actix_web::test]
+async fn pages_follow_requested_language() {
+    let app = TestApp::new();
+
+    let ru = app.get("/").await.text();
+    assert!(ru.contains("lang=\"ru\""));
+    let en = app.call(TestRequest::get().uri("/").insert_header(("Accept-Language", "en-US,en;q=0.9"))).await;
+    assert!(en.text().contains("lang=\"en\""));
+    let en = app.get("/users?lang=en").await.text();
+    assert!(en.contains("lang=\"en\""));
+}
+
+#[actix_web::test]
+async fn unknown_addresses_are_not_found() {
+    let app = TestApp::new();
+
+    assert_eq!(app.get("/projects/missing").await.status, StatusCode::NOT_FOUND);
+    assert_eq!(app.get("/no-such-page").await.status, StatusCode::NOT_FOUND);
+
+    let res = app.get("/api/no-such-route").await;
+    assert_eq!(res.status, StatusCode::NOT_FOUND);
+    assert_eq!(res.code(), "route_not_found");
+    assert!(res.headers.contains_key("x-request-id"));
+}
+
+#[actix_web::test]
+async fn static_files_are_served() {
+    let app = TestApp::new();
+    let res = app.get("/static/css/main.css").await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert!(res.content_type().starts_with("text/css"));
+}
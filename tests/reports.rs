@@ -0,0 +1,75 @@
+// Маршруты /api/reports
+
+mod common;
+
+use actix_web::http::StatusCode;
+use serde_json::json;
+
+use common::{id, TestApp};
+
+// Задачи со сроками 2 и 9 октября 2026 года, созданные в четверг 1 октября
+async fn tasks_due_in_october(app: &TestApp) -> (String, String) {
+    app.clock.set("2026-10-01T09:00:00Z".parse().unwrap());
+    let alice = app.create_user("alice", "customer").await;
+    let bob = app.create_user("bob", "executor").await;
+    let first = app.create_task(&alice, &bob, json!({ "title": "first", "due_date": "2026-10-02" })).await;
+    let second = app.create_task(&alice, &bob, json!({ "title": "second", "due_date": "2026-10-09", "priority": "high" })).await;
+    (id(&first), id(&second))
+}
+
+#[actix_web::test]
+async fn overdue_report_uses_application_clock() {
+    let app = TestApp::new();
+    let (first, _) = tasks_due_in_october(&app).await;
+
+    assert!(app.get("/api/reports/overdue").await.json().as_array().unwrap().is_empty());
+
+    app.clock.set("2026-10-07T09:00:00Z".parse().unwrap());
+    let report = app.get("/api/reports/overdue").await.json();
+    assert_eq!(report.as_array().unwrap().len(), 1);
+    assert_eq!(report[0]["id"], first);
+    assert_eq!(report[0]["overdue_working_days"], 3);
+}
+
+#[actix_web::test]
+async fn overdue_report_as_of_given_moment() {
+    let app = TestApp::new();
+    let (first, second) = tasks_due_in_october(&app).await;
+
+    // Что окажется просрочено к понедельнику 12 октября
+    let report = app.get("/api/reports/overdue?as_of=2026-10-12T09:00:00Z").await.json();
+    let ids: Vec<_> = report.as_array().unwrap().iter().map(|t| t["id"].clone()).collect();
+    assert_eq!(ids, [json!(first), json!(second)]);
+    assert_eq!(report[0]["overdue_days"], 9);
+    assert_eq!(report[0]["overdue_working_days"], 6);
+    assert_eq!(report[1]["overdue_working_days"], 1);
+
+    // Часы приложения при этом не переставляются
+    assert!(app.get("/api/reports/overdue").await.json().as_array().unwrap().is_empty());
+}
+
+#[actix_web::test]
+async fn stats_report_as_of_given_moment() {
+    let app = TestApp::new();
+    tasks_due_in_october(&app).await;
+
+    let stats = app.get("/api/reports/stats").await.json();
+    assert_eq!(stats["total"], 2);
+    assert_eq!(stats["overdue"], 0);
+
+    let stats = app.get("/api/reports/stats?as_of=2026-10-12T09:00:00%2B03:00").await.json();
+    assert_eq!(stats["overdue"], 2);
+    assert_eq!(stats["overdue_days_total"], 9 + 2);
+    assert_eq!(stats["overdue_working_days_total"], 6 + 1);
+}
+
+#[actix_web::test]
+async fn invalid_as_of_is_rejected() {
+    let app = TestApp::new();
+
+    for uri in ["/api/reports/overdue?as_of=tomorrow", "/api/reports/stats?as_of=2026-10-12"] {
+        let res = app.get(uri).await;
+        assert_eq!(res.status, StatusCode::BAD_REQUEST, "{}", uri);
+        assert_eq!(res.code(), "invalid_query");
+    }
+}
@@ -0,0 +1,47 @@
+// Запуск приложения на базе SQLite, созданной первой версией схемы
+
+use rusqlite::Connection;
+
+use business_tasks::db;
+
+// Временная база с начальной схемой и тестовыми данными из migrations/001_initial.sql
+fn legacy_database(seed: &str) -> (std::path::PathBuf, String) {
+    let dir = std::env::temp_dir().join(format!("business-tasks-migrations-{}", uuid::Uuid::new_v4().simple()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("legacy.db");
+    let conn = Connection::open(&path).unwrap();
+    conn.execute_batch(include_str!("../migrations/001_initial.sql")).unwrap();
+    conn.execute_batch(seed).unwrap();
+    let url = path.to_str().unwrap().to_string();
+    (dir, url)
+}
+
+#[test]
+fn duplicate_tags_are_merged_on_startup() {
+    // Дубль с пробелами создан раньше тега "Отчет": его очищенное имя
+    // совпадает с именем тега, который удаляется при объединении
+    let (dir, url) = legacy_database(
+        "INSERT INTO tags (rowid, id, name) VALUES (0, 'tag_0', ' Отчет ');
+         INSERT INTO tags (id, name) VALUES ('tag_6', 'ОТЧЁТ');
+         INSERT INTO task_tags (task_id, tag_id) VALUES ('task_2', 'tag_0'), ('task_1', 'tag_6');",
+    );
+
+    let repo = db::connect(&url).unwrap();
+    let reports: Vec<_> = repo.get_tags().unwrap().into_iter().filter(|t| t.name.contains("тч")).collect();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].id, "tag_0");
+    assert_eq!(reports[0].name, "Отчет");
+
+    for task_id in ["task_1", "task_2"] {
+        let task = repo.get_task_by_id(task_id).unwrap();
+        let tags: Vec<_> = task.tags.iter().filter(|t| t.id == "tag_0").collect();
+        assert_eq!(tags.len(), 1, "{}", task_id);
+        assert!(!task.tags.iter().any(|t| t.id == "tag_2" || t.id == "tag_6"), "{}", task_id);
+    }
+
+    // Повторный запуск ничего не меняет
+    drop(repo);
+    let repo = db::connect(&url).unwrap();
+    assert_eq!(repo.get_tags().unwrap().len(), 5);
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,30 @@
+// Маршруты /api/tags
+
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use serde_json::json;
+
+use common::{id, TestApp};
+
+#[actix_web::test]
+async fn tag_name_length_is_limited() {
+    let app = TestApp::new();
+    let long = "т".repeat(51);
+
+    let res = app.post("/api/tags", json!({ "name": long })).await;
+    assert_eq!(res.status, StatusCode::UNPROCESSABLE_ENTITY, "{}", res.text());
+    assert_eq!(res.json()["errors"][0]["field"], "name");
+    assert_eq!(res.json()["errors"][0]["code"], "too_long");
+    assert_eq!(res.json()["errors"][0]["limit"], 50);
+
+    let res = app.post("/api/tags", json!({ "name": format!(" {} ", "т".repeat(50)) })).await;
+    assert_eq!(res.status, StatusCode::CREATED, "{}", res.text());
+    let tag = res.json();
+
+    let req = TestRequest::put().uri(&format!("/api/tags/{}", id(&tag))).set_json(json!({ "name": long }));
+    let res = app.call(req).await;
+    assert_eq!(res.status, StatusCode::UNPROCESSABLE_ENTITY, "{}", res.text());
+    assert_eq!(app.get("/api/tags").await.json()[0]["name"], "т".repeat(50));
+}
@@ -0,0 +1,460 @@
+// Маршруты /api/tasks
+
+mod common;
+
+use actix_web::http::StatusCode;
+use chrono::{DateTime, Duration, Utc};
+use serde_json::{json, Value};
+
+use business_tasks::api::ApiError;
+use business_tasks::clock::Clock;
+use business_tasks::models::TimeEntry;
+use common::{id, version, TestApp};
+
+// Пользователи для задачи: заказчик alice и исполнитель bob
+async fn customer_and_executor(app: &TestApp) -> (Value, Value) {
+    (app.create_user("alice", "customer").await, app.create_user("bob", "executor").await)
+}
+
+fn update_body(task: &Value) -> Value {
+    json!({
+        "title": task["title"],
+        "description": task["description"],
+        "status": "new",
+        "priority": "medium",
+        "customer_id": task["customer_id"],
+        "executor_id": task["executor_id"],
+        "tags": [],
+    })
+}
+
+fn utc(value: &str) -> DateTime<Utc> {
+    value.parse().unwrap()
+}
+
+#[actix_web::test]
+async fn create_and_get_task() {
+    let app = TestApp::new();
+    let (alice, bob) = customer_and_executor(&app).await;
+    let due = (app.clock.now() + Duration::days(5)).date_naive();
+
+    let task = app
+        .create_task(&alice, &bob, json!({ "priority": "high", "due_date": due.to_string(), "estimate_minutes": 120 }))
+        .await;
+    assert_eq!(task["status"], "New");
+    assert_eq!(task["priority"], "High");
+    assert_eq!(task["estimate_minutes"], 120);
+    // Срок-дата - конец дня в поясе исполнителя
+    assert_eq!(task["due_date_local"], format!("{} 23:59", due.format("%d.%m.%Y")));
+    assert!(task["overdue_days"].is_null());
+
+    let res = app.get(&format!("/api/tasks/{}", id(&task))).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.etag().as_deref(), Some("\"1\""));
+    assert_eq!(res.json()["title"], "Подготовить отчет");
+
+    let tasks = app.get("/api/tasks").await.json();
+    assert_eq!(tasks.as_array().unwrap().len(), 1);
+    assert_eq!(tasks[0]["time_zone"], "Europe/Moscow");
+}
+
+#[actix_web::test]
+async fn create_task_creates_tags_once() {
+    let app = TestApp::new();
+    let (alice, bob) = customer_and_executor(&app).await;
+
+    // Имена, различающиеся регистром, пробелами и ё/е, дают один тег
+    let first = app.create_task(&alice, &bob, json!({ "tags": ["Ёлка", " срочно", "СРОЧНО"] })).await;
+    let names: Vec<_> = first["tags"].as_array().unwrap().iter().map(|t| t["name"].clone()).collect();
+    assert_eq!(names, [json!("Ёлка"), json!("срочно")]);
+
+    let second = app.create_task(&alice, &bob, json!({ "tags": ["елка"] })).await;
+    assert_eq!(second["tags"][0]["id"], first["tags"][0]["id"]);
+    assert_eq!(app.repo().get_tags().unwrap().len(), 2);
+}
+
+#[actix_web::test]
+async fn create_task_rejects_invalid_input() {
+    let app = TestApp::new();
+    let (alice, bob) = customer_and_executor(&app).await;
+    let body = |extra: Value| {
+        let mut body = json!({
+            "title": "t", "description": "", "priority": "low",
+            "customer_id": id(&alice), "executor_id": id(&bob), "tags": [],
+        });
+        for (key, value) in extra.as_object().unwrap() {
+            body[key] = value.clone();
+        }
+        body
+    };
+
+    let res = app.post("/api/tasks", body(json!({ "priority": "urgent" }))).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.code(), "invalid_priority");
+
+    let res = app.post("/api/tasks", body(json!({ "customer_id": "missing" }))).await;
+    assert_eq!(res.status, StatusCode::NOT_FOUND);
+
+    let res = app.post("/api/tasks", body(json!({ "project_id": "missing" }))).await;
+    assert_eq!(res.status, StatusCode::NOT_FOUND);
+
+    let res = app.post("/api/tasks", body(json!({ "estimate_minutes": 0 }))).await;
+    assert_eq!(res.code(), "invalid_estimate");
+
+    let res = app.post("/api/tasks", body(json!({ "sla_days": u32::MAX }))).await;
+    assert_eq!(res.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(res.json()["errors"][0]["field"], "sla_days");
+    assert_eq!(res.json()["errors"][0]["code"], "too_large");
+
+    let res = app.post("/api/tasks", body(json!({ "title": "", "due_date": "2000-01-01" }))).await;
+    assert_eq!(res.status, StatusCode::UNPROCESSABLE_ENTITY);
+    let codes: Vec<_> = res.json()["errors"].as_array().unwrap().iter().map(|e| e["code"].clone()).collect();
+    assert_eq!(codes, ["required", "in_past"]);
+
+    let res = app.post("/api/tasks", json!({ "title": "t" })).await;
+    assert_eq!(res.code(), "invalid_json");
+
+    assert!(app.get("/api/tasks").await.json().as_array().unwrap().is_empty());
+}
+
+#[actix_web::test]
+async fn missing_task_is_not_found() {
+    let app = TestApp::new();
+    let (alice, bob) = customer_and_executor(&app).await;
+    let task = app.create_task(&alice, &bob, json!({})).await;
+    let participant = json!({ "user_id": id(&bob) });
+
+    for res in [
+        app.get("/api/tasks/missing").await,
+        app.put("/api/tasks/missing", 1, update_body(&task)).await,
+        app.patch("/api/tasks/missing", 1, json!({ "title": "x" })).await,
+        app.delete("/api/tasks/missing", 1).await,
+        app.post("/api/tasks/missing/co-executors", participant.clone()).await,
+        app.delete(&format!("/api/tasks/missing/co-executors/{}", id(&bob)), 1).await,
+        app.post("/api/tasks/missing/watchers", participant).await,
+        app.delete(&format!("/api/tasks/missing/watchers/{}", id(&bob)), 1).await,
+        app.post_versioned("/api/tasks/missing/reassign", 1, json!({ "to_user_id": id(&bob), "by_user_id": id(&alice), "reason": "r" })).await,
+        app.get("/api/tasks/missing/delegations").await,
+    ] {
+        assert_eq!(res.status, StatusCode::NOT_FOUND, "{}", res.text());
+    }
+}
+
+#[actix_web::test]
+async fn update_task_changes_status_and_tags() {
+    let app = TestApp::new();
+    let (alice, bob) = customer_and_executor(&app).await;
+    let task = app.create_task(&alice, &bob, json!({ "tags": ["backend"] })).await;
+    let uri = format!("/api/tasks/{}", id(&task));
+
+    let mut body = update_body(&task);
+    body["status"] = json!("done");
+    let res = app.put(&uri, 1, body.clone()).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.code(), "invalid_status");
+
+    body["status"] = json!("completed");
+    body["title"] = json!("Отчет сдан");
+    body["tags"] = json!(["frontend", "Backend"]);
+    let res = app.put(&uri, 1, body.clone()).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.text());
+    let updated = res.json();
+    assert_eq!(updated["status"], "Completed");
+    assert!(updated["completed_at"].is_string());
+    assert_eq!(updated["tags"].as_array().unwrap().len(), 2);
+    assert_eq!(res.etag().as_deref(), Some("\"2\""));
+
+    // Исполнитель меняется только передачей задачи
+    body["executor_id"] = alice["id"].clone();
+    let res = app.put(&uri, 2, body).await;
+    assert_eq!(res.code(), "executor_change_requires_reassign");
+
+    let res = app.put(&uri, 1, update_body(&task)).await;
+    assert_eq!(res.status, StatusCode::PRECONDITION_FAILED);
+}
+
+#[actix_web::test]
+async fn patch_task_changes_only_given_fields() {
+    let app = TestApp::new();
+    let (alice, bob) = customer_and_executor(&app).await;
+    let due = (app.clock.now() + Duration::days(3)).to_rfc3339();
+    let task = app.create_task(&alice, &bob, json!({ "due_date": due, "tags": ["docs"] })).await;
+    let uri = format!("/api/tasks/{}", id(&task));
+
+    let res = app.patch(&uri, 1, json!({ "status": "in_progress", "priority": "critical" })).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.text());
+    let patched = res.json();
+    assert_eq!(patched["status"], "InProgress");
+    assert_eq!(patched["priority"], "Critical");
+    assert_eq!(patched["title"], task["title"]);
+    assert_eq!(patched["tags"].as_array().unwrap().len(), 1);
+    assert!(patched["due_date"].is_string());
+
+    // null снимает срок
+    let res = app.patch(&uri, 2, json!({ "due_date": null })).await;
+    assert!(res.json()["due_date"].is_null());
+
+    let res = app.patch(&uri, 3, json!({ "priority": "someday" })).await;
+    assert_eq!(res.code(), "invalid_priority");
+    let res = app.patch(&uri, 3, json!({ "title": null })).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn delete_task_moves_it_to_trash() {
+    let app = TestApp::new();
+    let (alice, bob) = customer_and_executor(&app).await;
+    let task = app.create_task(&alice, &bob, json!({})).await;
+    let uri = format!("/api/tasks/{}", id(&task));
+
+    let res = app.call(actix_web::test::TestRequest::delete().uri(&uri)).await;
+    assert_eq!(res.status, StatusCode::PRECONDITION_REQUIRED);
+
+    app.clock.advance(Duration::hours(1));
+    assert_eq!(app.delete(&uri, 1).await.status, StatusCode::NO_CONTENT);
+    assert_eq!(app.get(&uri).await.status, StatusCode::NOT_FOUND);
+    let deleted = app.repo().get_deleted_tasks().unwrap();
+    assert_eq!(deleted.len(), 1);
+    // Время удаления берется из часов приложения
+    assert_eq!(deleted[0].deleted_at, Some(app.clock.now()));
+}
+
+#[actix_web::test]
+async fn participants() {
+    let app = TestApp::new();
+    let (alice, bob) = customer_and_executor(&app).await;
+    let carol = app.create_user("carol", "executor").await;
+    let task = app.create_task(&alice, &bob, json!({})).await;
+    let uri = format!("/api/tasks/{}", id(&task));
+
+    let add = |kind: &str, version: i64, user: &str| {
+        let (app, uri, body) = (&app, format!("{}/{}", uri, kind), json!({ "user_id": user }));
+        async move { app.post_versioned(&uri, version, body).await }
+    };
+    // Участники входят в версию задачи, поэтому изменения требуют If-Match
+    let res = app.post(&format!("{}/co-executors", uri), json!({ "user_id": id(&carol) })).await;
+    assert_eq!(res.status, StatusCode::PRECONDITION_REQUIRED);
+    let res = app.call(actix_web::test::TestRequest::delete().uri(&format!("{}/watchers/{}", uri, id(&alice)))).await;
+    assert_eq!(res.status, StatusCode::PRECONDITION_REQUIRED);
+
+    let res = add("co-executors", 1, &id(&carol)).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.text());
+    let task = res.json();
+    assert_eq!(task["co_executor_ids"], json!([id(&carol)]));
+    assert_eq!(version(&task), 2);
+    assert_eq!(add("watchers", 1, &id(&alice)).await.status, StatusCode::PRECONDITION_FAILED);
+
+    let res = add("co-executors", 2, &id(&bob)).await;
+    assert_eq!(res.code(), "co_executor_is_executor");
+    let res = add("watchers", 2, "missing").await;
+    assert_eq!(res.status, StatusCode::NOT_FOUND);
+
+    let res = add("watchers", 2, &id(&alice)).await;
+    assert_eq!(res.json()["watcher_ids"], json!([id(&alice)]));
+
+    let res = app.delete(&format!("{}/watchers/{}", uri, id(&alice)), 2).await;
+    assert_eq!(res.status, StatusCode::PRECONDITION_FAILED);
+    let res = app.delete(&format!("{}/watchers/{}", uri, id(&alice)), 3).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.json()["watcher_ids"], json!([]));
+    let res = app.delete(&format!("{}/watchers/{}", uri, id(&alice)), 4).await;
+    assert_eq!(res.status, StatusCode::NOT_FOUND);
+
+    let res = app.delete(&format!("{}/co-executors/{}", uri, id(&carol)), 4).await;
+    assert_eq!(res.json()["co_executor_ids"], json!([]));
+    assert_eq!(version(&res.json()), 5);
+}
+
+#[actix_web::test]
+async fn reassign_task_records_delegation() {
+    let app = TestApp::new();
+    let (alice, bob) = customer_and_executor(&app).await;
+    let carol = app.create_user("carol", "executor").await;
+    let task = app.create_task(&alice, &bob, json!({})).await;
+    let uri = format!("/api/tasks/{}", id(&task));
+    // Новый соисполнитель меняет версию задачи
+    let task = app.post_versioned(&format!("{}/co-executors", uri), 1, json!({ "user_id": id(&carol) })).await.json();
+    assert_eq!(version(&task), 2);
+
+    let reassign = |by: &Value, reason: &str| json!({ "to_user_id": id(&carol), "by_user_id": id(by), "reason": reason });
+    let res = app.post_versioned(&format!("{}/reassign", uri), 1, reassign(&bob, "Отпуск")).await;
+    assert_eq!(res.status, StatusCode::PRECONDITION_FAILED);
+    let res = app.post_versioned(&format!("{}/reassign", uri), 2, reassign(&bob, " ")).await;
+    assert_eq!(res.code(), "reassign_reason_required");
+    let res = app.post_versioned(&format!("{}/reassign", uri), 2, reassign(&carol, "Отпуск")).await;
+    assert_eq!(res.code(), "reassign_not_allowed");
+
+    let res = app.post_versioned(&format!("{}/reassign", uri), 2, reassign(&bob, "Отпуск")).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.text());
+    let reassigned = res.json();
+    assert_eq!(reassigned["executor_id"], id(&carol));
+    assert_eq!(reassigned["co_executor_ids"], json!([]));
+    assert_eq!(version(&reassigned), 3);
+
+    let delegations = app.get(&format!("{}/delegations", uri)).await.json();
+    assert_eq!(delegations.as_array().unwrap().len(), 1);
+    assert_eq!(delegations[0]["from_user_id"], id(&bob));
+    assert_eq!(delegations[0]["by_user_id"], id(&bob));
+    assert_eq!(delegations[0]["reason"], "Отпуск");
+}
+
+#[actix_web::test]
+async fn overdue_is_computed_from_due_date() {
+    let app = TestApp::new();
+    let (alice, bob) = customer_and_executor(&app).await;
+    // Четверг; сроки-даты истекают в 23:59 по Москве
+    app.clock.set(utc("2026-10-01T09:00:00Z"));
+
+    let late = app.create_task(&alice, &bob, json!({ "title": "late", "due_date": "2026-10-02" })).await;
+    app.create_task(&alice, &bob, json!({ "title": "upcoming", "due_date": "2026-10-09" })).await;
+    let done = app.create_task(&alice, &bob, json!({ "title": "done", "due_date": "2026-10-02" })).await;
+    app.patch(&format!("/api/tasks/{}", id(&done)), 1, json!({ "status": "completed" })).await;
+    assert!(late["overdue_days"].is_null());
+
+    // Среда следующей недели: прошли суббота, воскресенье и три рабочих дня
+    app.clock.set(utc("2026-10-07T09:00:00Z"));
+    let late = app.get(&format!("/api/tasks/{}", id(&late))).await.json();
+    assert_eq!(late["overdue_days"], 4);
+    assert_eq!(late["overdue_working_days"], 3);
+
+    // Завершенная задача не просрочена
+    let done = app.get(&format!("/api/tasks/{}", id(&done))).await.json();
+    assert!(done["overdue_days"].is_null());
+    assert!(done["overdue_working_days"].is_null());
+
+    let tasks = app.get("/api/tasks").await.json();
+    let overdue: Vec<_> = tasks.as_array().unwrap().iter().filter(|t| !t["overdue_days"].is_null()).collect();
+    assert_eq!(overdue.len(), 1);
+
+    app.clock.advance(Duration::days(7));
+    let tasks = app.get("/api/tasks").await.json();
+    let overdue: Vec<_> = tasks.as_array().unwrap().iter().filter(|t| !t["overdue_days"].is_null()).collect();
+    assert_eq!(overdue.len(), 2);
+}
+
+#[actix_web::test]
+async fn bulk_due_date_is_checked_for_each_task() {
+    let app = TestApp::new();
+    let (alice, bob) = customer_and_executor(&app).await;
+    let res = app
+        .post("/api/users", json!({ "name": "carol", "email": "carol@example.com", "role": "executor", "time_zone": "America/New_York" }))
+        .await;
+    let carol = res.json();
+    let moscow = app.create_task(&alice, &bob, json!({})).await;
+    let new_york = app.create_task(&alice, &carol, json!({})).await;
+    let ids = json!([id(&moscow), id(&new_york)]);
+
+    // В Москве 1 октября уже закончилось, в Нью-Йорке еще нет
+    app.clock.set(utc("2026-10-01T22:00:00Z"));
+    let body = |due: &str| json!({ "ids": ids, "operation": { "type": "set_due_date", "due_date": due } });
+    let res = app.post("/api/tasks/bulk", body("2026-10-01")).await;
+    assert_eq!(res.status, StatusCode::CONFLICT, "{}", res.text());
+    let results = res.json()["results"].clone();
+    assert_eq!(results[0]["ok"], false);
+    assert!(results[0]["error"].as_str().unwrap().contains("Срок не может быть в прошлом"));
+    assert_eq!(results[1]["ok"], true);
+    assert!(app.get(&format!("/api/tasks/{}", id(&new_york))).await.json()["due_date"].is_null());
+
+    let res = app.post("/api/tasks/bulk", body("2026-10-02")).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.text());
+}
+
+#[actix_web::test]
+async fn time_entry_duration_is_limited() {
+    let app = TestApp::new();
+    let (alice, bob) = customer_and_executor(&app).await;
+    let task = app.create_task(&alice, &bob, json!({})).await;
+    let uri = format!("/api/tasks/{}/time-entries", id(&task));
+    let codes = |res: &common::Response| -> Vec<Value> {
+        res.json()["errors"].as_array().unwrap().iter().map(|e| e["code"].clone()).collect()
+    };
+
+    let res = app.post(&uri, json!({ "user_id": id(&bob), "minutes": i64::MAX })).await;
+    assert_eq!(res.status, StatusCode::UNPROCESSABLE_ENTITY, "{}", res.text());
+    assert_eq!(codes(&res), ["too_large"]);
+
+    let res = app
+        .post(&uri, json!({ "user_id": id(&bob), "started_at": "2026-03-01T09:00:00Z", "ended_at": "2026-03-03T09:00:00Z" }))
+        .await;
+    assert_eq!(codes(&res), ["too_large"]);
+
+    let res = app.post(&uri, json!({ "user_id": id(&bob), "minutes": 30, "note": "x".repeat(1001) })).await;
+    assert_eq!(codes(&res), ["too_long"]);
+
+    let res = app.post(&uri, json!({ "user_id": id(&bob), "minutes": 0 })).await;
+    assert_eq!(res.code(), "invalid_time_entry");
+
+    let res = app.post(&uri, json!({ "user_id": id(&bob), "minutes": 24 * 60 })).await;
+    assert_eq!(res.status, StatusCode::CREATED, "{}", res.text());
+    assert_eq!(res.json()["minutes"], 24 * 60);
+}
+
+#[actix_web::test]
+async fn time_entries_change_task_version() {
+    let app = TestApp::new();
+    let (alice, bob) = customer_and_executor(&app).await;
+    let task = app.create_task(&alice, &bob, json!({})).await;
+    let uri = format!("/api/tasks/{}", id(&task));
+    let task_version = || async { version(&app.get(&uri).await.json()) };
+
+    let res = app.post(&format!("{}/timer/start", uri), json!({ "user_id": id(&bob) })).await;
+    assert_eq!(res.status, StatusCode::CREATED, "{}", res.text());
+    assert_eq!(task_version().await, 2);
+    let res = app.post(&format!("{}/timer/start", uri), json!({ "user_id": id(&bob) })).await;
+    assert_eq!(res.status, StatusCode::CONFLICT);
+    assert_eq!(res.code(), "timer_already_running");
+
+    // Одновременный запуск, прошедший проверку в обработчике, отклоняет индекс базы
+    let racing = TimeEntry::start(id(&task), id(&bob), app.clock.now(), String::new());
+    let err = ApiError::from(app.repo().create_time_entry(&racing).unwrap_err());
+    assert!(matches!(&err, ApiError::Conflict(message) if message == "errors.timer_already_running"), "{:?}", err);
+    assert_eq!(task_version().await, 2);
+
+    app.clock.advance(Duration::minutes(30));
+    let res = app.post(&format!("{}/timer/stop", uri), json!({ "user_id": id(&bob) })).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.text());
+    let task = app.get(&uri).await.json();
+    assert_eq!((task["spent_minutes"].clone(), version(&task)), (json!(30), 3));
+
+    let res = app.call(actix_web::test::TestRequest::delete().uri(&format!("/api/time-entries/{}", id(&res.json())))).await;
+    assert_eq!(res.status, StatusCode::NO_CONTENT, "{}", res.text());
+    let task = app.get(&uri).await.json();
+    assert_eq!((task["spent_minutes"].clone(), version(&task)), (json!(0), 4));
+}
+
+#[actix_web::test]
+async fn cancel_records_cancellation_time() {
+    let app = TestApp::new();
+    let (alice, bob) = customer_and_executor(&app).await;
+    let task = app.create_task(&alice, &bob, json!({})).await;
+    assert!(task["cancelled_at"].is_null());
+
+    app.clock.advance(Duration::days(3));
+    let res = app.patch(&format!("/api/tasks/{}", id(&task)), 1, json!({ "status": "cancelled" })).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.text());
+    assert_eq!(utc(res.json()["cancelled_at"].as_str().unwrap()), app.clock.now());
+    assert!(res.json()["completed_at"].is_null());
+}
+
+#[actix_web::test]
+async fn bulk_add_tags_keeps_tag_limit_per_task() {
+    let app = TestApp::new();
+    let (alice, bob) = customer_and_executor(&app).await;
+    let names: Vec<String> = (1..=19).map(|i| format!("tag{}", i)).collect();
+    let full = app.create_task(&alice, &bob, json!({ "tags": names })).await;
+    let empty = app.create_task(&alice, &bob, json!({})).await;
+    let body = |tags: Value| json!({ "ids": [id(&full), id(&empty)], "operation": { "type": "add_tags", "tags": tags } });
+
+    let res = app.post("/api/tasks/bulk", body(json!(["extra1", "extra2"]))).await;
+    assert_eq!(res.status, StatusCode::CONFLICT, "{}", res.text());
+    let results = res.json()["results"].clone();
+    assert_eq!(results[0]["ok"], false);
+    assert!(results[0]["error"].as_str().unwrap().contains("Не больше 20 значений"));
+    assert_eq!(results[1]["ok"], true);
+    assert!(app.get(&format!("/api/tasks/{}", id(&empty))).await.json()["tags"].as_array().unwrap().is_empty());
+
+    // Уже имеющийся тег (с точностью до регистра) места не занимает
+    let res = app.post("/api/tasks/bulk", body(json!(["TAG1", "extra1"]))).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.text());
+    assert_eq!(app.get(&format!("/api/tasks/{}", id(&full))).await.json()["tags"].as_array().unwrap().len(), 20);
+}
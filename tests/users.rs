@@ -0,0 +1,300 @@
+// Маршруты /api/users
+
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use serde_json::json;
+
+use common::{id, version, TestApp};
+
+#[actix_web::test]
+async fn create_and_get_user() {
+    let app = TestApp::new();
+    let alice = app.create_user("alice", "customer").await;
+    assert_eq!(alice["role"], "Customer");
+    assert_eq!(alice["time_zone"], "Europe/Moscow");
+    assert_eq!(alice["active"], true);
+
+    let res = app.get(&format!("/api/users/{}", id(&alice))).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.etag().as_deref(), Some("\"1\""));
+    assert_eq!(res.json()["email"], "alice@example.com");
+
+    let res = app.get("/api/users").await;
+    assert_eq!(res.json().as_array().unwrap().len(), 1);
+}
+
+#[actix_web::test]
+async fn create_user_rejects_invalid_input() {
+    let app = TestApp::new();
+    app.create_user("alice", "customer").await;
+
+    let res = app.post("/api/users", json!({ "name": "Bob", "email": "bob@example.com", "role": "admin" })).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.code(), "invalid_role");
+
+    let res = app.post("/api/users", json!({ "name": "Alice", "email": "alice@example.com", "role": "executor" })).await;
+    assert_eq!(res.status, StatusCode::CONFLICT);
+    assert_eq!(res.code(), "email_taken");
+
+    let res = app
+        .post("/api/users", json!({ "name": "Bob", "email": "bob@example.com", "role": "executor", "time_zone": "Mars/Olympus" }))
+        .await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.code(), "invalid_time_zone");
+
+    let res = app.post("/api/users", json!({ "name": "", "email": "not an email", "role": "executor" })).await;
+    assert_eq!(res.status, StatusCode::UNPROCESSABLE_ENTITY);
+    let fields: Vec<_> = res.json()["errors"].as_array().unwrap().iter().map(|e| e["field"].clone()).collect();
+    assert_eq!(fields, ["name", "email"]);
+}
+
+#[actix_web::test]
+async fn missing_user_is_not_found() {
+    let app = TestApp::new();
+
+    for res in [
+        app.get("/api/users/missing").await,
+        app.get("/api/users/missing/tasks").await,
+        app.put("/api/users/missing", 1, json!({ "name": "X", "email": "x@example.com", "role": "customer" })).await,
+        app.patch("/api/users/missing", 1, json!({ "name": "X" })).await,
+        app.delete("/api/users/missing", 1).await,
+        app.post_versioned("/api/users/missing/deactivate", 1, json!({})).await,
+        app.post_versioned("/api/users/missing/reactivate", 1, json!({})).await,
+    ] {
+        assert_eq!(res.status, StatusCode::NOT_FOUND, "{}", res.text());
+        assert_eq!(res.code(), "not_found");
+    }
+
+    // Сообщение берется из каталога с подстановкой идентификатора
+    assert_eq!(app.get("/api/users/missing?lang=en").await.json()["error"], "Not found: User missing not found");
+    assert_eq!(app.get("/api/users/missing?lang=ru").await.json()["error"], "Не найдено: Пользователь missing не найден");
+}
+
+#[actix_web::test]
+async fn update_user_requires_current_version() {
+    let app = TestApp::new();
+    let alice = app.create_user("alice", "customer").await;
+    let uri = format!("/api/users/{}", id(&alice));
+    let body = json!({ "name": "Alice", "email": "alice@example.org", "role": "executor", "time_zone": "Asia/Yekaterinburg" });
+
+    let res = app.call(actix_web::test::TestRequest::put().uri(&uri).set_json(&body)).await;
+    assert_eq!(res.status, StatusCode::PRECONDITION_REQUIRED);
+
+    let res = app.put(&uri, version(&alice), body.clone()).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.text());
+    let updated = res.json();
+    assert_eq!(updated["role"], "Executor");
+    assert_eq!(updated["time_zone"], "Asia/Yekaterinburg");
+    assert_eq!(version(&updated), 2);
+
+    // Старая версия отклоняется, в ответе текущая
+    let res = app.put(&uri, version(&alice), body).await;
+    assert_eq!(res.status, StatusCode::PRECONDITION_FAILED);
+    assert_eq!(res.json()["current_version"], 2);
+}
+
+#[actix_web::test]
+async fn patch_user_changes_only_given_fields() {
+    let app = TestApp::new();
+    let alice = app.create_user("alice", "customer").await;
+    let uri = format!("/api/users/{}", id(&alice));
+
+    let res = app.patch(&uri, 1, json!({ "time_zone": "Europe/Samara" })).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.text());
+    assert_eq!(res.json()["time_zone"], "Europe/Samara");
+    assert_eq!(res.json()["name"], "alice");
+
+    // null возвращает часовой пояс по умолчанию
+    let res = app.patch(&uri, 2, json!({ "time_zone": null, "name": "Алиса" })).await;
+    let patched = res.json();
+    assert_eq!(patched["time_zone"], "Europe/Moscow");
+    assert_eq!(patched["name"], "Алиса");
+
+    let res = app.patch(&uri, 3, json!({ "role": "manager" })).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.code(), "invalid_role");
+}
+
+#[actix_web::test]
+async fn delete_user_without_tasks() {
+    let app = TestApp::new();
+    let alice = app.create_user("alice", "customer").await;
+    let uri = format!("/api/users/{}", id(&alice));
+
+    let res = app.delete(&uri, 1).await;
+    assert_eq!(res.status, StatusCode::NO_CONTENT);
+    assert_eq!(app.get(&uri).await.status, StatusCode::NOT_FOUND);
+    assert!(app.get("/api/users").await.json().as_array().unwrap().is_empty());
+}
+
+#[actix_web::test]
+async fn delete_user_with_tasks_requires_handover() {
+    let app = TestApp::new();
+    let alice = app.create_user("alice", "customer").await;
+    let bob = app.create_user("bob", "executor").await;
+    let carol = app.create_user("carol", "executor").await;
+    let task = app.create_task(&alice, &bob, json!({})).await;
+    let uri = format!("/api/users/{}", id(&bob));
+
+    let res = app.delete(&uri, 1).await;
+    assert_eq!(res.status, StatusCode::CONFLICT);
+    assert_eq!(res.code(), "user_has_tasks");
+
+    let res = app.delete(&format!("{}?on_tasks=reassign", uri), 1).await;
+    assert_eq!(res.code(), "handover_target_required");
+    let res = app.delete(&format!("{}?on_tasks=reassign&to_user_id={}", uri, id(&alice)), 1).await;
+    assert_eq!(res.code(), "handover_role_mismatch");
+    let res = app.delete(&format!("{}?on_tasks=archive", uri), 1).await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.code(), "invalid_query");
+
+    let handover = format!("{}?on_tasks=reassign&to_user_id={}&reason=vacation", uri, id(&carol));
+    assert_eq!(app.delete(&handover, 1).await.code(), "handover_actor_required");
+    let res = app.delete(&format!("{}&by_user_id={}", handover, id(&bob)), 1).await;
+    assert_eq!(res.code(), "handover_actor_deleted");
+    let res = app.delete(&format!("{}&by_user_id=missing", handover), 1).await;
+    assert_eq!(res.status, StatusCode::NOT_FOUND);
+
+    let res = app.delete(&format!("{}&by_user_id={}", handover, id(&alice)), 1).await;
+    assert_eq!(res.status, StatusCode::NO_CONTENT, "{}", res.text());
+
+    let task = app.get(&format!("/api/tasks/{}", id(&task))).await.json();
+    assert_eq!(task["executor_id"], id(&carol));
+    let delegations = app.get(&format!("/api/tasks/{}/delegations", id(&task))).await.json();
+    assert_eq!(delegations[0]["reason"], "vacation");
+    // В истории передач - тот, кто удалял пользователя, а не сам удаленный
+    assert_eq!(delegations[0]["from_user_id"], id(&bob));
+    assert_eq!(delegations[0]["by_user_id"], id(&alice));
+}
+
+#[actix_web::test]
+async fn delete_user_referenced_by_trash_or_projects() {
+    let app = TestApp::new();
+    let alice = app.create_user("alice", "customer").await;
+    let bob = app.create_user("bob", "executor").await;
+    let task = app.create_task(&alice, &bob, json!({})).await;
+    assert_eq!(app.delete(&format!("/api/tasks/{}", id(&task)), 1).await.status, StatusCode::NO_CONTENT);
+
+    // Поручение в корзине не передается, поэтому удаление не проходит и с передачей
+    for uri in [
+        format!("/api/users/{}", id(&bob)),
+        format!("/api/users/{}?on_tasks=reassign&to_user_id={}&by_user_id={}", id(&alice), id(&bob), id(&bob)),
+    ] {
+        let res = app.delete(&uri, 1).await;
+        assert_eq!(res.status, StatusCode::CONFLICT);
+        assert_eq!(res.code(), "user_has_deleted_tasks");
+    }
+
+    let carol = app.create_user("carol", "customer").await;
+    let res = app
+        .post("/api/projects", json!({ "name": "Проект", "owner_id": id(&carol), "customer_id": id(&alice) }))
+        .await;
+    assert_eq!(res.status, StatusCode::CREATED, "{}", res.text());
+    let project = res.json();
+    let res = app.delete(&format!("/api/users/{}", id(&carol)), 1).await;
+    assert_eq!(res.status, StatusCode::CONFLICT);
+    assert_eq!(res.code(), "user_has_projects");
+
+    assert_eq!(app.delete(&format!("/api/projects/{}", id(&project)), 1).await.status, StatusCode::NO_CONTENT);
+    assert_eq!(app.delete(&format!("/api/users/{}", id(&carol)), 1).await.status, StatusCode::NO_CONTENT);
+}
+
+#[actix_web::test]
+async fn deactivate_and_reactivate_user() {
+    let app = TestApp::new();
+    let alice = app.create_user("alice", "customer").await;
+    let bob = app.create_user("bob", "executor").await;
+    app.create_task(&alice, &bob, json!({})).await;
+
+    // Удаление с on_tasks=deactivate оставляет пользователя у его задач
+    let res = app.delete(&format!("/api/users/{}?on_tasks=deactivate", id(&bob)), 1).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.text());
+    let deactivated = res.json();
+    assert_eq!(deactivated["active"], false);
+    assert!(deactivated["deactivated_at"].is_string());
+
+    let active = app.get("/api/users?active=true").await.json();
+    assert_eq!(active.as_array().unwrap().len(), 1);
+    let inactive = app.get("/api/users?active=false").await.json();
+    assert_eq!(inactive[0]["id"], id(&bob));
+    assert_eq!(app.get("/api/users?active=maybe").await.code(), "invalid_query");
+
+    // Деактивированного пользователя нельзя назначить исполнителем
+    let res = app
+        .post("/api/tasks", json!({
+            "title": "t", "description": "", "priority": "low",
+            "customer_id": id(&alice), "executor_id": id(&bob), "tags": [],
+        }))
+        .await;
+    assert_eq!(res.status, StatusCode::BAD_REQUEST);
+    assert_eq!(res.code(), "user_inactive");
+
+    let uri = format!("/api/users/{}/reactivate", id(&bob));
+    let res = app.post_versioned(&uri, version(&deactivated), json!({})).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.json()["active"], true);
+    assert!(res.json()["deactivated_at"].is_null());
+
+    let uri = format!("/api/users/{}/deactivate", id(&bob));
+    assert_eq!(app.post_versioned(&uri, 1, json!({})).await.status, StatusCode::PRECONDITION_FAILED);
+    let res = app.post_versioned(&uri, version(&deactivated) + 1, json!({})).await;
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.json()["active"], false);
+}
+
+#[actix_web::test]
+async fn user_tasks_list_roles() {
+    let app = TestApp::new();
+    let alice = app.create_user("alice", "customer").await;
+    let bob = app.create_user("bob", "executor").await;
+    let carol = app.create_user("carol", "executor").await;
+    let task = app.create_task(&alice, &bob, json!({})).await;
+    app.create_task(&alice, &carol, json!({ "title": "Другая задача" })).await;
+    app.post_versioned(&format!("/api/tasks/{}/watchers", id(&task)), 1, json!({ "user_id": id(&carol) })).await;
+
+    let tasks = app.get(&format!("/api/users/{}/tasks", id(&alice))).await.json();
+    assert_eq!(tasks.as_array().unwrap().len(), 2);
+
+    let tasks = app.get(&format!("/api/users/{}/tasks", id(&carol))).await.json();
+    let roles: Vec<_> = tasks.as_array().unwrap().iter().map(|t| t["roles"].clone()).collect();
+    assert!(roles.contains(&json!(["watcher"])));
+    assert!(roles.contains(&json!(["executor"])));
+
+    let tasks = app.get(&format!("/api/users/{}/tasks", id(&bob))).await.json();
+    assert_eq!(tasks[0]["id"], id(&task));
+    assert_eq!(tasks[0]["roles"], json!(["executor"]));
+}
+
+#[actix_web::test]
+async fn user_locale_is_used_for_messages() {
+    let app = TestApp::new();
+    let res = app
+        .post("/api/users", json!({ "name": "Bob", "email": "bob@example.com", "role": "executor", "locale": "en-GB" }))
+        .await;
+    assert_eq!(res.status, StatusCode::CREATED, "{}", res.text());
+    let bob = res.json();
+    assert_eq!(bob["locale"], "en");
+
+    let missing = |lang: Option<&str>| {
+        let uri = match lang {
+            Some(lang) => format!("/api/tasks/missing?lang={}", lang),
+            None => "/api/tasks/missing".to_string(),
+        };
+        TestRequest::get()
+            .uri(&uri)
+            .insert_header(("X-User-Id", id(&bob)))
+            .insert_header(("Accept-Language", "ru"))
+    };
+    // Настройка пользователя важнее Accept-Language, но уступает явному выбору
+    assert_eq!(app.call(missing(None)).await.json()["error"], "Not found: Task missing not found");
+    assert_eq!(app.call(missing(Some("ru"))).await.json()["error"], "Не найдено: Поручение missing не найдено");
+
+    let uri = format!("/api/users/{}", id(&bob));
+    assert_eq!(app.patch(&uri, 1, json!({ "locale": "de" })).await.code(), "invalid_locale");
+    let res = app.patch(&uri, 1, json!({ "locale": null })).await;
+    assert_eq!(res.status, StatusCode::OK, "{}", res.text());
+    assert!(res.json()["locale"].is_null());
+    assert_eq!(app.call(missing(None)).await.json()["error"], "Не найдено: Поручение missing не найдено");
+}